@@ -12,6 +12,10 @@ pub struct CliArgs {
     /// Path to collection file
     #[arg(short, long)]
     pub collection: Option<PathBuf>,
+
+    /// Environment to load from .netbook/.env.<name>
+    #[arg(short, long)]
+    pub env: Option<String>,
 }
 
 #[derive(Parser)]
@@ -20,44 +24,224 @@ pub enum Commands {
     Open {
         /// Collection file path (optional, will auto-discover)
         collection: Option<PathBuf>,
+        /// Environment to load from .netbook/.env.<name>
+        #[arg(short, long)]
+        env: Option<String>,
     },
     /// Initialize a new collection in the current directory
     Init,
     /// Run request in headless mode
     #[command(name = "run")]
     HeadlessRun {
-        /// Name of request to run
+        /// Name or id of request to run
         name: String,
         /// Collection file path (optional, will auto-discover)
         #[arg(short, long)]
         collection: Option<PathBuf>,
+        /// Environment to load from .netbook/.env.<name>
+        #[arg(short, long)]
+        env: Option<String>,
     },
-    /// Export last response
+    /// Export last response, or history entries as HAR when the path ends in .har
     Export {
         /// Export file path
         path: PathBuf,
+        /// Only export history entries for this request name or id (HAR only)
+        #[arg(long)]
+        request: Option<String>,
+        /// Export at most this many of the latest history entries (HAR only)
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Collection file path (optional, will auto-discover)
+        #[arg(short, long)]
+        collection: Option<PathBuf>,
+    },
+    /// Compare two history entries: status, headers and a structural diff
+    /// of the JSON bodies
+    Diff {
+        /// Id (or the start of it) of the old entry
+        old: String,
+        /// Id (or the start of it) of the new entry
+        new: String,
+        /// Collection file path (optional, will auto-discover)
+        #[arg(short, long)]
+        collection: Option<PathBuf>,
+    },
+    /// Print a request as ready-to-paste client code
+    Codegen {
+        /// Name or id of request to render
+        name: String,
+        /// Target language or tool
+        #[arg(short, long, value_enum, default_value = "curl")]
+        lang: crate::core::CodeLanguage,
+        /// Collection file path (optional, will auto-discover)
+        #[arg(short, long)]
+        collection: Option<PathBuf>,
+        /// Environment to load from .netbook/.env.<name>
+        #[arg(short, long)]
+        env: Option<String>,
+    },
+    /// Check a collection for errors, duplicate names, unknown fields and
+    /// undefined variables
+    Validate {
+        /// Collection file path (optional, will auto-discover)
+        collection: Option<PathBuf>,
+    },
+    /// Import requests into the collection
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+}
+
+#[derive(Parser)]
+pub enum ImportSource {
+    /// Import requests captured in a HAR file (e.g. from browser devtools)
+    Har {
+        /// HAR file path
+        path: PathBuf,
+        /// Only import requests to this host or its subdomains (repeatable)
+        #[arg(long)]
+        host: Vec<String>,
+        /// Only import requests with this HTTP method (repeatable)
+        #[arg(long)]
+        method: Vec<String>,
+        /// Collection file path (optional, will auto-discover)
+        #[arg(short, long)]
+        collection: Option<PathBuf>,
+    },
+    /// Import an Insomnia v4 JSON export, including folders and environments
+    Insomnia {
+        /// Insomnia export file path
+        path: PathBuf,
+        /// Collection file path (optional, will auto-discover)
+        #[arg(short, long)]
+        collection: Option<PathBuf>,
+    },
+    /// Import a Bruno collection directory, including folders and environments
+    Bruno {
+        /// Bruno collection directory (the one containing bruno.json)
+        path: PathBuf,
+        /// Collection file path (optional, will auto-discover)
+        #[arg(short, long)]
+        collection: Option<PathBuf>,
     },
 }
 
 pub async fn run_cli(args: CliArgs) -> Result<()> {
     match args.command {
-        Some(Commands::Open { collection }) => {
+        Some(Commands::Open { collection, env }) => {
             let collection_path = resolve_collection(collection)?;
-            crate::tui::run_tui(collection_path).await
+            crate::tui::run_tui(collection_path, env.or(args.env)).await
         }
         Some(Commands::Init) => init_collection().await,
-        Some(Commands::HeadlessRun { name, collection }) => {
+        Some(Commands::HeadlessRun {
+            name,
+            collection,
+            env,
+        }) => {
+            let collection_path = resolve_collection(collection)?;
+            crate::core::run_headless(&name, &collection_path, env.or(args.env).as_deref()).await
+        }
+        Some(Commands::Export {
+            path,
+            request,
+            limit,
+            collection,
+        }) => {
+            let collection_path = resolve_collection(collection)?;
+            if path.extension().and_then(|s| s.to_str()) == Some("har") {
+                crate::io::export_history_har(&path, &collection_path, request.as_deref(), limit)
+                    .await
+            } else {
+                crate::io::export_last_response(&path, &collection_path).await
+            }
+        }
+        Some(Commands::Diff {
+            old,
+            new,
+            collection,
+        }) => {
+            let collection_path = resolve_collection(collection)?;
+            crate::io::print_history_diff(&collection_path, &old, &new).await
+        }
+        Some(Commands::Codegen {
+            name,
+            lang,
+            collection,
+            env,
+        }) => {
             let collection_path = resolve_collection(collection)?;
-            crate::core::run_headless(&name, &collection_path).await
+            crate::core::print_request_code(
+                &name,
+                &collection_path,
+                env.or(args.env).as_deref(),
+                lang,
+            )
+            .await
         }
-        Some(Commands::Export { path }) => crate::io::export_last_response(&path).await,
+        Some(Commands::Validate { collection }) => run_validate(collection).await,
+        Some(Commands::Import { source }) => run_import(source),
         None => {
             let collection_path = resolve_collection(args.collection)?;
-            crate::tui::run_tui(collection_path).await
+            crate::tui::run_tui(collection_path, args.env).await
         }
     }
 }
 
+async fn run_validate(collection: Option<PathBuf>) -> Result<()> {
+    let collection_path = match collection {
+        Some(path) => path,
+        None => crate::io::discover_collection()?,
+    };
+    let defined = crate::io::defined_variables(&collection_path).await?;
+    let report = crate::io::validate_collection(&collection_path, &defined)?;
+    print!("{}", report);
+
+    if report.errors() > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn run_import(source: ImportSource) -> Result<()> {
+    let (result, collection) = match source {
+        ImportSource::Har {
+            path,
+            host,
+            method,
+            collection,
+        } => {
+            let content = std::fs::read_to_string(&path)?;
+            let filter = crate::io::HarFilter {
+                hosts: host,
+                methods: method,
+            };
+            (crate::io::import_har(&content, &filter)?, collection)
+        }
+        ImportSource::Insomnia { path, collection } => {
+            let content = std::fs::read_to_string(&path)?;
+            (crate::io::import_insomnia(&content)?, collection)
+        }
+        ImportSource::Bruno { path, collection } => (crate::io::import_bruno(&path)?, collection),
+    };
+
+    let collection_path = resolve_import_target(collection)?;
+    let report = crate::io::import_into_collection(&collection_path, result)?;
+    print!("{}", report);
+    println!("Collection: {}", collection_path.display());
+    Ok(())
+}
+
+/// Import targets the given or discovered collection without seeding it with
+/// example requests when it does not exist yet
+fn resolve_import_target(collection: Option<PathBuf>) -> Result<PathBuf> {
+    match collection {
+        Some(path) => Ok(path),
+        None => crate::io::discover_collection(),
+    }
+}
+
 fn resolve_collection(collection: Option<PathBuf>) -> Result<PathBuf> {
     if let Some(path) = collection {
         Ok(path)
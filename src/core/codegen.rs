@@ -0,0 +1,486 @@
+use crate::core::{HttpMethod, Request, RequestBody};
+
+/// Target for rendering a request as client code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CodeLanguage {
+    Curl,
+    Rust,
+    Python,
+    Js,
+    Go,
+    Httpie,
+    Wget,
+}
+
+impl CodeLanguage {
+    pub const ALL: [CodeLanguage; 7] = [
+        CodeLanguage::Curl,
+        CodeLanguage::Rust,
+        CodeLanguage::Python,
+        CodeLanguage::Js,
+        CodeLanguage::Go,
+        CodeLanguage::Httpie,
+        CodeLanguage::Wget,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CodeLanguage::Curl => "curl",
+            CodeLanguage::Rust => "Rust (reqwest)",
+            CodeLanguage::Python => "Python (requests)",
+            CodeLanguage::Js => "JavaScript (fetch)",
+            CodeLanguage::Go => "Go (net/http)",
+            CodeLanguage::Httpie => "HTTPie",
+            CodeLanguage::Wget => "wget",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|l| l == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(&self) -> Self {
+        let index = Self::ALL.iter().position(|l| l == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// Renders an (already interpolated) request as ready-to-paste client code
+pub fn generate_code(request: &Request, language: CodeLanguage) -> String {
+    match language {
+        CodeLanguage::Curl => generate_curl(request),
+        CodeLanguage::Rust => generate_rust(request),
+        CodeLanguage::Python => generate_python(request),
+        CodeLanguage::Js => generate_js(request),
+        CodeLanguage::Go => generate_go(request),
+        CodeLanguage::Httpie => generate_httpie(request),
+        CodeLanguage::Wget => generate_wget(request),
+    }
+}
+
+/// Headers in a stable order, adding a JSON content type when the body is
+/// JSON and the request doesn't set one itself
+fn headers_for_wire(request: &Request) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = request
+        .headers
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    headers.sort();
+
+    let has_content_type = headers
+        .iter()
+        .any(|(k, _)| k.eq_ignore_ascii_case("content-type"));
+    if matches!(request.body, Some(RequestBody::Json(_))) && !has_content_type {
+        headers.push(("Content-Type".to_string(), "application/json".to_string()));
+    }
+    headers
+}
+
+fn sorted_query(request: &Request) -> Vec<(&String, &String)> {
+    let mut query: Vec<_> = request.query.iter().collect();
+    query.sort();
+    query
+}
+
+fn body_text(request: &Request) -> Option<String> {
+    request.body.as_ref().map(|body| match body {
+        RequestBody::Json(json) => serde_json::to_string_pretty(json).unwrap_or_default(),
+        RequestBody::Text(text) => text.clone(),
+    })
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn shell_command(parts: Vec<String>) -> String {
+    parts.join(" \\\n  ")
+}
+
+fn generate_curl(request: &Request) -> String {
+    let mut parts = vec![format!(
+        "curl -X {} {}",
+        request.method,
+        shell_quote(&request.full_url())
+    )];
+    for (key, value) in headers_for_wire(request) {
+        parts.push(format!(
+            "-H {}",
+            shell_quote(&format!("{}: {}", key, value))
+        ));
+    }
+    if let Some(body) = body_text(request) {
+        parts.push(format!("--data-raw {}", shell_quote(&body)));
+    }
+    shell_command(parts)
+}
+
+fn generate_httpie(request: &Request) -> String {
+    let mut parts = vec![format!(
+        "http {} {}",
+        request.method,
+        shell_quote(&request.full_url())
+    )];
+    for (key, value) in headers_for_wire(request) {
+        parts.push(shell_quote(&format!("{}:{}", key, value)));
+    }
+    if let Some(body) = body_text(request) {
+        parts.push(format!("--raw {}", shell_quote(&body)));
+    }
+    shell_command(parts)
+}
+
+fn generate_wget(request: &Request) -> String {
+    let mut parts = vec![format!("wget --method={}", request.method)];
+    for (key, value) in headers_for_wire(request) {
+        parts.push(format!(
+            "--header={}",
+            shell_quote(&format!("{}: {}", key, value))
+        ));
+    }
+    if let Some(body) = body_text(request) {
+        parts.push(format!("--body-data={}", shell_quote(&body)));
+    }
+    parts.push("-O -".to_string());
+    parts.push(shell_quote(&request.full_url()));
+    shell_command(parts)
+}
+
+fn generate_rust(request: &Request) -> String {
+    let method = match request.method {
+        HttpMethod::Get => "GET",
+        HttpMethod::Post => "POST",
+        HttpMethod::Put => "PUT",
+        HttpMethod::Patch => "PATCH",
+        HttpMethod::Delete => "DELETE",
+        HttpMethod::Head => "HEAD",
+        HttpMethod::Options => "OPTIONS",
+    };
+
+    let mut code = String::from("let client = reqwest::Client::new();\nlet response = client\n");
+    code.push_str(&format!(
+        "    .request(reqwest::Method::{}, {:?})\n",
+        method, request.url
+    ));
+
+    let query = sorted_query(request);
+    if !query.is_empty() {
+        code.push_str("    .query(&[\n");
+        for (key, value) in query {
+            code.push_str(&format!("        ({:?}, {:?}),\n", key, value));
+        }
+        code.push_str("    ])\n");
+    }
+
+    let mut headers: Vec<_> = request.headers.iter().collect();
+    headers.sort();
+    for (key, value) in headers {
+        code.push_str(&format!("    .header({:?}, {:?})\n", key, value));
+    }
+
+    match &request.body {
+        Some(RequestBody::Json(json)) => {
+            let pretty = serde_json::to_string_pretty(json).unwrap_or_default();
+            code.push_str(&format!(
+                "    .json(&serde_json::json!({}))\n",
+                indent_continuation(&pretty, "    ")
+            ));
+        }
+        Some(RequestBody::Text(text)) => {
+            let hashes = "#".repeat(raw_string_hashes(text));
+            code.push_str(&format!("    .body(r{0}\"{1}\"{0})\n", hashes, text));
+        }
+        None => {}
+    }
+
+    code.push_str("    .send()\n    .await?;\n\n");
+    code.push_str("println!(\"{}\", response.status());\n");
+    code.push_str("println!(\"{}\", response.text().await?);\n");
+    code
+}
+
+/// Number of `#`s needed so `text` can't terminate a Rust raw string early
+fn raw_string_hashes(text: &str) -> usize {
+    let mut needed = 0;
+    for (i, _) in text.match_indices('"') {
+        let run = text[i + 1..].chars().take_while(|&c| c == '#').count();
+        needed = needed.max(run + 1);
+    }
+    needed
+}
+
+fn generate_python(request: &Request) -> String {
+    let mut code = String::from("import requests\n\nresponse = requests.request(\n");
+    code.push_str(&format!(
+        "    {},\n",
+        json_string(&request.method.to_string())
+    ));
+    code.push_str(&format!("    {},\n", json_string(&request.url)));
+
+    let query = sorted_query(request);
+    if !query.is_empty() {
+        code.push_str("    params={\n");
+        for (key, value) in query {
+            code.push_str(&format!(
+                "        {}: {},\n",
+                json_string(key),
+                json_string(value)
+            ));
+        }
+        code.push_str("    },\n");
+    }
+
+    let mut headers: Vec<_> = request.headers.iter().collect();
+    headers.sort();
+    if !headers.is_empty() {
+        code.push_str("    headers={\n");
+        for (key, value) in headers {
+            code.push_str(&format!(
+                "        {}: {},\n",
+                json_string(key),
+                json_string(value)
+            ));
+        }
+        code.push_str("    },\n");
+    }
+
+    match &request.body {
+        Some(RequestBody::Json(json)) => {
+            code.push_str(&format!("    json={},\n", python_literal(json, 1)));
+        }
+        Some(RequestBody::Text(text)) => {
+            code.push_str(&format!("    data={},\n", json_string(text)));
+        }
+        None => {}
+    }
+
+    code.push_str(")\n\nprint(response.status_code)\nprint(response.text)\n");
+    code
+}
+
+fn python_literal(value: &serde_json::Value, depth: usize) -> String {
+    let indent = "    ".repeat(depth + 1);
+    let closing = "    ".repeat(depth);
+    match value {
+        serde_json::Value::Null => "None".to_string(),
+        serde_json::Value::Bool(true) => "True".to_string(),
+        serde_json::Value::Bool(false) => "False".to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => json_string(s),
+        serde_json::Value::Array(items) if items.is_empty() => "[]".to_string(),
+        serde_json::Value::Array(items) => {
+            let inner: Vec<String> = items
+                .iter()
+                .map(|item| format!("{}{},\n", indent, python_literal(item, depth + 1)))
+                .collect();
+            format!("[\n{}{}]", inner.concat(), closing)
+        }
+        serde_json::Value::Object(map) if map.is_empty() => "{}".to_string(),
+        serde_json::Value::Object(map) => {
+            let inner: Vec<String> = map
+                .iter()
+                .map(|(k, v)| {
+                    format!(
+                        "{}{}: {},\n",
+                        indent,
+                        json_string(k),
+                        python_literal(v, depth + 1)
+                    )
+                })
+                .collect();
+            format!("{{\n{}{}}}", inner.concat(), closing)
+        }
+    }
+}
+
+fn generate_js(request: &Request) -> String {
+    let mut code = format!(
+        "const response = await fetch({}, {{\n",
+        json_string(&request.full_url())
+    );
+    code.push_str(&format!(
+        "  method: {},\n",
+        json_string(&request.method.to_string())
+    ));
+
+    let headers = headers_for_wire(request);
+    if !headers.is_empty() {
+        code.push_str("  headers: {\n");
+        for (key, value) in headers {
+            code.push_str(&format!(
+                "    {}: {},\n",
+                json_string(&key),
+                json_string(&value)
+            ));
+        }
+        code.push_str("  },\n");
+    }
+
+    match &request.body {
+        Some(RequestBody::Json(json)) => {
+            let pretty = serde_json::to_string_pretty(json).unwrap_or_default();
+            code.push_str(&format!(
+                "  body: JSON.stringify({}),\n",
+                indent_continuation(&pretty, "  ")
+            ));
+        }
+        Some(RequestBody::Text(text)) => {
+            code.push_str(&format!("  body: {},\n", json_string(text)));
+        }
+        None => {}
+    }
+
+    code.push_str("});\n\nconsole.log(response.status);\nconsole.log(await response.text());\n");
+    code
+}
+
+fn generate_go(request: &Request) -> String {
+    let body = body_text(request);
+
+    let mut code = String::from("package main\n\nimport (\n\t\"fmt\"\n\t\"io\"\n\t\"net/http\"\n");
+    if body.is_some() {
+        code.push_str("\t\"strings\"\n");
+    }
+    code.push_str(")\n\nfunc main() {\n");
+
+    let body_arg = match &body {
+        Some(text) => {
+            let literal = if text.contains('`') {
+                json_string(text)
+            } else {
+                format!("`{}`", text)
+            };
+            code.push_str(&format!("\tbody := strings.NewReader({})\n", literal));
+            "body"
+        }
+        None => "nil",
+    };
+
+    code.push_str(&format!(
+        "\treq, err := http.NewRequest({}, {}, {})\n",
+        json_string(&request.method.to_string()),
+        json_string(&request.full_url()),
+        body_arg
+    ));
+    code.push_str("\tif err != nil {\n\t\tpanic(err)\n\t}\n");
+    for (key, value) in headers_for_wire(request) {
+        code.push_str(&format!(
+            "\treq.Header.Set({}, {})\n",
+            json_string(&key),
+            json_string(&value)
+        ));
+    }
+
+    code.push_str(
+        "\n\tresp, err := http.DefaultClient.Do(req)\n\
+         \tif err != nil {\n\t\tpanic(err)\n\t}\n\
+         \tdefer resp.Body.Close()\n\n\
+         \trespBody, err := io.ReadAll(resp.Body)\n\
+         \tif err != nil {\n\t\tpanic(err)\n\t}\n\
+         \tfmt.Println(resp.Status)\n\
+         \tfmt.Println(string(respBody))\n}\n",
+    );
+    code
+}
+
+/// Double-quoted string literal; JSON escaping is valid in Python, JS and Go
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+/// Indents every line but the first so a multi-line literal lines up inside
+/// a call
+fn indent_continuation(text: &str, indent: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                line.to_string()
+            } else {
+                format!("{}{}", indent, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post_request() -> Request {
+        Request {
+            method: HttpMethod::Post,
+            headers: [(
+                "Authorization".to_string(),
+                "Bearer it's-a-token".to_string(),
+            )]
+            .into_iter()
+            .collect(),
+            query: [("draft".to_string(), "true".to_string())]
+                .into_iter()
+                .collect(),
+            body: Some(RequestBody::Json(
+                serde_json::json!({"title": "Hello", "published": false, "tags": null}),
+            )),
+            ..Request::test("Create Post", "https://api.example.com/posts")
+        }
+    }
+
+    #[test]
+    fn test_generate_curl() {
+        let code = generate_code(&post_request(), CodeLanguage::Curl);
+        assert!(code.starts_with("curl -X POST 'https://api.example.com/posts?draft=true'"));
+        assert!(code.contains(r"-H 'Authorization: Bearer it'\''s-a-token'"));
+        assert!(code.contains("-H 'Content-Type: application/json'"));
+        assert!(code.contains("--data-raw '{"));
+    }
+
+    #[test]
+    fn test_generate_python() {
+        let code = generate_code(&post_request(), CodeLanguage::Python);
+        assert!(code.contains("params={\n        \"draft\": \"true\",\n    },"));
+        assert!(code.contains("\"published\": False,"));
+        assert!(code.contains("\"tags\": None,"));
+    }
+
+    #[test]
+    fn test_generate_rust_and_go() {
+        let rust = generate_code(&post_request(), CodeLanguage::Rust);
+        assert!(
+            rust.contains(".request(reqwest::Method::POST, \"https://api.example.com/posts\")")
+        );
+        assert!(rust.contains("(\"draft\", \"true\"),"));
+        assert!(rust.contains(".json(&serde_json::json!({"));
+
+        let go = generate_code(&post_request(), CodeLanguage::Go);
+        assert!(go.contains("\"strings\""));
+        assert!(go.contains(
+            "http.NewRequest(\"POST\", \"https://api.example.com/posts?draft=true\", body)"
+        ));
+    }
+
+    #[test]
+    fn test_generate_without_body() {
+        let request = Request::test("Health", "https://api.example.com/health");
+
+        let go = generate_code(&request, CodeLanguage::Go);
+        assert!(!go.contains("strings"));
+        assert!(go.contains(", nil)"));
+
+        let wget = generate_code(&request, CodeLanguage::Wget);
+        assert_eq!(
+            wget,
+            "wget --method=GET \\\n  -O - \\\n  'https://api.example.com/health'"
+        );
+    }
+
+    #[test]
+    fn test_raw_string_hashes() {
+        assert_eq!(raw_string_hashes("plain"), 0);
+        assert_eq!(raw_string_hashes(r#"say "hi""#), 1);
+        assert_eq!(raw_string_hashes(r##"tricky "# end"##), 2);
+    }
+}
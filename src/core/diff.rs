@@ -0,0 +1,288 @@
+use crate::core::Response;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How one value differs between the old and the new side
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<T> {
+    Added(T),
+    Removed(T),
+    Changed(T, T),
+}
+
+impl<T> Change<T> {
+    /// `+`, `-` or `~`, as in the CLI output
+    pub fn marker(&self) -> char {
+        match self {
+            Change::Added(_) => '+',
+            Change::Removed(_) => '-',
+            Change::Changed(..) => '~',
+        }
+    }
+
+    /// The value on the old side, if it has one
+    pub fn old_side(&self) -> Option<&T> {
+        match self {
+            Change::Removed(old) | Change::Changed(old, _) => Some(old),
+            Change::Added(_) => None,
+        }
+    }
+
+    pub fn new_side(&self) -> Option<&T> {
+        match self {
+            Change::Added(new) | Change::Changed(_, new) => Some(new),
+            Change::Removed(_) => None,
+        }
+    }
+}
+
+/// A difference in a JSON body, at a JSONPath such as `$.items[0].id`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonChange {
+    pub path: String,
+    pub change: Change<Value>,
+}
+
+/// Differences in the leaves of two JSON documents, in document order.
+/// Object keys are matched by name, so their order doesn't matter; array
+/// elements are matched by position.
+pub fn diff_json(old: &Value, new: &Value) -> Vec<JsonChange> {
+    let mut changes = Vec::new();
+    diff_at("$".to_string(), old, new, &mut changes);
+    changes
+}
+
+fn diff_at(path: String, old: &Value, new: &Value, changes: &mut Vec<JsonChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = child_path(&path, key);
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => diff_at(path, old, new, changes),
+                    (Some(old), None) => changes.push(JsonChange {
+                        path,
+                        change: Change::Removed(old.clone()),
+                    }),
+                    (None, Some(new)) => changes.push(JsonChange {
+                        path,
+                        change: Change::Added(new.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                let path = format!("{}[{}]", path, i);
+                match (old.get(i), new.get(i)) {
+                    (Some(old), Some(new)) => diff_at(path, old, new, changes),
+                    (Some(old), None) => changes.push(JsonChange {
+                        path,
+                        change: Change::Removed(old.clone()),
+                    }),
+                    (None, Some(new)) => changes.push(JsonChange {
+                        path,
+                        change: Change::Added(new.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(JsonChange {
+            path,
+            change: Change::Changed(old.clone(), new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    let plain = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if plain {
+        format!("{}.{}", parent, key)
+    } else {
+        format!("{}[{}]", parent, Value::String(key.to_string()))
+    }
+}
+
+/// Status, header and body differences between two responses
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResponseDiff {
+    /// Old and new status, when they differ
+    pub status: Option<(u16, u16)>,
+    /// By lowercased header name
+    pub headers: Vec<(String, Change<String>)>,
+    pub body: Vec<JsonChange>,
+}
+
+impl ResponseDiff {
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of differences
+    pub fn len(&self) -> usize {
+        self.status.is_some() as usize + self.headers.len() + self.body.len()
+    }
+}
+
+pub fn diff_responses(old: &Response, new: &Response) -> ResponseDiff {
+    // Header names are case-insensitive
+    let lowercase = |response: &Response| -> BTreeMap<String, String> {
+        response
+            .headers
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value.clone()))
+            .collect()
+    };
+    let (old_headers, new_headers) = (lowercase(old), lowercase(new));
+    let mut names: Vec<&String> = old_headers.keys().chain(new_headers.keys()).collect();
+    names.sort();
+    names.dedup();
+    let headers = names
+        .into_iter()
+        .filter_map(|name| {
+            let change = match (old_headers.get(name), new_headers.get(name)) {
+                (Some(old), Some(new)) if old != new => Change::Changed(old.clone(), new.clone()),
+                (Some(old), None) => Change::Removed(old.clone()),
+                (None, Some(new)) => Change::Added(new.clone()),
+                _ => return None,
+            };
+            Some((name.clone(), change))
+        })
+        .collect();
+
+    ResponseDiff {
+        status: (old.status != new.status).then_some((old.status, new.status)),
+        headers,
+        body: diff_json(&old.body, &new.body),
+    }
+}
+
+/// `+ path: new`, `- path: old` and `~ path: old → new` lines under
+/// status/headers/body headings
+impl fmt::Display for ResponseDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        if let Some((old, new)) = self.status {
+            writeln!(f, "status: {} → {}", old, new)?;
+        }
+        if !self.headers.is_empty() {
+            writeln!(f, "headers:")?;
+            for (name, change) in &self.headers {
+                write_change(f, name, change, |value| value.clone())?;
+            }
+        }
+        if !self.body.is_empty() {
+            writeln!(f, "body:")?;
+            for change in &self.body {
+                write_change(f, &change.path, &change.change, Value::to_string)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_change<T>(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    change: &Change<T>,
+    show: impl Fn(&T) -> String,
+) -> fmt::Result {
+    match change {
+        Change::Added(value) | Change::Removed(value) => {
+            writeln!(f, "  {} {}: {}", change.marker(), name, show(value))
+        }
+        Change::Changed(old, new) => {
+            writeln!(f, "  ~ {}: {} → {}", name, show(old), show(new))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_diff_json() {
+        let old = json!({"id": 1, "tags": ["a", "b"], "user": {"name": "Ann", "x-y": true}});
+        let new =
+            json!({"user": {"x-y": true, "name": "Bob"}, "tags": ["a"], "id": 1, "new": null});
+        assert_eq!(
+            diff_json(&old, &new),
+            vec![
+                JsonChange {
+                    path: "$.new".to_string(),
+                    change: Change::Added(Value::Null),
+                },
+                JsonChange {
+                    path: "$.tags[1]".to_string(),
+                    change: Change::Removed(json!("b")),
+                },
+                JsonChange {
+                    path: "$.user.name".to_string(),
+                    change: Change::Changed(json!("Ann"), json!("Bob")),
+                },
+            ]
+        );
+
+        // Key order doesn't matter
+        assert!(diff_json(&json!({"a": 1, "b": 2}), &json!({"b": 2, "a": 1})).is_empty());
+        assert_eq!(
+            diff_json(&json!({"a-b": 1}), &json!({"a-b": [1]}))[0].path,
+            "$[\"a-b\"]"
+        );
+    }
+
+    #[test]
+    fn test_diff_responses() {
+        let response = |status, etag: &str, body| Response {
+            id: uuid::Uuid::new_v4(),
+            request_id: None,
+            status,
+            headers: HashMap::from([
+                ("ETag".to_string(), etag.to_string()),
+                ("Server".to_string(), "x".to_string()),
+            ]),
+            body,
+            timing: crate::core::ResponseTiming::default(),
+            timestamp: chrono::Utc::now(),
+            sent: None,
+        };
+        let old = response(200, "v1", json!({"count": 1}));
+        let mut new = response(404, "v2", json!({"count": 2}));
+        new.headers.remove("Server");
+
+        let diff = diff_responses(&old, &new);
+        assert_eq!(diff.status, Some((200, 404)));
+        assert_eq!(
+            diff.headers,
+            vec![
+                (
+                    "etag".to_string(),
+                    Change::Changed("v1".to_string(), "v2".to_string())
+                ),
+                ("server".to_string(), Change::Removed("x".to_string())),
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "status: 200 → 404\nheaders:\n  ~ etag: v1 → v2\n  - server: x\nbody:\n  ~ $.count: 1 → 2\n"
+        );
+
+        assert!(diff_responses(&old, &old).is_empty());
+        assert_eq!(diff_responses(&old, &old).to_string(), "No differences\n");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::{Request, Response, ResponseTiming, VariableInterpolator};
+use crate::core::{Request, Response, ResponseTiming, SentRequest, VariableInterpolator};
 use crate::plugins::PluginManager;
 use chrono::Utc;
 use color_eyre::{Result, eyre::WrapErr};
@@ -49,11 +49,15 @@ impl RequestExecutor {
         request: &Request,
         interpolator: &VariableInterpolator,
     ) -> Result<Response> {
-        let interpolated_request = interpolator.interpolate_request(request);
+        self.send(&interpolator.interpolate_request(request)).await
+    }
 
+    /// Sends a request whose variables were already interpolated, e.g. one
+    /// kept in the history
+    pub async fn send(&self, interpolated_request: &Request) -> Result<Response> {
         // Plugin hook: before_request
         self.plugin_manager
-            .before_request(&interpolated_request)
+            .before_request(interpolated_request)
             .await;
 
         let start_time = Instant::now();
@@ -86,11 +90,16 @@ impl RequestExecutor {
             }
         }
 
-        // Execute the request
-        let http_response = req_builder
-            .send()
-            .await
-            .with_context(|| format!("Failed to send request to {}", interpolated_request.url))?;
+        // Execute the request, recording what actually goes out
+        let http_request = req_builder
+            .build()
+            .with_context(|| format!("Failed to build request to {}", interpolated_request.url))?;
+        let sent =
+            SentRequest::capture(interpolated_request.method.clone(), &http_request).redacted();
+        let http_response =
+            self.client.execute(http_request).await.with_context(|| {
+                format!("Failed to send request to {}", interpolated_request.url)
+            })?;
 
         let total_time = start_time.elapsed();
         let status = http_response.status().as_u16();
@@ -115,7 +124,7 @@ impl RequestExecutor {
 
         let response = Response {
             id: Uuid::new_v4(),
-            request_id: None,
+            request_id: Some(interpolated_request.id),
             status,
             headers: response_headers,
             body: body_json,
@@ -127,6 +136,7 @@ impl RequestExecutor {
                 request_ms: None,
             },
             timestamp: Utc::now(),
+            sent: Some(sent),
         };
 
         // Plugin hook: after_response
@@ -149,7 +159,7 @@ impl RequestExecutor {
                 // Create error response
                 Response {
                     id: Uuid::new_v4(),
-                    request_id: None,
+                    request_id: Some(request.id),
                     status: 0,
                     headers: HashMap::new(),
                     body: serde_json::json!({
@@ -159,6 +169,7 @@ impl RequestExecutor {
                     }),
                     timing: ResponseTiming::default(),
                     timestamp: Utc::now(),
+                    sent: None,
                 }
             }
         }
@@ -202,6 +213,12 @@ mod tests {
                 .collect(),
             body: Some(RequestBody::Json(serde_json::json!({"test": "data"}))),
             notes: Some("Test notes".to_string()),
+            folder: None,
+            source: None,
+            disabled_headers: HashMap::new(),
+            disabled_query: HashMap::new(),
+            extract: HashMap::new(),
+            id: Uuid::new_v4(),
         };
 
         assert_eq!(request.name, "Test Request");
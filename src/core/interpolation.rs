@@ -2,12 +2,14 @@ use crate::core::{Request, RequestBody};
 use color_eyre::Result;
 use regex::Regex;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Clone)]
 pub struct VariableInterpolator {
     pub in_memory: HashMap<String, String>,
     pub env_vars: HashMap<String, String>,
+    /// Env file each of `env_vars` was last set from
+    pub env_sources: HashMap<String, PathBuf>,
     regex: Regex,
 }
 
@@ -16,11 +18,24 @@ impl VariableInterpolator {
         Self {
             in_memory: HashMap::new(),
             env_vars: HashMap::new(),
+            env_sources: HashMap::new(),
             regex: Regex::new(r"\{\{(\w+)\}\}").expect("Invalid regex"),
         }
     }
 
     pub fn load_env_file(&mut self, collection_path: impl AsRef<Path>) -> Result<()> {
+        for env_file in Self::env_file_paths(collection_path) {
+            if env_file.exists() {
+                let content = std::fs::read_to_string(&env_file)?;
+                // Insert/overwrite with values from this file
+                self.extend_env(&content, &env_file);
+            }
+        }
+        Ok(())
+    }
+
+    /// Env files read by `load_env_file`, lowest priority first
+    pub fn env_file_paths(collection_path: impl AsRef<Path>) -> Vec<PathBuf> {
         let collection_dir = collection_path
             .as_ref()
             .parent()
@@ -36,36 +51,54 @@ impl VariableInterpolator {
         // Load from multiple locations in reverse priority order
         // (later files override earlier ones)
         // Priority: .netbook/.env > project_root/.env.local > project_root/.env > .netbook.env
-        let env_files = [
-            collection_dir.join(".netbook.env"),        // Lowest priority (backward compat)
-            project_root.join(".env"),                  // Base config in project root
-            project_root.join(".env.local"),            // Local overrides (Next.js) in project root
+        vec![
+            collection_dir.join(".netbook.env"), // Lowest priority (backward compat)
+            project_root.join(".env"),           // Base config in project root
+            project_root.join(".env.local"),     // Local overrides (Next.js) in project root
             project_root.join(".netbook").join(".env"), // Highest priority
-        ];
+        ]
+    }
 
-        for env_file in &env_files {
-            if env_file.exists() {
-                let content = std::fs::read_to_string(env_file)?;
-                for line in content.lines() {
-                    let line = line.trim();
-                    if line.is_empty() || line.starts_with('#') {
-                        continue;
-                    }
-                    if let Some((key, value)) = line.split_once('=') {
-                        // Insert/overwrite with values from this file
-                        self.env_vars
-                            .insert(key.trim().to_string(), value.trim().to_string());
-                    }
-                }
-            }
-        }
+    /// Path of the `.netbook/.env.<name>` file for a named environment
+    pub fn environment_file_path(collection_path: impl AsRef<Path>, name: &str) -> PathBuf {
+        crate::io::get_netbook_dir(collection_path.as_ref())
+            .join(crate::io::environment_file_name(Some(name)))
+    }
+
+    /// Loads `.netbook/.env.<name>` on top of the already loaded env files
+    pub fn load_environment(
+        &mut self,
+        collection_path: impl AsRef<Path>,
+        name: &str,
+    ) -> Result<()> {
+        let env_file = Self::environment_file_path(collection_path, name);
+
+        let content = std::fs::read_to_string(&env_file).map_err(|_| {
+            color_eyre::eyre::eyre!(
+                "Environment '{}' not found (expected {})",
+                name,
+                env_file.display()
+            )
+        })?;
+        self.extend_env(&content, &env_file);
         Ok(())
     }
 
+    fn extend_env(&mut self, content: &str, path: &Path) {
+        for (key, value) in parse_env_content(content) {
+            self.env_sources.insert(key.clone(), path.to_path_buf());
+            self.env_vars.insert(key, value);
+        }
+    }
+
     pub fn set_variable(&mut self, key: String, value: String) {
         self.in_memory.insert(key, value);
     }
 
+    pub fn remove_variable(&mut self, key: &str) -> Option<String> {
+        self.in_memory.remove(key)
+    }
+
     pub fn get_variable(&self, key: &str) -> Option<String> {
         // Priority: 1) in-memory, 2) env file, 3) process env
         self.in_memory
@@ -75,6 +108,14 @@ impl VariableInterpolator {
             .or_else(|| std::env::var(key).ok())
     }
 
+    /// Names of the `{{variables}}` referenced in `input`
+    pub fn variables_in(&self, input: &str) -> Vec<String> {
+        self.regex
+            .captures_iter(input)
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
     pub fn interpolate_string(&self, input: &str) -> String {
         self.regex
             .replace_all(input, |caps: &regex::Captures| {
@@ -124,31 +165,56 @@ impl VariableInterpolator {
         interpolated
     }
 
+    /// First value matched by `path`, a dotted path or any expression
+    /// understood by [`crate::core::Query`]. Strings are returned unquoted.
     pub fn extract_from_response_path(
         &self,
         response_body: &serde_json::Value,
         path: &str,
     ) -> Option<String> {
-        let parts: Vec<&str> = path.split('.').collect();
-        let mut current = response_body;
-
-        for part in parts {
-            if let Ok(index) = part.parse::<usize>() {
-                current = current.get(index)?;
-            } else {
-                current = current.get(part)?;
-            }
+        let value = crate::core::run_query(response_body, path)
+            .ok()?
+            .into_iter()
+            .next()?;
+
+        match value {
+            serde_json::Value::String(s) => Some(s),
+            value => Some(value.to_string()),
         }
+    }
 
-        match current {
-            serde_json::Value::String(s) => Some(s.clone()),
-            serde_json::Value::Number(n) => Some(n.to_string()),
-            serde_json::Value::Bool(b) => Some(b.to_string()),
-            _ => Some(current.to_string()),
-        }
+    /// Sets the request's `extract` variables from a response body and
+    /// returns the names that were found, sorted
+    pub fn apply_extract(
+        &mut self,
+        request: &Request,
+        response_body: &serde_json::Value,
+    ) -> Vec<String> {
+        let mut names: Vec<String> = request
+            .extract
+            .iter()
+            .filter_map(|(name, path)| {
+                let value = self.extract_from_response_path(response_body, path)?;
+                self.set_variable(name.clone(), value);
+                Some(name.clone())
+            })
+            .collect();
+        names.sort();
+        names
     }
 }
 
+/// Parses `KEY=value` lines, skipping blanks and `#` comments
+pub fn parse_env_content(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
 impl Default for VariableInterpolator {
     fn default() -> Self {
         Self::new()
@@ -188,6 +254,12 @@ mod tests {
             query: HashMap::new(),
             body: None,
             notes: None,
+            folder: None,
+            source: None,
+            disabled_headers: HashMap::new(),
+            disabled_query: HashMap::new(),
+            extract: HashMap::new(),
+            id: uuid::Uuid::new_v4(),
         };
 
         let interpolated = interpolator.interpolate_request(&request);
@@ -246,21 +318,48 @@ mod tests {
             Some("from_netbook".to_string())
         );
         // All unique variables should be loaded
-        assert_eq!(
-            interpolator.get_variable("VAR1"),
-            Some("root".to_string())
-        );
-        assert_eq!(
-            interpolator.get_variable("VAR2"),
-            Some("local".to_string())
-        );
+        assert_eq!(interpolator.get_variable("VAR1"), Some("root".to_string()));
+        assert_eq!(interpolator.get_variable("VAR2"), Some("local".to_string()));
         assert_eq!(
             interpolator.get_variable("VAR3"),
             Some("netbook".to_string())
         );
+        assert_eq!(interpolator.get_variable("BASE"), Some("root".to_string()));
+    }
+
+    #[test]
+    fn test_load_environment() {
+        let dir = tempdir().unwrap();
+        let netbook_dir = dir.path().join(".netbook");
+        std::fs::create_dir(&netbook_dir).unwrap();
+        let collection_file = netbook_dir.join("collection.json");
+
+        std::fs::write(
+            netbook_dir.join(".env"),
+            "baseUrl=http://localhost\ntoken=dev",
+        )
+        .unwrap();
+        std::fs::write(
+            netbook_dir.join(".env.staging"),
+            "# staging\nbaseUrl=https://staging.example.com",
+        )
+        .unwrap();
+
+        let mut interpolator = VariableInterpolator::new();
+        interpolator.load_env_file(&collection_file).unwrap();
+        interpolator
+            .load_environment(&collection_file, "staging")
+            .unwrap();
+
         assert_eq!(
-            interpolator.get_variable("BASE"),
-            Some("root".to_string())
+            interpolator.get_variable("baseUrl"),
+            Some("https://staging.example.com".to_string())
+        );
+        assert_eq!(interpolator.get_variable("token"), Some("dev".to_string()));
+        assert!(
+            interpolator
+                .load_environment(&collection_file, "missing")
+                .is_err()
         );
     }
 
@@ -289,5 +388,31 @@ mod tests {
             interpolator.extract_from_response_path(&response, "items.1"),
             Some("2".to_string())
         );
+        assert_eq!(
+            interpolator.extract_from_response_path(&response, ""),
+            Some(response.to_string())
+        );
+        assert_eq!(
+            interpolator.extract_from_response_path(&response, "$.items[?(@ > 1)]"),
+            Some("2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_apply_extract() {
+        let mut interpolator = VariableInterpolator::new();
+        let request = Request {
+            method: HttpMethod::Post,
+            extract: HashMap::from([
+                ("token".to_string(), "auth.token".to_string()),
+                ("missing".to_string(), "auth.refresh".to_string()),
+            ]),
+            ..Request::test("Login", "https://example.com/login")
+        };
+        let response = serde_json::json!({"auth": {"token": "abc"}});
+
+        assert_eq!(interpolator.apply_extract(&request, &response), ["token"]);
+        assert_eq!(interpolator.get_variable("token"), Some("abc".to_string()));
+        assert_eq!(interpolator.get_variable("missing"), None);
     }
 }
@@ -1,28 +1,61 @@
+pub mod codegen;
+pub mod diff;
 pub mod executor;
 pub mod interpolation;
 pub mod models;
+pub mod query;
+pub mod sent;
 
+pub use codegen::*;
+pub use diff::*;
 pub use executor::*;
 pub use interpolation::*;
 pub use models::*;
+pub use query::*;
+pub use sent::*;
 
 use color_eyre::Result;
 use std::path::Path;
 
-pub async fn run_headless(name: &str, collection_path: &Path) -> Result<()> {
+/// Finds a request by id, or by name when the name is unique in the collection
+pub fn find_request<'a>(collection: &'a Collection, key: &str) -> Result<&'a Request> {
+    if let Ok(id) = key.parse::<uuid::Uuid>()
+        && let Some(request) = collection.iter().find(|r| r.id == id)
+    {
+        return Ok(request);
+    }
+
+    let matches: Vec<&Request> = collection.iter().filter(|r| r.name == key).collect();
+    match matches.as_slice() {
+        [request] => Ok(request),
+        [] => Err(color_eyre::eyre::eyre!("Request '{}' not found", key)),
+        _ => Err(color_eyre::eyre::eyre!(
+            "{} requests are named '{}'; pass one of their ids instead: {}",
+            matches.len(),
+            key,
+            matches
+                .iter()
+                .map(|r| r.id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+pub async fn run_headless(
+    name: &str,
+    collection_path: &Path,
+    environment: Option<&str>,
+) -> Result<()> {
     let collection = crate::io::load_collection(collection_path)?;
-    let request = collection
-        .iter()
-        .find(|r| r.name == name)
-        .ok_or_else(|| color_eyre::eyre::eyre!("Request '{}' not found", name))?;
+    let request = find_request(&collection, name)?;
 
-    let interpolator = crate::io::load_interpolator_with_context(collection_path).await?;
+    let interpolator =
+        crate::io::load_interpolator_for_environment(collection_path, environment).await?;
     let executor = RequestExecutor::new();
 
-    match executor
-        .execute_with_interpolator(request, &interpolator)
-        .await
-    {
+    let sent = interpolator.interpolate_request(request);
+    match executor.send(&sent).await {
         Ok(response) => {
             println!("Status: {}", response.status);
             println!("Time: {}ms", response.timing.total_ms);
@@ -36,7 +69,7 @@ pub async fn run_headless(name: &str, collection_path: &Path) -> Result<()> {
             }
 
             // Save to history
-            let _ = crate::io::add_to_history(request.name.clone(), response).await;
+            let _ = crate::io::add_to_history(collection_path, &sent, response).await;
         }
         Err(e) => {
             eprintln!("Request failed: {}", e);
@@ -46,3 +79,20 @@ pub async fn run_headless(name: &str, collection_path: &Path) -> Result<()> {
 
     Ok(())
 }
+
+pub async fn print_request_code(
+    name: &str,
+    collection_path: &Path,
+    environment: Option<&str>,
+    language: CodeLanguage,
+) -> Result<()> {
+    let collection = crate::io::load_collection(collection_path)?;
+    let request = find_request(&collection, name)?;
+
+    let interpolator =
+        crate::io::load_interpolator_for_environment(collection_path, environment).await?;
+    let interpolated = interpolator.interpolate_request(request);
+
+    println!("{}", generate_code(&interpolated, language));
+    Ok(())
+}
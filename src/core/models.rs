@@ -5,6 +5,9 @@ use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Request {
+    /// Stable identifier that survives renames; assigned on first load
+    #[serde(default)]
+    pub id: Uuid,
     pub name: String,
     pub method: HttpMethod,
     pub url: String,
@@ -12,13 +15,85 @@ pub struct Request {
     pub headers: HashMap<String, String>,
     #[serde(default)]
     pub query: HashMap<String, String>,
+    /// Headers switched off in the editor: kept, but not sent
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub disabled_headers: HashMap<String, String>,
+    /// Query parameters switched off in the editor: kept, but not sent
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub disabled_query: HashMap<String, String>,
     #[serde(default)]
     pub body: Option<RequestBody>,
+    /// Variables set from the JSON response body after the request runs,
+    /// as variable name -> path (e.g. `token` -> `data.auth.token`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extract: HashMap<String, String>,
     #[serde(default)]
     pub notes: Option<String>,
+    /// Slash-separated folder path, e.g. `Users/Admin`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    /// File this request was loaded from when the collection is a directory
+    #[serde(skip)]
+    pub source: Option<std::path::PathBuf>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Request {
+    /// Keys a request may have in a collection file
+    pub const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "method",
+        "url",
+        "headers",
+        "query",
+        "disabled_headers",
+        "disabled_query",
+        "body",
+        "extract",
+        "notes",
+        "folder",
+    ];
+
+    /// URL with the query parameters appended, in a stable (sorted) order
+    pub fn full_url(&self) -> String {
+        match reqwest::Url::parse(&self.url) {
+            Ok(mut url) => {
+                if !self.query.is_empty() {
+                    let mut pairs: Vec<_> = self.query.iter().collect();
+                    pairs.sort();
+                    url.query_pairs_mut().extend_pairs(pairs);
+                }
+                url.to_string()
+            }
+            Err(_) => self.url.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Request {
+    /// A GET request with only a name and URL, for tests to fill in the
+    /// fields they care about with `..Request::test(name, url)`
+    pub fn test(name: &str, url: &str) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            method: HttpMethod::Get,
+            url: url.to_string(),
+            headers: HashMap::new(),
+            query: HashMap::new(),
+            disabled_headers: HashMap::new(),
+            disabled_query: HashMap::new(),
+            body: None,
+            extract: HashMap::new(),
+            notes: None,
+            folder: None,
+            source: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum HttpMethod {
     Get,
@@ -44,6 +119,26 @@ impl std::fmt::Display for HttpMethod {
     }
 }
 
+impl std::str::FromStr for HttpMethod {
+    type Err = color_eyre::eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "GET" => Ok(HttpMethod::Get),
+            "POST" => Ok(HttpMethod::Post),
+            "PUT" => Ok(HttpMethod::Put),
+            "PATCH" => Ok(HttpMethod::Patch),
+            "DELETE" => Ok(HttpMethod::Delete),
+            "HEAD" => Ok(HttpMethod::Head),
+            "OPTIONS" => Ok(HttpMethod::Options),
+            other => Err(color_eyre::eyre::eyre!(
+                "Unsupported HTTP method: {}",
+                other
+            )),
+        }
+    }
+}
+
 impl From<HttpMethod> for reqwest::Method {
     fn from(method: HttpMethod) -> Self {
         match method {
@@ -65,11 +160,11 @@ pub enum RequestBody {
     Json(serde_json::Value),
 }
 
-impl RequestBody {
-    pub fn to_string(&self) -> String {
+impl std::fmt::Display for RequestBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            RequestBody::Text(s) => s.clone(),
-            RequestBody::Json(v) => serde_json::to_string(v).unwrap_or_default(),
+            RequestBody::Text(s) => write!(f, "{}", s),
+            RequestBody::Json(v) => write!(f, "{}", serde_json::to_string(v).unwrap_or_default()),
         }
     }
 }
@@ -83,6 +178,9 @@ pub struct Response {
     pub body: serde_json::Value,
     pub timing: ResponseTiming,
     pub timestamp: DateTime<Utc>,
+    /// The request that produced this response, with secrets redacted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sent: Option<crate::core::SentRequest>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -0,0 +1,583 @@
+//! A small query language for JSON response bodies, accepting both JSONPath
+//! (`$.items[?(@.price < 10)].name`) and jq-like filters
+//! (`.items[] | select(.active) | .id`).
+//!
+//! Dotted paths with array indices as plain segments (`data.items.0.id`) are
+//! accepted too, so older `extract` paths keep working.
+
+use color_eyre::{Result, eyre::eyre};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// A parsed query, ready to run against any number of documents
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    stages: Vec<Stage>,
+}
+
+/// One `|`-separated part of a query; each output of a stage is fed to the
+/// next one
+#[derive(Debug, Clone, PartialEq)]
+enum Stage {
+    Path(Vec<Step>),
+    Select(Condition),
+    /// jq's `[query]`: every output of the inner query as one array
+    Collect(Query),
+    Keys,
+    Length,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    /// Object key; on arrays a numeric key is used as an index
+    Key(String),
+    /// Array index, negative counting from the end
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    /// Every value of an object or element of an array
+    Wildcard,
+    /// Applies the step to the current value and all its descendants
+    Descend(Box<Step>),
+    Filter(Condition),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    path: Vec<Step>,
+    test: Option<(Comparison, Value)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Query {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let stages = split_top_level(expression, '|')
+            .into_iter()
+            .map(parse_stage)
+            .collect::<Result<_>>()?;
+        Ok(Self { stages })
+    }
+
+    /// Every value the query produces, in document order
+    pub fn run(&self, value: &Value) -> Vec<Value> {
+        let mut values = vec![value.clone()];
+        for stage in &self.stages {
+            values = values.iter().flat_map(|value| stage.apply(value)).collect();
+        }
+        values
+    }
+}
+
+/// Parses and runs `expression` against `value`
+pub fn run_query(value: &Value, expression: &str) -> Result<Vec<Value>> {
+    Ok(Query::parse(expression)?.run(value))
+}
+
+/// The query's result as one document: the single value it produced, or an
+/// array of all of them
+pub fn query_value(value: &Value, expression: &str) -> Result<Value> {
+    let mut values = run_query(value, expression)?;
+    Ok(if values.len() == 1 {
+        values.remove(0)
+    } else {
+        Value::Array(values)
+    })
+}
+
+impl Stage {
+    fn apply(&self, value: &Value) -> Vec<Value> {
+        match self {
+            Stage::Path(steps) => select_path(value, steps).into_iter().cloned().collect(),
+            Stage::Select(condition) => {
+                if condition.holds(value) {
+                    vec![value.clone()]
+                } else {
+                    Vec::new()
+                }
+            }
+            Stage::Collect(query) => vec![Value::Array(query.run(value))],
+            Stage::Keys => match value {
+                Value::Object(obj) => vec![Value::Array(
+                    obj.keys().map(|k| Value::String(k.clone())).collect(),
+                )],
+                Value::Array(arr) => vec![Value::Array((0..arr.len()).map(Value::from).collect())],
+                _ => Vec::new(),
+            },
+            Stage::Length => vec![Value::from(match value {
+                Value::Object(obj) => obj.len(),
+                Value::Array(arr) => arr.len(),
+                Value::String(s) => s.chars().count(),
+                Value::Null => 0,
+                _ => return Vec::new(),
+            })],
+        }
+    }
+}
+
+fn select_path<'a>(value: &'a Value, steps: &[Step]) -> Vec<&'a Value> {
+    let mut values = vec![value];
+    for step in steps {
+        values = values
+            .into_iter()
+            .flat_map(|value| step.apply(value))
+            .collect();
+    }
+    values
+}
+
+impl Step {
+    fn apply<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        match self {
+            Step::Key(key) => match value {
+                Value::Object(obj) => obj.get(key).into_iter().collect(),
+                Value::Array(arr) => key
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| arr.get(i))
+                    .into_iter()
+                    .collect(),
+                _ => Vec::new(),
+            },
+            Step::Index(index) => match value {
+                Value::Array(arr) => resolve_index(*index, arr.len())
+                    .and_then(|i| arr.get(i))
+                    .into_iter()
+                    .collect(),
+                _ => Vec::new(),
+            },
+            Step::Slice(start, end) => match value {
+                Value::Array(arr) => {
+                    let len = arr.len() as i64;
+                    let clamp = |i: i64| if i < 0 { (len + i).max(0) } else { i.min(len) };
+                    let start = clamp(start.unwrap_or(0)) as usize;
+                    let end = clamp(end.unwrap_or(len)) as usize;
+                    arr.get(start..end.max(start))
+                        .map(|slice| slice.iter().collect())
+                        .unwrap_or_default()
+                }
+                _ => Vec::new(),
+            },
+            Step::Wildcard => children(value),
+            Step::Descend(step) => pre_order(value)
+                .into_iter()
+                .flat_map(|value| step.apply(value))
+                .collect(),
+            Step::Filter(condition) => children(value)
+                .into_iter()
+                .filter(|child| condition.holds(child))
+                .collect(),
+        }
+    }
+}
+
+fn children(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Object(obj) => obj.values().collect(),
+        Value::Array(arr) => arr.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn pre_order(value: &Value) -> Vec<&Value> {
+    let mut values = vec![value];
+    for child in children(value) {
+        values.extend(pre_order(child));
+    }
+    values
+}
+
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    if index < 0 {
+        len.checked_sub(index.unsigned_abs() as usize)
+    } else {
+        Some(index as usize)
+    }
+}
+
+impl Condition {
+    fn holds(&self, value: &Value) -> bool {
+        let values = select_path(value, &self.path);
+        match &self.test {
+            None => values
+                .iter()
+                .any(|v| !matches!(v, Value::Null | Value::Bool(false))),
+            Some((comparison, expected)) => values
+                .iter()
+                .any(|v| comparison.holds(compare(v, expected))),
+        }
+    }
+}
+
+impl Comparison {
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match (self, ordering) {
+            (Comparison::Ne, ordering) => ordering != Some(Ordering::Equal),
+            (_, None) => false,
+            (Comparison::Eq, Some(o)) => o.is_eq(),
+            (Comparison::Lt, Some(o)) => o.is_lt(),
+            (Comparison::Le, Some(o)) => o.is_le(),
+            (Comparison::Gt, Some(o)) => o.is_gt(),
+            (Comparison::Ge, Some(o)) => o.is_ge(),
+        }
+    }
+}
+
+/// Orders numbers and strings; other values are only equal or not
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (a, b) if a == b => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+fn parse_stage(stage: &str) -> Result<Stage> {
+    let stage = stage.trim();
+    match stage {
+        "keys" => return Ok(Stage::Keys),
+        "length" => return Ok(Stage::Length),
+        _ => {}
+    }
+    if let Some(condition) = stage
+        .strip_prefix("select(")
+        .and_then(|s| s.strip_suffix(')'))
+    {
+        return Ok(Stage::Select(parse_condition(condition)?));
+    }
+    match Parser::new(stage).parse_path() {
+        Ok(steps) => Ok(Stage::Path(steps)),
+        // Not a bracket step like `[0]` or `['key']`, so a jq array
+        Err(error) => match stage.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            Some(inner) => Ok(Stage::Collect(Query::parse(inner)?)),
+            None => Err(error),
+        },
+    }
+}
+
+fn parse_condition(condition: &str) -> Result<Condition> {
+    let mut parser = Parser::new(condition.trim());
+    let path = parser.parse_path()?;
+    parser.skip_whitespace();
+    if parser.at_end() {
+        return Ok(Condition { path, test: None });
+    }
+
+    let comparison = parser.parse_comparison()?;
+    let literal = parser.rest().trim();
+    let expected = if let Some(text) = literal
+        .strip_prefix('\'')
+        .and_then(|s| s.strip_suffix('\''))
+    {
+        Value::String(text.to_string())
+    } else {
+        serde_json::from_str(literal).map_err(|_| eyre!("Invalid value '{}'", literal))?
+    };
+    Ok(Condition {
+        path,
+        test: Some((comparison, expected)),
+    })
+}
+
+/// Splits on `separator` outside brackets, parentheses and quotes
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => depth -= 1,
+            (None, c) if c == separator && depth == 0 => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, position: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn at_end(&self) -> bool {
+        self.position >= self.text.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        if self.rest().starts_with(prefix) {
+            self.position += prefix.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.position = self.text.len() - trimmed.len();
+    }
+
+    fn error(&self, expected: &str) -> color_eyre::Report {
+        match self.peek() {
+            Some(c) => eyre!(
+                "Expected {} at column {}, found '{}'",
+                expected,
+                self.position + 1,
+                c
+            ),
+            None => eyre!("Expected {} at the end of '{}'", expected, self.text),
+        }
+    }
+
+    /// Steps of a path, up to the end of the text or the first character
+    /// that cannot continue it (such as a comparison operator)
+    fn parse_path(&mut self) -> Result<Vec<Step>> {
+        self.skip_whitespace();
+        let mut steps = Vec::new();
+        // `$` (JSONPath root), `@` (filter item) and `.` (jq identity) all
+        // mean the current value
+        let prefixed = self.eat("$") || self.eat("@");
+        if !prefixed && !self.rest().starts_with('.') && self.is_name_start() {
+            steps.push(Step::Key(self.parse_name()));
+        }
+
+        loop {
+            if self.eat("..") {
+                let step = if self.eat("*") {
+                    Step::Wildcard
+                } else if self.peek() == Some('[') {
+                    self.parse_bracket()?
+                } else if self.is_name_start() {
+                    Step::Key(self.parse_name())
+                } else {
+                    return Err(self.error("a name after '..'"));
+                };
+                steps.push(Step::Descend(Box::new(step)));
+            } else if self.eat(".") {
+                if self.eat("*") {
+                    steps.push(Step::Wildcard);
+                } else if self.peek() == Some('"') {
+                    steps.push(Step::Key(self.parse_quoted()?));
+                } else if self.is_name_start() {
+                    steps.push(Step::Key(self.parse_name()));
+                } else if self.peek() != Some('[') && !self.at_path_end() {
+                    return Err(self.error("a name after '.'"));
+                }
+            } else if self.peek() == Some('[') {
+                steps.push(self.parse_bracket()?);
+            } else {
+                break;
+            }
+        }
+
+        if !self.at_path_end() {
+            return Err(self.error("'.', '[' or the end of the path"));
+        }
+        Ok(steps)
+    }
+
+    fn at_path_end(&self) -> bool {
+        self.rest()
+            .chars()
+            .next()
+            .is_none_or(|c| c.is_whitespace() || "=!<>)".contains(c))
+    }
+
+    fn is_name_start(&self) -> bool {
+        self.peek().is_some_and(is_name_char)
+    }
+
+    fn parse_name(&mut self) -> String {
+        let length = self
+            .rest()
+            .find(|c| !is_name_char(c))
+            .unwrap_or(self.rest().len());
+        let name = &self.rest()[..length];
+        self.position += length;
+        name.to_string()
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        let quote = self.peek().ok_or_else(|| self.error("a quoted key"))?;
+        self.position += 1;
+        let length = self
+            .rest()
+            .find(quote)
+            .ok_or_else(|| eyre!("Unterminated string in '{}'", self.text))?;
+        let key = self.rest()[..length].to_string();
+        self.position += length + 1;
+        Ok(key)
+    }
+
+    fn parse_bracket(&mut self) -> Result<Step> {
+        self.eat("[");
+        self.skip_whitespace();
+
+        let step = if self.peek() == Some(']') || self.eat("*") {
+            Step::Wildcard
+        } else if matches!(self.peek(), Some('\'' | '"')) {
+            Step::Key(self.parse_quoted()?)
+        } else if self.eat("?") {
+            self.skip_whitespace();
+            let end = self
+                .rest()
+                .rfind(']')
+                .ok_or_else(|| eyre!("Unterminated filter in '{}'", self.text))?;
+            let filter = self.rest()[..end].trim();
+            let filter = filter
+                .strip_prefix('(')
+                .and_then(|f| f.strip_suffix(')'))
+                .unwrap_or(filter);
+            let condition = parse_condition(filter)?;
+            self.position += end;
+            Step::Filter(condition)
+        } else {
+            let end = self
+                .rest()
+                .find(']')
+                .ok_or_else(|| eyre!("Missing ']' in '{}'", self.text))?;
+            let inner = self.rest()[..end].trim();
+            let number = |text: &str| -> Result<Option<i64>> {
+                let text = text.trim();
+                if text.is_empty() {
+                    return Ok(None);
+                }
+                text.parse()
+                    .map(Some)
+                    .map_err(|_| eyre!("Invalid index '{}' in '{}'", text, self.text))
+            };
+            let step = match inner.split_once(':') {
+                Some((start, end)) => Step::Slice(number(start)?, number(end)?),
+                None => Step::Index(number(inner)?.ok_or_else(|| self.error("an index"))?),
+            };
+            self.position += end;
+            step
+        };
+
+        self.skip_whitespace();
+        if !self.eat("]") {
+            return Err(self.error("']'"));
+        }
+        Ok(step)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Comparison> {
+        for (operator, comparison) in [
+            ("==", Comparison::Eq),
+            ("!=", Comparison::Ne),
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+        ] {
+            if self.eat(operator) {
+                return Ok(comparison);
+            }
+        }
+        Err(self.error("a comparison (==, !=, <, <=, >, >=)"))
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '$'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn body() -> Value {
+        json!({
+            "data": {"user": {"id": 123, "name": "John Doe"}},
+            "items": [
+                {"id": 1, "price": 5, "tags": ["a"], "active": true},
+                {"id": 2, "price": 15, "tags": [], "active": false},
+                {"id": 3, "price": 8, "tags": ["b", "c"]}
+            ]
+        })
+    }
+
+    fn run(expression: &str) -> Vec<Value> {
+        run_query(&body(), expression).unwrap()
+    }
+
+    #[test]
+    fn test_paths_in_every_syntax() {
+        for expression in [
+            "data.user.id",
+            "$.data.user.id",
+            "$['data']['user'].id",
+            ".data.user.id",
+            "data | user | id",
+        ] {
+            assert_eq!(run(expression), [json!(123)], "{}", expression);
+        }
+        assert_eq!(run("items.1.id"), [json!(2)]);
+        assert_eq!(run("$.items[-1].id"), [json!(3)]);
+        assert_eq!(run(".items[1:].id"), [json!(2), json!(3)]);
+        assert_eq!(run(".items[].id"), [json!(1), json!(2), json!(3)]);
+        assert_eq!(run("$..tags[*]"), [json!("a"), json!("b"), json!("c")]);
+        assert_eq!(run(""), [body()]);
+        assert!(run("data.missing").is_empty());
+    }
+
+    #[test]
+    fn test_filters_and_builtins() {
+        assert_eq!(run("$.items[?(@.price < 10)].id"), [json!(1), json!(3)]);
+        assert_eq!(run("$.items[?(@.active)].id"), [json!(1)]);
+        assert_eq!(
+            run(".items[] | select(.id != 2) | .id"),
+            [json!(1), json!(3)]
+        );
+        assert_eq!(run("$.items[?(@.tags[0] == 'b')].id"), [json!(3)]);
+        assert_eq!(run(".items | length"), [json!(3)]);
+        assert_eq!(run(".data.user | keys"), [json!(["id", "name"])]);
+        assert_eq!(run("[.items[].id] | .[1]"), [json!(2)]);
+        assert_eq!(
+            query_value(&body(), ".items[].price").unwrap(),
+            json!([5, 15, 8])
+        );
+    }
+
+    #[test]
+    fn test_errors_point_at_the_problem() {
+        let error = Query::parse("$.items[0").unwrap_err().to_string();
+        assert!(error.contains("Missing ']'"), "{}", error);
+        let error = Query::parse("$.items[?(@.id ~ 1)]")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("comparison"), "{}", error);
+        assert!(Query::parse("$.items[x]").is_err());
+    }
+}
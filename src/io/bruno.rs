@@ -0,0 +1,595 @@
+use crate::core::{HttpMethod, Request, RequestBody};
+use crate::io::{ImportReport, ImportResult, ImportedEnvironment, basic_auth, form_urlencode};
+use color_eyre::{Result, eyre::WrapErr};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Blocks whose body is free text rather than `key: value` pairs
+const TEXT_BLOCKS: &[&str] = &[
+    "body:json",
+    "body:text",
+    "body:xml",
+    "body:graphql",
+    "body:graphql:vars",
+    "body:sparql",
+    "docs",
+    "tests",
+];
+
+const METHOD_BLOCKS: &[&str] = &["get", "post", "put", "patch", "delete", "head", "options"];
+
+/// One top-level `name { ... }` or `name [ ... ]` block of a `.bru` file
+#[derive(Debug, Default)]
+struct BruBlock {
+    name: String,
+    /// `key: value` entries; keys prefixed with `~` are disabled
+    pairs: Vec<(String, String)>,
+    /// Dedented contents of text and list blocks
+    text: String,
+}
+
+impl BruBlock {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn enabled_pairs(&self) -> impl Iterator<Item = &(String, String)> {
+        self.pairs.iter().filter(|(k, _)| !k.starts_with('~'))
+    }
+}
+
+fn parse_bru(content: &str) -> Vec<BruBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(BruBlock, char)> = None;
+
+    for line in content.lines() {
+        match current.as_mut() {
+            None => {
+                let trimmed = line.trim_end();
+                let opener = trimmed
+                    .strip_suffix(" {")
+                    .map(|name| (name, '}'))
+                    .or_else(|| trimmed.strip_suffix(" [").map(|name| (name, ']')));
+                if let Some((name, closer)) = opener {
+                    let block = BruBlock {
+                        name: name.trim().to_string(),
+                        ..Default::default()
+                    };
+                    current = Some((block, closer));
+                }
+            }
+            Some((block, closer)) => {
+                if line.trim_end() == closer.to_string() {
+                    blocks.push(current.take().map(|(b, _)| b).unwrap_or_default());
+                    continue;
+                }
+
+                if *closer == ']' || TEXT_BLOCKS.contains(&block.name.as_str()) {
+                    block.text.push_str(line.strip_prefix("  ").unwrap_or(line));
+                    block.text.push('\n');
+                } else if let Some((key, value)) = line.trim().split_once(':') {
+                    block
+                        .pairs
+                        .push((key.trim().to_string(), value.trim().to_string()));
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+/// Imports a Bruno collection directory: every `.bru` request file becomes a
+/// request, subdirectories become folders and `environments/*.bru` become
+/// netbook environments
+pub fn import_bruno(dir: &Path) -> Result<ImportResult> {
+    if !dir.is_dir() {
+        return Err(color_eyre::eyre::eyre!(
+            "Bruno import expects a collection directory: {}",
+            dir.display()
+        ));
+    }
+
+    let mut result = ImportResult::new("Bruno");
+    collect_requests(dir, dir, &mut result)?;
+
+    let environments_dir = dir.join("environments");
+    if environments_dir.is_dir() {
+        for path in sorted_entries(&environments_dir)? {
+            if path.extension().and_then(|s| s.to_str()) != Some("bru") {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+                .to_string();
+            result
+                .environments
+                .push(convert_environment(&name, &content, &mut result.report));
+        }
+    }
+
+    if dir.join("collection.bru").exists() {
+        result
+            .report
+            .warn("collection.bru: collection-level headers, auth and scripts were not imported");
+    }
+
+    Ok(result)
+}
+
+fn collect_requests(root: &Path, dir: &Path, result: &mut ImportResult) -> Result<()> {
+    let mut requests = Vec::new();
+
+    for path in sorted_entries(dir)? {
+        let file_name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+
+        if path.is_dir() {
+            let skip = file_name.starts_with('.')
+                || file_name == "node_modules"
+                || (dir == root && file_name == "environments");
+            if !skip {
+                collect_requests(root, &path, result)?;
+            }
+            continue;
+        }
+
+        if path.extension().and_then(|s| s.to_str()) != Some("bru")
+            || file_name == "collection.bru"
+            || file_name == "folder.bru"
+        {
+            continue;
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let folder = dir
+            .strip_prefix(root)
+            .ok()
+            .filter(|rel| !rel.as_os_str().is_empty())
+            .map(|rel| rel.to_string_lossy().replace('\\', "/"));
+
+        if let Some((seq, request)) =
+            convert_request(&content, file_name, folder, &mut result.report)
+        {
+            requests.push((seq, request));
+        }
+    }
+
+    // Bruno orders requests inside a folder by their `seq`
+    requests.sort_by_key(|(seq, _)| *seq);
+    result
+        .requests
+        .extend(requests.into_iter().map(|(_, request)| request));
+    Ok(())
+}
+
+fn convert_request(
+    content: &str,
+    file_name: &str,
+    folder: Option<String>,
+    report: &mut ImportReport,
+) -> Option<(u32, Request)> {
+    // Every field may read the process environment, the URL as well as
+    // headers, query, auth and body
+    let blocks = parse_bru(&rewrite_process_env(content));
+    let block = |name: &str| blocks.iter().find(|b| b.name == name);
+
+    let meta = block("meta");
+    let name = meta
+        .and_then(|m| m.get("name"))
+        .map(str::to_string)
+        .unwrap_or_else(|| file_name.trim_end_matches(".bru").to_string());
+    let seq = meta
+        .and_then(|m| m.get("seq"))
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(u32::MAX);
+
+    if let Some(kind) = meta.and_then(|m| m.get("type"))
+        && kind != "http"
+    {
+        report.skip(format!("{} ({} requests are not supported)", name, kind));
+        return None;
+    }
+
+    let Some(method_block) = blocks
+        .iter()
+        .find(|b| METHOD_BLOCKS.contains(&b.name.as_str()))
+    else {
+        report.skip(format!("{} (no HTTP method block)", name));
+        return None;
+    };
+    let method = method_block.name.parse::<HttpMethod>().ok()?;
+
+    let query_block = block("params:query").or_else(|| block("query"));
+    let mut url = method_block.get("url").unwrap_or_default();
+    // Bruno writes the query both into the URL and as params; the params
+    // also list the disabled ones, so they win
+    if query_block.is_some() {
+        url = url.split_once('?').map_or(url, |(path, _)| path);
+    }
+    let path_params: HashMap<&str, &str> = block("params:path")
+        .map(|b| {
+            b.enabled_pairs()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Only whole `:name` segments are parameters
+    let url = url
+        .split('/')
+        .map(|segment| {
+            segment
+                .strip_prefix(':')
+                .and_then(|key| path_params.get(key))
+                .copied()
+                .unwrap_or(segment)
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let query: HashMap<String, String> = query_block
+        .map(|b| b.enabled_pairs().cloned().collect())
+        .unwrap_or_default();
+
+    let mut headers: HashMap<String, String> = block("headers")
+        .map(|b| b.enabled_pairs().cloned().collect())
+        .unwrap_or_default();
+
+    match method_block.get("auth").unwrap_or("none") {
+        "none" => {}
+        "inherit" => report.warn(format!(
+            "{}: inherits collection auth, which was not imported",
+            name
+        )),
+        "bearer" => {
+            let token = block("auth:bearer")
+                .and_then(|b| b.get("token"))
+                .unwrap_or_default();
+            headers.insert("Authorization".to_string(), format!("Bearer {}", token));
+        }
+        "basic" => {
+            let auth = block("auth:basic");
+            let field = |key| auth.and_then(|b| b.get(key)).unwrap_or_default();
+            match basic_auth(field("username"), field("password")) {
+                Some(value) => {
+                    headers.insert("Authorization".to_string(), value);
+                }
+                None => report.warn(format!(
+                    "{}: basic auth with variables can't be encoded; add the Authorization header manually",
+                    name
+                )),
+            }
+        }
+        other => report.warn(format!(
+            "{}: '{}' auth is not supported; add the header manually",
+            name, other
+        )),
+    }
+
+    let body = match method_block.get("body").unwrap_or("none") {
+        "none" => None,
+        "json" => block("body:json").map(|b| {
+            serde_json::from_str(&b.text)
+                .map(RequestBody::Json)
+                .unwrap_or_else(|_| RequestBody::Text(b.text.trim_end().to_string()))
+        }),
+        "text" | "xml" => {
+            let kind = method_block.get("body").unwrap_or_default();
+            block(&format!("body:{}", kind)).map(|b| {
+                let content_type = if kind == "xml" {
+                    "application/xml"
+                } else {
+                    "text/plain"
+                };
+                headers
+                    .entry("Content-Type".to_string())
+                    .or_insert_with(|| content_type.to_string());
+                RequestBody::Text(b.text.trim_end().to_string())
+            })
+        }
+        "formUrlEncoded" => block("body:form-urlencoded").map(|b| {
+            headers
+                .entry("Content-Type".to_string())
+                .or_insert_with(|| "application/x-www-form-urlencoded".to_string());
+            let pairs: Vec<(String, String)> = b.enabled_pairs().cloned().collect();
+            RequestBody::Text(form_urlencode(&pairs))
+        }),
+        other => {
+            report.warn(format!(
+                "{}: '{}' bodies are not supported; body dropped",
+                name, other
+            ));
+            None
+        }
+    };
+
+    if blocks
+        .iter()
+        .any(|b| b.name.starts_with("script:") || b.name == "tests")
+    {
+        report.warn(format!("{}: scripts and tests were not imported", name));
+    }
+
+    let notes = block("docs")
+        .map(|b| b.text.trim().to_string())
+        .filter(|docs| !docs.is_empty());
+
+    Some((
+        seq,
+        Request {
+            name,
+            method,
+            url,
+            headers,
+            query,
+            body,
+            notes,
+            folder,
+            source: None,
+            disabled_headers: HashMap::new(),
+            disabled_query: HashMap::new(),
+            extract: HashMap::new(),
+            id: uuid::Uuid::new_v4(),
+        },
+    ))
+}
+
+fn convert_environment(
+    name: &str,
+    content: &str,
+    report: &mut ImportReport,
+) -> ImportedEnvironment {
+    let blocks = parse_bru(content);
+    let mut environment = ImportedEnvironment {
+        name: Some(name.to_string()),
+        ..Default::default()
+    };
+
+    if let Some(vars) = blocks.iter().find(|b| b.name == "vars") {
+        environment.variables = vars
+            .enabled_pairs()
+            .map(|(k, v)| (k.clone(), rewrite_process_env(v)))
+            .collect();
+    }
+
+    if let Some(secrets) = blocks.iter().find(|b| b.name == "vars:secret") {
+        for secret in secrets.text.split([',', '\n']).map(str::trim) {
+            if !secret.is_empty() {
+                report.warn(format!(
+                    "Environment '{}': secret '{}' is not stored in the export; set it in .netbook/.env.{}",
+                    name,
+                    secret,
+                    crate::io::environment_slug(name)
+                ));
+            }
+        }
+    }
+
+    environment
+}
+
+/// Bruno's `{{process.env.NAME}}` reads the process environment, which
+/// netbook's `{{NAME}}` already falls back to
+fn rewrite_process_env(value: &str) -> String {
+    value.replace("{{process.env.", "{{")
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const GET_USER: &str = r#"meta {
+  name: Get User
+  type: http
+  seq: 2
+}
+
+get {
+  url: {{baseUrl}}/users/:id
+  body: none
+  auth: bearer
+}
+
+params:query {
+  expand: profile
+  ~debug: true
+}
+
+params:path {
+  id: {{userId}}
+}
+
+headers {
+  Accept: application/json
+}
+
+auth:bearer {
+  token: {{token}}
+}
+
+docs {
+  Fetches a single user.
+}
+"#;
+
+    const CREATE_USER: &str = r#"meta {
+  name: Create User
+  type: http
+  seq: 1
+}
+
+post {
+  url: {{baseUrl}}/users
+  body: json
+  auth: none
+}
+
+body:json {
+  {
+    "name": "Ada",
+    "tags": ["admin"]
+  }
+}
+"#;
+
+    #[test]
+    fn test_parse_bru_blocks() {
+        let blocks = parse_bru(GET_USER);
+        let names: Vec<&str> = blocks.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "meta",
+                "get",
+                "params:query",
+                "params:path",
+                "headers",
+                "auth:bearer",
+                "docs"
+            ]
+        );
+        assert_eq!(blocks[1].get("url"), Some("{{baseUrl}}/users/:id"));
+        assert_eq!(blocks[2].enabled_pairs().count(), 1);
+    }
+
+    #[test]
+    fn test_import_bruno_directory() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("bruno.json"), r#"{"name": "Shop"}"#).unwrap();
+        std::fs::create_dir_all(root.join("users")).unwrap();
+        std::fs::write(root.join("users").join("Get User.bru"), GET_USER).unwrap();
+        std::fs::write(root.join("users").join("Create User.bru"), CREATE_USER).unwrap();
+        std::fs::create_dir_all(root.join("environments")).unwrap();
+        std::fs::write(
+            root.join("environments").join("Staging.bru"),
+            "vars {\n  baseUrl: https://staging.example.com\n}\nvars:secret [\n  token\n]\n",
+        )
+        .unwrap();
+
+        let result = import_bruno(root).unwrap();
+        assert_eq!(result.requests.len(), 2);
+
+        // Ordered by seq within the folder
+        let create = &result.requests[0];
+        assert_eq!(create.name, "Create User");
+        assert_eq!(create.folder.as_deref(), Some("users"));
+        match &create.body {
+            Some(RequestBody::Json(json)) => assert_eq!(json["tags"][0], "admin"),
+            other => panic!("expected JSON body, got {:?}", other),
+        }
+
+        let get = &result.requests[1];
+        assert_eq!(get.url, "{{baseUrl}}/users/{{userId}}");
+        assert_eq!(get.query.len(), 1);
+        assert_eq!(
+            get.headers.get("Authorization"),
+            Some(&"Bearer {{token}}".to_string())
+        );
+        assert_eq!(get.notes.as_deref(), Some("Fetches a single user."));
+
+        assert_eq!(result.environments.len(), 1);
+        assert_eq!(result.environments[0].name.as_deref(), Some("Staging"));
+        assert!(result.report.warnings.iter().any(|w| w.contains("token")));
+    }
+
+    #[test]
+    fn test_convert_request_templates() {
+        let content = r#"meta {
+  name: Login
+  type: http
+}
+
+post {
+  url: {{process.env.BASE_URL}}/login
+  body: formUrlEncoded
+  auth: basic
+}
+
+params:query {
+  client: {{process.env.CLIENT_ID}}
+}
+
+headers {
+  X-Trace: {{process.env.TRACE}}
+}
+
+auth:basic {
+  username: {{user}}
+  password: {{process.env.PASSWORD}}
+}
+
+body:form-urlencoded {
+  grant type: password
+  secret: {{process.env.SECRET}}
+}
+"#;
+        let mut report = ImportReport::new("Bruno");
+        let (_, request) = convert_request(content, "Login.bru", None, &mut report).unwrap();
+        assert_eq!(request.url, "{{BASE_URL}}/login");
+        assert_eq!(request.query["client"], "{{CLIENT_ID}}");
+        assert_eq!(request.headers["X-Trace"], "{{TRACE}}");
+        assert!(matches!(
+            &request.body,
+            Some(RequestBody::Text(t)) if t == "grant+type=password&secret={{SECRET}}"
+        ));
+        assert!(!request.headers.contains_key("Authorization"));
+        assert!(report.warnings.iter().any(|w| w.contains("basic auth")));
+
+        let json = "post {\n  url: https://example.com\n  body: json\n}\n\nbody:json {\n  {\"key\": \"{{process.env.API_KEY}}\"}\n}\n";
+        let (_, request) = convert_request(json, "Json.bru", None, &mut report).unwrap();
+        match &request.body {
+            Some(RequestBody::Json(json)) => assert_eq!(json["key"], "{{API_KEY}}"),
+            other => panic!("expected JSON body, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_convert_request_url() {
+        let content = r#"get {
+  url: {{baseUrl}}/users/:id/items/:idx?page=2&sort=name
+}
+
+params:query {
+  page: 2
+  sort: name
+  ~debug: true
+}
+
+params:path {
+  id: 7
+  idx: {{index}}
+}
+"#;
+        let mut report = ImportReport::new("Bruno");
+        let (_, request) = convert_request(content, "Items.bru", None, &mut report).unwrap();
+        assert_eq!(request.url, "{{baseUrl}}/users/7/items/{{index}}");
+        assert_eq!(request.query.len(), 2);
+        assert_eq!(request.query["page"], "2");
+
+        // Without params the query stays in the URL
+        let content = "get {\n  url: https://example.com/a?b=c\n}\n";
+        let (_, request) = convert_request(content, "A.bru", None, &mut report).unwrap();
+        assert_eq!(request.url, "https://example.com/a?b=c");
+    }
+}
@@ -0,0 +1,130 @@
+use crate::io::Config;
+use base64::Engine;
+use color_eyre::{Result, eyre::eyre};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Where copied text goes, from the `clipboard` setting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Clipboard {
+    /// The OSC 52 escape sequence, which the terminal turns into a clipboard
+    /// write. Works over SSH, if the terminal allows it.
+    Osc52,
+    /// A program that reads the text from stdin, e.g. `wl-copy`
+    Command(Vec<String>),
+}
+
+impl Clipboard {
+    /// `auto` (the default), `osc52`, or a command line
+    pub fn from_config(config: &Config) -> Result<Self> {
+        match config.clipboard.as_deref().map(str::trim) {
+            None | Some("auto") => Ok(Self::detect()),
+            Some("osc52") => Ok(Self::Osc52),
+            Some(command) => {
+                let words: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+                if words.is_empty() {
+                    return Err(eyre!("clipboard: expected auto, osc52 or a command"));
+                }
+                Ok(Self::Command(words))
+            }
+        }
+    }
+
+    /// OSC 52 in an SSH session, where a local program would copy on the
+    /// remote machine; otherwise the first clipboard program found
+    pub fn detect() -> Self {
+        let set = |name| std::env::var_os(name).is_some();
+        if set("SSH_CONNECTION") || set("SSH_TTY") {
+            return Self::Osc52;
+        }
+        let candidates: [(&[&str], bool); 4] = [
+            (&["wl-copy"], set("WAYLAND_DISPLAY")),
+            (&["pbcopy"], cfg!(target_os = "macos")),
+            (&["xclip", "-selection", "clipboard"], set("DISPLAY")),
+            (&["xsel", "--clipboard", "--input"], set("DISPLAY")),
+        ];
+        candidates
+            .into_iter()
+            .find(|(command, usable)| *usable && on_path(command[0]))
+            .map_or(Self::Osc52, |(command, _)| {
+                Self::Command(command.iter().map(|w| w.to_string()).collect())
+            })
+    }
+
+    /// For the status bar: `OSC 52` or the program's name
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Osc52 => "OSC 52",
+            Self::Command(command) => &command[0],
+        }
+    }
+
+    pub fn copy(&self, text: &str) -> Result<()> {
+        match self {
+            Self::Osc52 => {
+                let mut stdout = std::io::stdout();
+                stdout.write_all(osc52_sequence(text).as_bytes())?;
+                stdout.flush()?;
+            }
+            Self::Command(command) => {
+                // Output would end up on top of the TUI. Programs like xclip
+                // keep running to serve the selection, so only the exit
+                // status of the one we started is waited for.
+                let mut child = Command::new(&command[0])
+                    .args(&command[1..])
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()
+                    .map_err(|e| eyre!("could not run {}: {}", command[0], e))?;
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(text.as_bytes())?;
+                }
+                let status = child.wait()?;
+                if !status.success() {
+                    return Err(eyre!("{} exited with {}", command[0], status));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sets the system clipboard (`c`) to `text`
+fn osc52_sequence(text: &str) -> String {
+    format!(
+        "\x1b]52;c;{}\x07",
+        base64::engine::general_purpose::STANDARD.encode(text)
+    )
+}
+
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_from_config() {
+        let clipboard =
+            |yaml: &str| Clipboard::from_config(&crate::io::parse_config(yaml).unwrap());
+        assert_eq!(clipboard("clipboard: osc52").unwrap(), Clipboard::Osc52);
+        assert_eq!(
+            clipboard("clipboard: xclip -selection clipboard").unwrap(),
+            Clipboard::Command(vec![
+                "xclip".to_string(),
+                "-selection".to_string(),
+                "clipboard".to_string()
+            ])
+        );
+        assert!(clipboard("clipboard: ' '").is_err());
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
@@ -1,9 +1,58 @@
-use crate::core::Collection;
+use crate::core::{Collection, Request};
 use color_eyre::{Result, eyre::WrapErr};
+use std::collections::HashSet;
 use std::path::Path;
+use uuid::Uuid;
 
+/// Loads a collection, giving requests without an id (or with one copied from
+/// another request) one in memory. The file isn't touched; the ids are written
+/// the next time the collection is saved.
 pub fn load_collection(path: impl AsRef<Path>) -> Result<Collection> {
-    let path = path.as_ref();
+    let mut collection = read_collection(path.as_ref())?;
+    ensure_request_ids(&mut collection);
+    Ok(collection)
+}
+
+/// Returns whether any request was given a new id. New ids are derived from
+/// the request's folder and name, so every load of an unsaved collection
+/// agrees on them and history recorded in between still matches.
+pub fn ensure_request_ids(collection: &mut Collection) -> bool {
+    let mut seen = HashSet::new();
+    let mut changed = false;
+    for request in collection.iter_mut() {
+        if request.id.is_nil() || seen.contains(&request.id) {
+            request.id = (0..)
+                .map(|n| derived_id(request, n))
+                .find(|id| !seen.contains(id))
+                .unwrap_or_else(Uuid::new_v4);
+            changed = true;
+        }
+        seen.insert(request.id);
+    }
+    changed
+}
+
+/// 128-bit FNV-1a of the folder, name and `n`, as a version 8 UUID
+fn derived_id(request: &Request, n: u32) -> Uuid {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+    let key = format!(
+        "{}\0{}\0{}",
+        request.folder.as_deref().unwrap_or_default(),
+        request.name,
+        n
+    );
+    let hash = key.bytes().fold(OFFSET, |hash, byte| {
+        (hash ^ u128::from(byte)).wrapping_mul(PRIME)
+    });
+    uuid::Builder::from_custom_bytes(hash.to_be_bytes()).into_uuid()
+}
+
+fn read_collection(path: &Path) -> Result<Collection> {
+    if path.is_dir() {
+        return crate::io::load_collection_dir(path);
+    }
+
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("Failed to read collection file: {}", path.display()))?;
 
@@ -11,10 +60,10 @@ pub fn load_collection(path: impl AsRef<Path>) -> Result<Collection> {
         || path.extension().and_then(|s| s.to_str()) == Some("yml")
     {
         serde_yaml::from_str::<Collection>(&content)
-            .with_context(|| format!("Failed to parse YAML collection: {}", path.display()))?
+            .map_err(|_| crate::io::parse_error(path, &content, true))?
     } else {
         serde_json::from_str::<Collection>(&content)
-            .with_context(|| format!("Failed to parse JSON collection: {}", path.display()))?
+            .map_err(|_| crate::io::parse_error(path, &content, true))?
     };
 
     Ok(collection)
@@ -22,6 +71,10 @@ pub fn load_collection(path: impl AsRef<Path>) -> Result<Collection> {
 
 pub fn save_collection(collection: &Collection, path: impl AsRef<Path>) -> Result<()> {
     let path = path.as_ref();
+    if path.is_dir() {
+        return crate::io::save_collection_dir(collection, path);
+    }
+
     let content = if path.extension().and_then(|s| s.to_str()) == Some("yaml")
         || path.extension().and_then(|s| s.to_str()) == Some("yml")
     {
@@ -41,7 +94,7 @@ pub fn save_collection(collection: &Collection, path: impl AsRef<Path>) -> Resul
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::{HttpMethod, RequestBody};
+    use crate::core::HttpMethod;
     use std::collections::HashMap;
     use tempfile::tempdir;
 
@@ -87,6 +140,12 @@ mod tests {
                 serde_json::json!({"test": "value"}),
             )),
             notes: Some("Test note".to_string()),
+            folder: None,
+            source: None,
+            disabled_headers: HashMap::new(),
+            disabled_query: HashMap::new(),
+            extract: HashMap::new(),
+            id: uuid::Uuid::new_v4(),
         }];
 
         save_collection(&collection, &file_path).unwrap();
@@ -96,4 +155,44 @@ mod tests {
         assert_eq!(loaded[0].name, "Test Request");
         assert!(matches!(loaded[0].method, crate::core::HttpMethod::Post));
     }
+
+    #[test]
+    fn test_load_assigns_stable_ids() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("test.json");
+
+        // One request without an id and two sharing a copy-pasted one
+        let id = uuid::Uuid::new_v4();
+        let content = serde_json::json!([
+            {"name": "A", "method": "GET", "url": "https://example.com/a"},
+            {"id": id, "name": "B", "method": "GET", "url": "https://example.com/b"},
+            {"id": id, "name": "C", "method": "GET", "url": "https://example.com/c"}
+        ]);
+        std::fs::write(&file_path, content.to_string()).unwrap();
+
+        let first = load_collection(&file_path).unwrap();
+        assert!(!first[0].id.is_nil());
+        assert_eq!(first[1].id, id);
+        assert_ne!(first[2].id, id);
+        // Loading doesn't write to the file
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            content.to_string()
+        );
+
+        let second = load_collection(&file_path).unwrap();
+        let ids = |c: &Collection| c.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(&first), ids(&second));
+
+        // Same names in one folder still get different ids
+        let mut copies = vec![first[0].clone(), first[0].clone()];
+        copies[0].id = Uuid::nil();
+        copies[1].id = Uuid::nil();
+        ensure_request_ids(&mut copies);
+        assert_ne!(copies[0].id, copies[1].id);
+
+        // Saving persists them
+        save_collection(&first, &file_path).unwrap();
+        assert_eq!(ids(&read_collection(&file_path).unwrap()), ids(&first));
+    }
 }
@@ -0,0 +1,126 @@
+use color_eyre::{Result, eyre::eyre};
+use directories::ProjectDirs;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// User settings from `config.yaml` in netbook's config directory
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Built-in theme or one of `themes`; `dark` when unset
+    pub theme: Option<String>,
+    /// User-defined palettes by name
+    pub themes: HashMap<String, ThemePalette>,
+    /// Keys by mode and action (`normal: {history: [H]}`), replacing the
+    /// defaults of the listed actions
+    pub keymap: HashMap<String, HashMap<String, Vec<String>>>,
+    /// `auto`, `osc52`, or a command reading the copied text from stdin
+    pub clipboard: Option<String>,
+    /// Where response history is kept
+    pub history: HistoryConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistoryConfig {
+    pub location: HistoryLocation,
+    /// Entries kept, newest first; `null` for no limit
+    pub max_entries: Option<usize>,
+    /// Entries older than this are dropped
+    pub max_age_days: Option<u32>,
+    /// Size of the history file, in megabytes
+    pub max_size_mb: Option<u64>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            location: HistoryLocation::default(),
+            max_entries: Some(1000),
+            max_age_days: None,
+            max_size_mb: Some(50),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryLocation {
+    /// `history.jsonl` in the collection's `.netbook` directory
+    #[default]
+    Project,
+    /// One file in netbook's data directory, shared by all collections
+    Global,
+}
+
+/// Colors overriding those of a built-in theme, by name (`accent`,
+/// `method_get`, ...)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ThemePalette {
+    /// Built-in theme the palette starts from; `dark` when unset
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub colors: HashMap<String, String>,
+}
+
+/// `NETBOOK_CONFIG` when set, otherwise `config.yaml` in the config directory
+pub fn get_config_file_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("NETBOOK_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    ProjectDirs::from("com", "netbook", "netbook").map(|dirs| dirs.config_dir().join("config.yaml"))
+}
+
+/// The user's config, or the defaults when there is no config file
+pub fn load_config() -> Result<Config> {
+    match get_config_file_path() {
+        Some(path) if path.exists() => {
+            let content = std::fs::read_to_string(&path)?;
+            parse_config(&content).map_err(|e| eyre!("{}: {}", path.display(), e))
+        }
+        _ => Ok(Config::default()),
+    }
+}
+
+pub fn parse_config(content: &str) -> Result<Config> {
+    if content.trim().is_empty() {
+        return Ok(Config::default());
+    }
+    Ok(serde_yaml::from_str(content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        assert!(parse_config("").unwrap().theme.is_none());
+
+        let config =
+            parse_config("theme: mine\nthemes:\n  mine:\n    base: light\n    accent: blue\n")
+                .unwrap();
+        assert_eq!(config.theme.as_deref(), Some("mine"));
+        let palette = &config.themes["mine"];
+        assert_eq!(palette.base.as_deref(), Some("light"));
+        assert_eq!(palette.colors["accent"], "blue");
+
+        let config = parse_config("keymap:\n  normal:\n    history: [H, ctrl+h]\n").unwrap();
+        assert_eq!(config.keymap["normal"]["history"], ["H", "ctrl+h"]);
+
+        assert_eq!(config.history.location, HistoryLocation::Project);
+        assert_eq!(config.history.max_entries, Some(1000));
+        let config =
+            parse_config("history:\n  location: global\n  max_entries: null\n  max_age_days: 30\n")
+                .unwrap();
+        assert_eq!(config.history.location, HistoryLocation::Global);
+        assert_eq!(config.history.max_entries, None);
+        assert_eq!(config.history.max_age_days, Some(30));
+        assert_eq!(config.history.max_size_mb, Some(50));
+        assert!(parse_config("history:\n  location: home\n").is_err());
+
+        assert!(parse_config("colour: red\n").is_err());
+    }
+}
@@ -0,0 +1,443 @@
+use crate::core::{Collection, Request};
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Optional per-folder file holding defaults for every request below it
+pub const FOLDER_DEFAULTS_FILE: &str = "_folder.yaml";
+
+/// Values a folder contributes to its requests; a request's own entries win
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FolderDefaults {
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub query: HashMap<String, String>,
+}
+
+impl FolderDefaults {
+    /// Layers a subfolder's defaults on top of its parent's
+    fn inherit(&self, child: FolderDefaults) -> FolderDefaults {
+        let mut merged = self.clone();
+        merged.headers.extend(child.headers);
+        merged.query.extend(child.query);
+        merged
+    }
+
+    fn apply(&self, request: &mut Request) {
+        for (key, value) in &self.headers {
+            request
+                .headers
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+        for (key, value) in &self.query {
+            request
+                .query
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    /// Drops entries that only repeat the folder default so they aren't
+    /// copied into the request file on save
+    fn strip(&self, request: &mut Request) {
+        request
+            .headers
+            .retain(|key, value| self.headers.get(key) != Some(value));
+        request
+            .query
+            .retain(|key, value| self.query.get(key) != Some(value));
+    }
+}
+
+/// Loads a directory collection: one JSON or YAML file per request, with
+/// subdirectories becoming folders. Files starting with `_` or `.` are ignored.
+pub fn load_collection_dir(dir: &Path) -> Result<Collection> {
+    let mut collection = Vec::new();
+    load_folder(dir, dir, &FolderDefaults::default(), &mut collection)?;
+    Ok(collection)
+}
+
+fn load_folder(
+    root: &Path,
+    dir: &Path,
+    inherited: &FolderDefaults,
+    collection: &mut Collection,
+) -> Result<()> {
+    let defaults = inherited.inherit(read_folder_defaults(dir)?);
+    let folder = folder_name(root, dir);
+
+    for path in sorted_entries(dir)? {
+        if path.is_dir() {
+            if !is_hidden(&path) {
+                load_folder(root, &path, &defaults, collection)?;
+            }
+        } else if is_request_file(&path) {
+            let mut request = read_request_file(&path)?;
+            request.folder = folder.clone();
+            defaults.apply(&mut request);
+            collection.push(request);
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a directory collection, touching only files whose request changed.
+/// New requests get a file named after the request, and the file of a request
+/// moved to another folder is removed. Other files are left alone, even ones
+/// that weren't loaded: they may have been added since, so deleting a request
+/// is up to [`remove_request_file`].
+pub fn save_collection_dir(collection: &Collection, dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    // Files that still belong to a request in the folder it was loaded from
+    let claimed: HashSet<PathBuf> = collection
+        .iter()
+        .filter_map(|request| {
+            let source = request.source.as_ref()?;
+            (source.parent() == Some(folder_dir(dir, request).as_path())).then(|| source.clone())
+        })
+        .collect();
+    let mut written = HashSet::new();
+
+    for request in collection {
+        let target_dir = folder_dir(dir, request);
+        let path = match &request.source {
+            Some(source) if claimed.contains(source) && !written.contains(source) => source.clone(),
+            _ => new_request_path(&target_dir, request, &claimed, &written),
+        };
+
+        let defaults = folder_defaults_for(dir, &target_dir)?;
+        let mut stored = request.clone();
+        stored.folder = None;
+        defaults.strip(&mut stored);
+
+        if !file_matches(&path, &stored)? {
+            std::fs::create_dir_all(&target_dir)?;
+            std::fs::write(&path, serialize_request(&stored, &path)?)
+                .with_context(|| format!("Failed to write request file: {}", path.display()))?;
+        }
+        written.insert(path);
+    }
+
+    for source in collection.iter().filter_map(|r| r.source.as_ref()) {
+        if !written.contains(source) && source.is_file() {
+            std::fs::remove_file(source)
+                .with_context(|| format!("Failed to remove request file: {}", source.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the file a request deleted from a directory collection was
+/// loaded from
+pub fn remove_request_file(request: &Request) -> Result<()> {
+    match &request.source {
+        Some(source) if source.is_file() => std::fs::remove_file(source)
+            .with_context(|| format!("Failed to remove request file: {}", source.display())),
+        _ => Ok(()),
+    }
+}
+
+fn read_request_file(path: &Path) -> Result<Request> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read request file: {}", path.display()))?;
+
+    let mut value: serde_json::Value = if is_yaml(path) {
+        serde_yaml::from_str(&content).map_err(|_| crate::io::parse_error(path, &content, false))?
+    } else {
+        serde_json::from_str(&content).map_err(|_| crate::io::parse_error(path, &content, false))?
+    };
+
+    // The file name doubles as the request name when none is given
+    if let Some(object) = value.as_object_mut()
+        && !object.contains_key("name")
+    {
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        object.insert("name".to_string(), serde_json::Value::from(stem));
+    }
+
+    let mut request: Request =
+        serde_json::from_value(value).map_err(|_| crate::io::parse_error(path, &content, false))?;
+    request.source = Some(path.to_path_buf());
+    Ok(request)
+}
+
+fn serialize_request(request: &Request, path: &Path) -> Result<String> {
+    if is_yaml(path) {
+        serde_yaml::to_string(request).with_context(|| "Failed to serialize request to YAML")
+    } else {
+        serde_json::to_string_pretty(request).with_context(|| "Failed to serialize request to JSON")
+    }
+}
+
+/// Whether the file already holds exactly this request, ignoring formatting
+fn file_matches(path: &Path, request: &Request) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let Ok(mut existing) = read_request_file(path) else {
+        return Ok(false);
+    };
+    existing.folder = None;
+    Ok(serde_json::to_value(&existing)? == serde_json::to_value(request)?)
+}
+
+fn read_folder_defaults(dir: &Path) -> Result<FolderDefaults> {
+    let path = dir.join(FOLDER_DEFAULTS_FILE);
+    if !path.exists() {
+        return Ok(FolderDefaults::default());
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read folder defaults: {}", path.display()))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse folder defaults: {}", path.display()))
+}
+
+/// Merged defaults from the collection root down to `dir`
+fn folder_defaults_for(root: &Path, dir: &Path) -> Result<FolderDefaults> {
+    let mut defaults = read_folder_defaults(root)?;
+    if let Ok(relative) = dir.strip_prefix(root) {
+        let mut current = root.to_path_buf();
+        for component in relative.components() {
+            current.push(component);
+            defaults = defaults.inherit(read_folder_defaults(&current)?);
+        }
+    }
+    Ok(defaults)
+}
+
+fn folder_name(root: &Path, dir: &Path) -> Option<String> {
+    dir.strip_prefix(root)
+        .ok()
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .map(|relative| {
+            relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+}
+
+fn folder_dir(root: &Path, request: &Request) -> PathBuf {
+    let mut dir = root.to_path_buf();
+    if let Some(folder) = &request.folder {
+        dir.extend(folder.split('/').filter(|segment| !segment.is_empty()));
+    }
+    dir
+}
+
+fn new_request_path(
+    dir: &Path,
+    request: &Request,
+    claimed: &HashSet<PathBuf>,
+    written: &HashSet<PathBuf>,
+) -> PathBuf {
+    let slug = file_slug(&request.name);
+    let mut candidate = dir.join(format!("{}.json", slug));
+    let mut suffix = 2;
+    while claimed.contains(&candidate) || written.contains(&candidate) {
+        candidate = dir.join(format!("{}-{}.json", slug, suffix));
+        suffix += 1;
+    }
+    candidate
+}
+
+fn file_slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        "request".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+pub(crate) fn request_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in sorted_entries(dir)? {
+        if path.is_dir() {
+            if !is_hidden(&path) {
+                files.extend(request_files(&path)?);
+            }
+        } else if is_request_file(&path) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn is_request_file(path: &Path) -> bool {
+    let extension = path.extension().and_then(|s| s.to_str());
+    let name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    matches!(extension, Some("json" | "yaml" | "yml"))
+        && !name.starts_with('_')
+        && !name.starts_with('.')
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .is_some_and(|name| name.starts_with('.') || name.starts_with('_'))
+}
+
+pub(crate) fn is_yaml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|s| s.to_str()),
+        Some("yaml" | "yml")
+    )
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read collection directory: {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn sample_dir(root: &Path) {
+        write(
+            &root.join("health.json"),
+            r#"{ "method": "GET", "url": "{{baseUrl}}/health" }"#,
+        );
+        write(
+            &root.join("users").join(FOLDER_DEFAULTS_FILE),
+            "headers:\n  Authorization: Bearer {{token}}\n",
+        );
+        write(
+            &root.join("users").join("get-user.yaml"),
+            "name: Get User\nmethod: GET\nurl: '{{baseUrl}}/users/1'\n",
+        );
+        write(
+            &root.join("users").join("admin").join("create.json"),
+            r#"{"name": "Create Admin", "method": "POST", "url": "{{baseUrl}}/admins",
+                "headers": {"Authorization": "Bearer {{adminToken}}"}}"#,
+        );
+    }
+
+    #[test]
+    fn test_load_collection_dir() {
+        let dir = tempdir().unwrap();
+        sample_dir(dir.path());
+
+        let collection = load_collection_dir(dir.path()).unwrap();
+        let names: Vec<&str> = collection.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["health", "Create Admin", "Get User"]);
+
+        assert_eq!(collection[0].folder, None);
+        assert_eq!(collection[1].folder.as_deref(), Some("users/admin"));
+        assert_eq!(
+            collection[1].headers.get("Authorization"),
+            Some(&"Bearer {{adminToken}}".to_string())
+        );
+        assert_eq!(collection[2].folder.as_deref(), Some("users"));
+        assert_eq!(
+            collection[2].headers.get("Authorization"),
+            Some(&"Bearer {{token}}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_save_collection_dir_only_rewrites_changed_files() {
+        let dir = tempdir().unwrap();
+        sample_dir(dir.path());
+        let health_path = dir.path().join("health.json");
+        let health_before = std::fs::read_to_string(&health_path).unwrap();
+
+        let mut collection = load_collection_dir(dir.path()).unwrap();
+        collection[2].url = "{{baseUrl}}/users/2".to_string();
+        save_collection_dir(&collection, dir.path()).unwrap();
+
+        // Untouched request keeps its original formatting
+        assert_eq!(
+            std::fs::read_to_string(&health_path).unwrap(),
+            health_before
+        );
+
+        // Changed request is rewritten in its own format without the folder default
+        let user = std::fs::read_to_string(dir.path().join("users/get-user.yaml")).unwrap();
+        assert!(user.contains("users/2"));
+        assert!(!user.contains("Authorization"));
+        assert!(!user.contains("folder"));
+
+        let reloaded = load_collection_dir(dir.path()).unwrap();
+        assert_eq!(reloaded[2].url, "{{baseUrl}}/users/2");
+        assert_eq!(reloaded[2].headers.len(), 1);
+    }
+
+    #[test]
+    fn test_save_collection_dir_adds_and_removes_files() {
+        let dir = tempdir().unwrap();
+        sample_dir(dir.path());
+
+        let mut collection = load_collection_dir(dir.path()).unwrap();
+        remove_request_file(&collection.remove(0)).unwrap();
+        collection[1].folder = Some("admins".to_string());
+        collection.push(Request {
+            folder: Some("orders".to_string()),
+            ..Request::test("List Orders!", "{{baseUrl}}/orders")
+        });
+        save_collection_dir(&collection, dir.path()).unwrap();
+
+        assert!(!dir.path().join("health.json").exists());
+        assert!(dir.path().join("orders/list-orders.json").exists());
+        // Moved to another folder
+        assert!(!dir.path().join("users/get-user.yaml").exists());
+        assert!(dir.path().join("admins/get-user.json").exists());
+
+        let reloaded = load_collection_dir(dir.path()).unwrap();
+        assert_eq!(reloaded.len(), 3);
+        assert!(
+            reloaded
+                .iter()
+                .any(|r| r.name == "List Orders!" && r.folder.as_deref() == Some("orders"))
+        );
+    }
+
+    #[test]
+    fn test_save_collection_dir_keeps_files_added_since_loading() {
+        let dir = tempdir().unwrap();
+        sample_dir(dir.path());
+
+        let collection = load_collection_dir(dir.path()).unwrap();
+        let added = dir.path().join("users").join("delete-user.json");
+        write(
+            &added,
+            r#"{"name": "Delete User", "method": "DELETE", "url": "/users/1"}"#,
+        );
+        save_collection_dir(&collection, dir.path()).unwrap();
+
+        assert!(added.exists());
+        assert_eq!(load_collection_dir(dir.path()).unwrap().len(), 4);
+    }
+}
@@ -11,13 +11,19 @@ pub fn discover_collection() -> Result<PathBuf> {
         return Ok(netbook_dir_collection);
     }
 
-    // Priority 2: netbook.json (simple approach)
+    // Priority 2: .netbook/requests/ (one file per request)
+    let netbook_dir_requests = current_dir.join(".netbook").join("requests");
+    if netbook_dir_requests.is_dir() {
+        return Ok(netbook_dir_requests);
+    }
+
+    // Priority 3: netbook.json (simple approach)
     let simple_collection = current_dir.join("netbook.json");
     if simple_collection.exists() {
         return Ok(simple_collection);
     }
 
-    // Priority 3: Return default path for new collection
+    // Priority 4: Return default path for new collection
     Ok(netbook_dir_collection)
 }
 
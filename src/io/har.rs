@@ -0,0 +1,551 @@
+use crate::core::{Collection, HttpMethod, Request, RequestBody, Response, SentRequest};
+use crate::io::{HistoryEntry, ImportResult};
+use chrono::Duration;
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarLog {
+    #[serde(default = "default_har_version")]
+    pub version: String,
+    #[serde(default)]
+    pub creator: HarCreator,
+    #[serde(default)]
+    pub entries: Vec<HarEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+impl Default for HarCreator {
+    fn default() -> Self {
+        Self {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    #[serde(default)]
+    pub started_date_time: String,
+    #[serde(default)]
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    #[serde(default)]
+    pub cache: serde_json::Value,
+    #[serde(default)]
+    pub timings: HarTimings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default = "default_http_version")]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<HarNameValue>,
+    #[serde(default)]
+    pub headers: Vec<HarNameValue>,
+    #[serde(default)]
+    pub query_string: Vec<HarNameValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<HarPostData>,
+    #[serde(default = "unknown_size")]
+    pub headers_size: i64,
+    #[serde(default = "unknown_size")]
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub status_text: String,
+    #[serde(default = "default_http_version")]
+    pub http_version: String,
+    #[serde(default)]
+    pub cookies: Vec<HarNameValue>,
+    #[serde(default)]
+    pub headers: Vec<HarNameValue>,
+    #[serde(default)]
+    pub content: HarContent,
+    #[serde(default, rename = "redirectURL")]
+    pub redirect_url: String,
+    #[serde(default = "unknown_size")]
+    pub headers_size: i64,
+    #[serde(default = "unknown_size")]
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarNameValue {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPostData {
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+    #[serde(default)]
+    pub size: i64,
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HarTimings {
+    #[serde(default)]
+    pub send: f64,
+    #[serde(default)]
+    pub wait: f64,
+    #[serde(default)]
+    pub receive: f64,
+}
+
+fn default_har_version() -> String {
+    "1.2".to_string()
+}
+
+fn default_http_version() -> String {
+    "HTTP/1.1".to_string()
+}
+
+fn unknown_size() -> i64 {
+    -1
+}
+
+/// Restricts which HAR entries are turned into requests
+#[derive(Debug, Clone, Default)]
+pub struct HarFilter {
+    /// Hosts to keep; subdomains of a listed host also match
+    pub hosts: Vec<String>,
+    /// HTTP methods to keep, case-insensitive
+    pub methods: Vec<String>,
+}
+
+impl HarFilter {
+    pub fn matches(&self, method: &str, url: &str) -> bool {
+        let method_ok =
+            self.methods.is_empty() || self.methods.iter().any(|m| m.eq_ignore_ascii_case(method));
+
+        let host_ok = self.hosts.is_empty()
+            || reqwest::Url::parse(url)
+                .ok()
+                .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+                .is_some_and(|host| {
+                    self.hosts.iter().any(|filter| {
+                        let filter = filter.to_lowercase();
+                        host == filter || host.ends_with(&format!(".{}", filter))
+                    })
+                });
+
+        method_ok && host_ok
+    }
+}
+
+/// Headers the browser adds on its own that would be wrong to replay verbatim
+const SKIPPED_IMPORT_HEADERS: &[&str] = &["content-length", "host", "connection"];
+
+pub fn import_har(content: &str, filter: &HarFilter) -> Result<ImportResult> {
+    let har: Har = serde_json::from_str(content).with_context(|| "Failed to parse HAR file")?;
+    let mut result = ImportResult::new("HAR");
+    let report = &mut result.report;
+
+    for entry in har.log.entries {
+        let har_request = entry.request;
+
+        if !filter.matches(&har_request.method, &har_request.url) {
+            report.skip(format!(
+                "{} {} (filtered out)",
+                har_request.method, har_request.url
+            ));
+            continue;
+        }
+
+        let method = match har_request.method.parse::<HttpMethod>() {
+            Ok(method) => method,
+            Err(e) => {
+                report.skip(format!("{} ({})", har_request.url, e));
+                continue;
+            }
+        };
+
+        let (url, mut pairs) = split_query(&har_request.url);
+        if pairs.is_empty() {
+            pairs = har_request
+                .query_string
+                .iter()
+                .map(|p| (p.name.clone(), p.value.clone()))
+                .collect();
+        }
+
+        let mut query = HashMap::new();
+        for (name, value) in pairs {
+            if query.insert(name.clone(), value).is_some() {
+                report.warn(format!(
+                    "{} {}: repeated query parameter '{}', keeping the last value",
+                    har_request.method, url, name
+                ));
+            }
+        }
+
+        let mut headers = HashMap::new();
+        for header in &har_request.headers {
+            let lower = header.name.to_lowercase();
+            if lower.starts_with(':') || SKIPPED_IMPORT_HEADERS.contains(&lower.as_str()) {
+                continue;
+            }
+            headers.insert(header.name.clone(), header.value.clone());
+        }
+
+        let body = har_request
+            .post_data
+            .filter(|data| !data.text.is_empty())
+            .map(|data| {
+                if data.mime_type.contains("json")
+                    && let Ok(json) = serde_json::from_str(&data.text)
+                {
+                    RequestBody::Json(json)
+                } else {
+                    RequestBody::Text(data.text)
+                }
+            });
+
+        let notes = if entry.started_date_time.is_empty() {
+            "Imported from HAR".to_string()
+        } else {
+            format!("Imported from HAR (captured {})", entry.started_date_time)
+        };
+
+        result.requests.push(Request {
+            name: request_name(&method, &url),
+            method,
+            url,
+            headers,
+            query,
+            body,
+            notes: Some(notes),
+            folder: None,
+            source: None,
+            disabled_headers: HashMap::new(),
+            disabled_query: HashMap::new(),
+            extract: HashMap::new(),
+            id: uuid::Uuid::new_v4(),
+        });
+    }
+
+    Ok(result)
+}
+
+/// Builds a HAR entry from the request that was sent and its response
+pub fn har_entry(sent: &SentRequest, response: &Response) -> HarEntry {
+    let post_data = sent.body.as_ref().map(|body| HarPostData {
+        mime_type: header_value(&sent.headers, "content-type")
+            .map(str::to_string)
+            .unwrap_or_else(|| match body {
+                serde_json::Value::String(_) => "text/plain".to_string(),
+                _ => "application/json".to_string(),
+            }),
+        text: match body {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        },
+    });
+    let query_string = split_query(&sent.url)
+        .1
+        .into_iter()
+        .map(|(name, value)| HarNameValue { name, value })
+        .collect();
+
+    let body_text = match &response.body {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let mime_type = header_value(&response.headers, "content-type")
+        .map(str::to_string)
+        .unwrap_or_else(|| match response.body {
+            serde_json::Value::String(_) => "text/plain".to_string(),
+            _ => "application/json".to_string(),
+        });
+
+    let total_ms = response.timing.total_ms as f64;
+    let started = response.timestamp - Duration::milliseconds(response.timing.total_ms as i64);
+
+    HarEntry {
+        started_date_time: started.to_rfc3339(),
+        time: total_ms,
+        request: HarRequest {
+            method: sent.method.to_string(),
+            url: sent.url.clone(),
+            http_version: default_http_version(),
+            cookies: Vec::new(),
+            headers: name_values(&sent.headers),
+            query_string,
+            body_size: post_data.as_ref().map_or(0, |d| d.text.len() as i64),
+            post_data,
+            headers_size: unknown_size(),
+        },
+        response: HarResponse {
+            status: response.status,
+            status_text: reqwest::StatusCode::from_u16(response.status)
+                .ok()
+                .and_then(|s| s.canonical_reason())
+                .unwrap_or_default()
+                .to_string(),
+            http_version: default_http_version(),
+            cookies: Vec::new(),
+            headers: name_values(&response.headers),
+            content: HarContent {
+                size: body_text.len() as i64,
+                mime_type,
+                text: Some(body_text.clone()),
+                encoding: None,
+            },
+            redirect_url: header_value(&response.headers, "location")
+                .unwrap_or_default()
+                .to_string(),
+            headers_size: unknown_size(),
+            body_size: body_text.len() as i64,
+        },
+        cache: serde_json::json!({}),
+        timings: HarTimings {
+            send: 0.0,
+            wait: total_ms,
+            receive: 0.0,
+        },
+    }
+}
+
+/// Exports history entries as HAR with the requests as they were sent,
+/// secrets redacted. Entries recorded before requests were captured fall
+/// back to their request in the collection, interpolated and redacted.
+/// Returns how many entries were written.
+pub fn export_har(
+    entries: &[&HistoryEntry],
+    collection: &Collection,
+    interpolator: &crate::core::VariableInterpolator,
+    path: &Path,
+) -> Result<usize> {
+    let har_entries: Vec<HarEntry> = entries
+        .iter()
+        .filter_map(|entry| {
+            let sent = entry.response.sent.clone().or_else(|| {
+                collection.iter().find(|r| entry.is_for(r)).map(|request| {
+                    SentRequest::of(&interpolator.interpolate_request(request)).redacted()
+                })
+            })?;
+            Some(har_entry(&sent, &entry.response))
+        })
+        .collect();
+
+    let count = har_entries.len();
+    let har = Har {
+        log: HarLog {
+            version: default_har_version(),
+            creator: HarCreator::default(),
+            entries: har_entries,
+        },
+    };
+
+    let content = serde_json::to_string_pretty(&har)?;
+    std::fs::write(path, content)
+        .with_context(|| format!("Failed to write HAR file: {}", path.display()))?;
+
+    Ok(count)
+}
+
+fn split_query(url: &str) -> (String, Vec<(String, String)>) {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) => {
+            let query = parsed.query_pairs().into_owned().collect();
+            parsed.set_query(None);
+            parsed.set_fragment(None);
+            (parsed.to_string(), query)
+        }
+        Err(_) => (url.to_string(), Vec::new()),
+    }
+}
+
+fn request_name(method: &HttpMethod, url: &str) -> String {
+    let path = reqwest::Url::parse(url)
+        .map(|u| u.path().to_string())
+        .unwrap_or_else(|_| url.to_string());
+    format!("{} {}", method, path)
+}
+
+fn header_value<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.as_str())
+}
+
+fn name_values(map: &HashMap<String, String>) -> Vec<HarNameValue> {
+    let mut pairs: Vec<HarNameValue> = map
+        .iter()
+        .map(|(name, value)| HarNameValue {
+            name: name.clone(),
+            value: value.clone(),
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.name.cmp(&b.name));
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ResponseTiming;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    const SAMPLE_HAR: &str = r#"{
+        "log": {
+            "version": "1.2",
+            "creator": {"name": "Firefox", "version": "120"},
+            "entries": [
+                {
+                    "startedDateTime": "2025-01-01T10:00:00.000Z",
+                    "time": 42,
+                    "request": {
+                        "method": "GET",
+                        "url": "https://api.example.com/users?page=2",
+                        "headers": [
+                            {"name": ":authority", "value": "api.example.com"},
+                            {"name": "Accept", "value": "application/json"},
+                            {"name": "Content-Length", "value": "0"}
+                        ],
+                        "queryString": [{"name": "page", "value": "2"}]
+                    },
+                    "response": {"status": 200, "content": {"mimeType": "application/json"}}
+                },
+                {
+                    "startedDateTime": "2025-01-01T10:00:01.000Z",
+                    "request": {
+                        "method": "POST",
+                        "url": "https://api.example.com/users",
+                        "postData": {"mimeType": "application/json", "text": "{\"name\":\"Ada\"}"}
+                    },
+                    "response": {"status": 201}
+                },
+                {
+                    "request": {"method": "GET", "url": "https://cdn.other.com/app.js"},
+                    "response": {"status": 200}
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn test_import_har() {
+        let result = import_har(SAMPLE_HAR, &HarFilter::default()).unwrap();
+        let requests = &result.requests;
+        assert_eq!(requests.len(), 3);
+        assert!(result.report.skipped.is_empty());
+
+        let get = &requests[0];
+        assert_eq!(get.name, "GET /users");
+        assert_eq!(get.url, "https://api.example.com/users");
+        assert_eq!(get.query.get("page"), Some(&"2".to_string()));
+        assert_eq!(get.headers.len(), 1);
+        assert!(get.headers.contains_key("Accept"));
+
+        let post = &requests[1];
+        assert!(matches!(post.method, HttpMethod::Post));
+        assert!(matches!(post.body, Some(RequestBody::Json(_))));
+    }
+
+    #[test]
+    fn test_import_har_with_filter() {
+        let filter = HarFilter {
+            hosts: vec!["example.com".to_string()],
+            methods: vec!["get".to_string()],
+        };
+        let result = import_har(SAMPLE_HAR, &filter).unwrap();
+
+        assert_eq!(result.requests.len(), 1);
+        assert_eq!(result.requests[0].name, "GET /users");
+        assert_eq!(result.report.skipped.len(), 2);
+    }
+
+    #[test]
+    fn test_har_entry_roundtrip() {
+        let request = Request {
+            method: HttpMethod::Post,
+            headers: [("Content-Type".to_string(), "application/json".to_string())]
+                .into_iter()
+                .collect(),
+            query: [("dryRun".to_string(), "true".to_string())]
+                .into_iter()
+                .collect(),
+            body: Some(RequestBody::Json(serde_json::json!({"name": "Ada"}))),
+            ..Request::test("Create", "https://api.example.com/users")
+        };
+        let response = Response {
+            id: Uuid::new_v4(),
+            request_id: None,
+            status: 201,
+            headers: HashMap::new(),
+            body: serde_json::json!({"id": 1}),
+            timing: ResponseTiming {
+                total_ms: 120,
+                ..Default::default()
+            },
+            timestamp: Utc::now(),
+            sent: None,
+        };
+
+        let entry = har_entry(&SentRequest::of(&request), &response);
+        assert_eq!(
+            entry.request.url,
+            "https://api.example.com/users?dryRun=true"
+        );
+        assert_eq!(entry.response.status_text, "Created");
+        assert_eq!(entry.time, 120.0);
+
+        let har = Har {
+            log: HarLog {
+                version: default_har_version(),
+                creator: HarCreator::default(),
+                entries: vec![entry],
+            },
+        };
+        let content = serde_json::to_string(&har).unwrap();
+        let imported = import_har(&content, &HarFilter::default())
+            .unwrap()
+            .requests;
+        assert_eq!(imported[0].url, "https://api.example.com/users");
+        assert_eq!(imported[0].query.get("dryRun"), Some(&"true".to_string()));
+        assert!(matches!(imported[0].body, Some(RequestBody::Json(_))));
+    }
+}
@@ -0,0 +1,354 @@
+use crate::core::Collection;
+use color_eyre::Result;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Requests, environments and a report produced by one of the importers
+#[derive(Debug, Clone)]
+pub struct ImportResult {
+    pub requests: Collection,
+    pub environments: Vec<ImportedEnvironment>,
+    pub report: ImportReport,
+}
+
+impl ImportResult {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            requests: Vec::new(),
+            environments: Vec::new(),
+            report: ImportReport::new(source),
+        }
+    }
+}
+
+/// A named set of variables; `name: None` is the base environment that is
+/// always loaded
+#[derive(Debug, Clone, Default)]
+pub struct ImportedEnvironment {
+    pub name: Option<String>,
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Summary of what an importer did with its input
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub source: String,
+    pub imported: Vec<String>,
+    pub skipped: Vec<String>,
+    pub warnings: Vec<String>,
+    pub environments: Vec<PathBuf>,
+}
+
+impl ImportReport {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn skip(&mut self, reason: impl Into<String>) {
+        self.skipped.push(reason.into());
+    }
+
+    pub fn warn(&mut self, warning: impl Into<String>) {
+        self.warnings.push(warning.into());
+    }
+}
+
+impl std::fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Imported {} request(s) from {}",
+            self.imported.len(),
+            self.source
+        )?;
+        for name in &self.imported {
+            writeln!(f, "  + {}", name)?;
+        }
+        if !self.skipped.is_empty() {
+            writeln!(f, "Skipped {}:", self.skipped.len())?;
+            for reason in &self.skipped {
+                writeln!(f, "  - {}", reason)?;
+            }
+        }
+        if !self.environments.is_empty() {
+            writeln!(f, "Environments:")?;
+            for path in &self.environments {
+                writeln!(f, "  * {}", path.display())?;
+            }
+        }
+        if !self.warnings.is_empty() {
+            writeln!(f, "Warnings:")?;
+            for warning in &self.warnings {
+                writeln!(f, "  ! {}", warning)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Appends imported requests to a collection, renaming any that clash with
+/// existing request names so lookups by name stay unambiguous
+pub fn merge_into_collection(
+    collection: &mut Collection,
+    imported: Collection,
+    report: &mut ImportReport,
+) {
+    let mut names: HashSet<String> = collection.iter().map(|r| r.name.clone()).collect();
+
+    for mut request in imported {
+        let unique = unique_name(&request.name, &names);
+        if unique != request.name {
+            report.warn(format!(
+                "Renamed '{}' to '{}' to avoid a duplicate name",
+                request.name, unique
+            ));
+            request.name = unique;
+        }
+        names.insert(request.name.clone());
+        report.imported.push(request.name.clone());
+        collection.push(request);
+    }
+}
+
+/// Loads the collection at `collection_path` (or starts an empty one), merges
+/// the imported requests into it and writes it back. Imported environments are
+/// written next to the collection as `.netbook/.env` (base) and
+/// `.netbook/.env.<name>`.
+pub fn import_into_collection(
+    collection_path: &Path,
+    result: ImportResult,
+) -> Result<ImportReport> {
+    let ImportResult {
+        requests,
+        environments,
+        mut report,
+    } = result;
+
+    let mut collection = if collection_path.exists() {
+        crate::io::load_collection(collection_path)?
+    } else {
+        if let Some(parent) = collection_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Vec::new()
+    };
+
+    merge_into_collection(&mut collection, requests, &mut report);
+    crate::io::save_collection(&collection, collection_path)?;
+
+    let netbook_dir = crate::io::get_netbook_dir(collection_path);
+    for environment in &environments {
+        let path = write_environment(&netbook_dir, environment, &mut report)?;
+        report.environments.push(path);
+    }
+
+    Ok(report)
+}
+
+/// File an environment's variables live in, relative to the `.netbook` dir
+pub fn environment_file_name(name: Option<&str>) -> String {
+    match name {
+        None => ".env".to_string(),
+        Some(name) => format!(".env.{}", environment_slug(name)),
+    }
+}
+
+pub fn environment_slug(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Adds variables to an env file, keeping any values that are already there
+fn write_environment(
+    netbook_dir: &Path,
+    environment: &ImportedEnvironment,
+    report: &mut ImportReport,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(netbook_dir)?;
+    let path = netbook_dir.join(environment_file_name(environment.name.as_deref()));
+
+    let mut content = if path.exists() {
+        std::fs::read_to_string(&path)?
+    } else {
+        format!("# Imported from {}\n", report.source)
+    };
+    let existing = crate::core::parse_env_content(&content);
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for (key, value) in &environment.variables {
+        match existing.get(key) {
+            Some(current) if current != value => report.warn(format!(
+                "Kept existing value of '{}' in {}",
+                key,
+                path.display()
+            )),
+            Some(_) => {}
+            None => content.push_str(&format!("{}={}\n", key, value)),
+        }
+    }
+
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Value for an `Authorization` header using HTTP basic auth. `None` when
+/// the credentials use `{{variables}}`: their values are only known when the
+/// request is sent, too late to encode them.
+pub fn basic_auth(username: &str, password: &str) -> Option<String> {
+    use base64::Engine;
+    if username.contains("{{") || password.contains("{{") {
+        return None;
+    }
+    let credentials = format!("{}:{}", username, password);
+    Some(format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(credentials)
+    ))
+}
+
+/// Encodes pairs as an `application/x-www-form-urlencoded` body. `{{variables}}`
+/// are left as they are, so they are still interpolated.
+pub fn form_urlencode(pairs: &[(String, String)]) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{}={}", form_component(name), form_component(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn form_component(text: &str) -> String {
+    let mut encoded = String::new();
+    let mut rest = text;
+    loop {
+        let variable = rest
+            .find("{{")
+            .and_then(|start| rest[start..].find("}}").map(|len| (start, start + len + 2)));
+        let Some((start, end)) = variable else {
+            encoded.push_str(&form_encode(rest));
+            return encoded;
+        };
+        encoded.push_str(&form_encode(&rest[..start]));
+        encoded.push_str(&rest[start..end]);
+        rest = &rest[end..];
+    }
+}
+
+fn form_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => {
+                (byte as char).to_string()
+            }
+            b' ' => "+".to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// `name`, or `name (2)`, `name (3)`, ... if it is already taken
+pub fn unique_name(name: &str, existing: &HashSet<String>) -> String {
+    if !existing.contains(name) {
+        return name.to_string();
+    }
+
+    let mut suffix = 2;
+    loop {
+        let candidate = format!("{} ({})", name, suffix);
+        if !existing.contains(&candidate) {
+            return candidate;
+        }
+        suffix += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Request;
+
+    fn request(name: &str) -> Request {
+        Request::test(name, "https://example.com")
+    }
+
+    #[test]
+    fn test_import_writes_environments() {
+        let dir = tempfile::tempdir().unwrap();
+        let collection_path = dir.path().join(".netbook").join("collection.json");
+        std::fs::create_dir_all(collection_path.parent().unwrap()).unwrap();
+        std::fs::write(dir.path().join(".netbook").join(".env"), "token=keep\n").unwrap();
+
+        let mut result = ImportResult::new("test");
+        result.requests.push(request("Get Users"));
+        result.environments.push(ImportedEnvironment {
+            name: None,
+            variables: [
+                ("token".to_string(), "imported".to_string()),
+                ("baseUrl".to_string(), "https://example.com".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+        });
+        result.environments.push(ImportedEnvironment {
+            name: Some("Staging EU".to_string()),
+            variables: [("baseUrl".to_string(), "https://staging".to_string())]
+                .into_iter()
+                .collect(),
+        });
+
+        let report = import_into_collection(&collection_path, result).unwrap();
+        assert_eq!(report.imported, vec!["Get Users"]);
+
+        let base = std::fs::read_to_string(dir.path().join(".netbook/.env")).unwrap();
+        assert!(base.contains("token=keep"));
+        assert!(!base.contains("token=imported"));
+        assert!(base.contains("baseUrl=https://example.com"));
+
+        let staging = std::fs::read_to_string(dir.path().join(".netbook/.env.staging-eu")).unwrap();
+        assert!(staging.contains("baseUrl=https://staging"));
+    }
+
+    #[test]
+    fn test_form_urlencode_and_basic_auth() {
+        let pairs = [
+            ("user name".to_string(), "ada & co".to_string()),
+            ("token".to_string(), "{{token}}/é{{ b }}".to_string()),
+            ("open".to_string(), "{{unclosed".to_string()),
+        ];
+        assert_eq!(
+            form_urlencode(&pairs),
+            "user+name=ada+%26+co&token={{token}}%2F%C3%A9{{ b }}&open=%7B%7Bunclosed"
+        );
+
+        assert_eq!(
+            basic_auth("ada", "secret").as_deref(),
+            Some("Basic YWRhOnNlY3JldA==")
+        );
+        assert_eq!(basic_auth("{{user}}", "secret"), None);
+        assert_eq!(basic_auth("ada", "{{pass}}"), None);
+    }
+
+    #[test]
+    fn test_merge_renames_duplicates() {
+        let mut collection = vec![request("Get Users")];
+        let mut report = ImportReport::new("test");
+
+        merge_into_collection(
+            &mut collection,
+            vec![request("Get Users"), request("Get Users")],
+            &mut report,
+        );
+
+        let names: Vec<&str> = collection.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Get Users", "Get Users (2)", "Get Users (3)"]);
+        assert_eq!(report.imported.len(), 2);
+        assert_eq!(report.warnings.len(), 2);
+    }
+}
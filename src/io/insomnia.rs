@@ -0,0 +1,488 @@
+use crate::core::{HttpMethod, Request, RequestBody};
+use crate::io::{ImportReport, ImportResult, ImportedEnvironment, basic_auth, form_urlencode};
+use color_eyre::{Result, eyre::WrapErr};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Deserialize)]
+struct InsomniaExport {
+    #[serde(rename = "__export_format", default)]
+    export_format: u32,
+    #[serde(default)]
+    resources: Vec<InsomniaResource>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InsomniaResource {
+    #[serde(rename = "_id")]
+    id: String,
+    #[serde(rename = "_type")]
+    kind: String,
+    #[serde(default)]
+    parent_id: Option<String>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    method: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    headers: Vec<InsomniaPair>,
+    #[serde(default)]
+    parameters: Vec<InsomniaPair>,
+    #[serde(default)]
+    body: Option<InsomniaBody>,
+    #[serde(default)]
+    authentication: Option<serde_json::Value>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InsomniaPair {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InsomniaBody {
+    #[serde(default)]
+    mime_type: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    params: Vec<InsomniaPair>,
+}
+
+/// Rewrites Insomnia's `{{ _.name }}` / `{{ name }}` references to netbook's
+/// `{{name}}` and reports template tags that have no equivalent
+struct TemplateConverter {
+    variable: Regex,
+    tag: Regex,
+}
+
+impl TemplateConverter {
+    fn new() -> Self {
+        Self {
+            variable: Regex::new(r"\{\{\s*(?:_\.)?(\w+)\s*\}\}").expect("Invalid regex"),
+            tag: Regex::new(r"\{%.*?%\}").expect("Invalid regex"),
+        }
+    }
+
+    fn convert(&self, input: &str, context: &str, report: &mut ImportReport) -> String {
+        if self.tag.is_match(input) {
+            report.warn(format!(
+                "{}: template tags like '{{% ... %}}' are not supported and were kept as-is",
+                context
+            ));
+        }
+        self.rewrite(input)
+    }
+
+    fn rewrite(&self, input: &str) -> String {
+        self.variable.replace_all(input, "{{$1}}").to_string()
+    }
+}
+
+pub fn import_insomnia(content: &str) -> Result<ImportResult> {
+    let export: InsomniaExport = serde_json::from_str(content)
+        .with_context(|| "Failed to parse Insomnia export (expected export format 4 JSON)")?;
+    let mut result = ImportResult::new("Insomnia");
+
+    if export.export_format != 4 {
+        result.report.warn(format!(
+            "Expected Insomnia export format 4, found {}; the import may be incomplete",
+            export.export_format
+        ));
+    }
+
+    let by_id: HashMap<&str, &InsomniaResource> = export
+        .resources
+        .iter()
+        .map(|r| (r.id.as_str(), r))
+        .collect();
+    let templates = TemplateConverter::new();
+
+    for resource in &export.resources {
+        match resource.kind.as_str() {
+            "request" => {
+                if let Some(request) =
+                    convert_request(resource, &by_id, &templates, &mut result.report)
+                {
+                    result.requests.push(request);
+                }
+            }
+            "environment" => {
+                let is_base = resource
+                    .parent_id
+                    .as_deref()
+                    .and_then(|id| by_id.get(id))
+                    .is_none_or(|parent| parent.kind != "environment");
+                let name = if is_base {
+                    None
+                } else {
+                    Some(resource.name.clone())
+                };
+                let variables = flatten_environment(resource, &mut result.report);
+                if !variables.is_empty() {
+                    result
+                        .environments
+                        .push(ImportedEnvironment { name, variables });
+                }
+            }
+            "request_group" => {
+                if resource
+                    .data
+                    .as_ref()
+                    .and_then(|d| d.as_object())
+                    .is_some_and(|d| !d.is_empty())
+                {
+                    result.report.warn(format!(
+                        "Folder '{}': folder-level environment was not imported",
+                        resource.name
+                    ));
+                }
+            }
+            "workspace" | "cookie_jar" | "api_spec" => {}
+            other => result
+                .report
+                .skip(format!("{} '{}' (unsupported type)", other, resource.name)),
+        }
+    }
+
+    Ok(result)
+}
+
+fn convert_request(
+    resource: &InsomniaResource,
+    by_id: &HashMap<&str, &InsomniaResource>,
+    templates: &TemplateConverter,
+    report: &mut ImportReport,
+) -> Option<Request> {
+    let method = match resource.method.parse::<HttpMethod>() {
+        Ok(method) => method,
+        Err(e) => {
+            report.skip(format!("{} ({})", resource.name, e));
+            return None;
+        }
+    };
+    let context = resource.name.as_str();
+
+    let mut headers = HashMap::new();
+    for header in resource.headers.iter().filter(|h| !h.name.is_empty()) {
+        if header.disabled {
+            report.warn(format!(
+                "{}: skipped disabled header '{}'",
+                context, header.name
+            ));
+            continue;
+        }
+        headers.insert(
+            header.name.clone(),
+            templates.convert(&header.value, context, report),
+        );
+    }
+
+    let mut query = HashMap::new();
+    for param in resource.parameters.iter().filter(|p| !p.name.is_empty()) {
+        if param.disabled {
+            report.warn(format!(
+                "{}: skipped disabled query parameter '{}'",
+                context, param.name
+            ));
+            continue;
+        }
+        query.insert(
+            param.name.clone(),
+            templates.convert(&param.value, context, report),
+        );
+    }
+
+    if let Some(auth) = &resource.authentication {
+        apply_auth(auth, &mut headers, context, templates, report);
+    }
+
+    let body = resource
+        .body
+        .as_ref()
+        .and_then(|body| convert_body(body, &mut headers, context, templates, report));
+
+    Some(Request {
+        name: resource.name.clone(),
+        method,
+        url: templates.convert(&resource.url, context, report),
+        headers,
+        query,
+        body,
+        notes: (!resource.description.is_empty()).then(|| resource.description.clone()),
+        folder: folder_path(resource, by_id),
+        source: None,
+        disabled_headers: HashMap::new(),
+        disabled_query: HashMap::new(),
+        extract: HashMap::new(),
+        id: uuid::Uuid::new_v4(),
+    })
+}
+
+fn convert_body(
+    body: &InsomniaBody,
+    headers: &mut HashMap<String, String>,
+    context: &str,
+    templates: &TemplateConverter,
+    report: &mut ImportReport,
+) -> Option<RequestBody> {
+    let mime_type = body.mime_type.as_deref().unwrap_or_default();
+
+    if mime_type == "application/x-www-form-urlencoded" {
+        let pairs: Vec<(String, String)> = body
+            .params
+            .iter()
+            .filter(|p| !p.disabled)
+            .map(|p| (p.name.clone(), templates.convert(&p.value, context, report)))
+            .collect();
+        headers
+            .entry("Content-Type".to_string())
+            .or_insert_with(|| mime_type.to_string());
+        return Some(RequestBody::Text(form_urlencode(&pairs)));
+    }
+
+    if mime_type.starts_with("multipart/") {
+        report.warn(format!(
+            "{}: multipart bodies are not supported; body dropped",
+            context
+        ));
+        return None;
+    }
+
+    let text = templates.convert(body.text.as_deref()?, context, report);
+    if mime_type.contains("json")
+        && let Ok(json) = serde_json::from_str(&text)
+    {
+        return Some(RequestBody::Json(json));
+    }
+    if !mime_type.is_empty() {
+        headers
+            .entry("Content-Type".to_string())
+            .or_insert_with(|| mime_type.to_string());
+    }
+    Some(RequestBody::Text(text))
+}
+
+fn apply_auth(
+    auth: &serde_json::Value,
+    headers: &mut HashMap<String, String>,
+    context: &str,
+    templates: &TemplateConverter,
+    report: &mut ImportReport,
+) {
+    if auth.get("disabled").and_then(|d| d.as_bool()) == Some(true) {
+        return;
+    }
+    let field =
+        |name: &str| templates.rewrite(auth.get(name).and_then(|v| v.as_str()).unwrap_or_default());
+
+    match auth.get("type").and_then(|t| t.as_str()) {
+        None | Some("none") => {}
+        Some("bearer") => {
+            let prefix = auth
+                .get("prefix")
+                .and_then(|p| p.as_str())
+                .filter(|p| !p.is_empty())
+                .unwrap_or("Bearer");
+            headers.insert(
+                "Authorization".to_string(),
+                format!("{} {}", prefix, field("token")),
+            );
+        }
+        Some("basic") => match basic_auth(&field("username"), &field("password")) {
+            Some(value) => {
+                headers.insert("Authorization".to_string(), value);
+            }
+            None => report.warn(format!(
+                "{}: basic auth with variables can't be encoded; add the Authorization header manually",
+                context
+            )),
+        },
+        Some("apikey") => {
+            headers.insert(field("key"), field("value"));
+        }
+        Some(other) => report.warn(format!(
+            "{}: '{}' authentication is not supported; add the header manually",
+            context, other
+        )),
+    }
+}
+
+fn folder_path(
+    resource: &InsomniaResource,
+    by_id: &HashMap<&str, &InsomniaResource>,
+) -> Option<String> {
+    let mut segments = Vec::new();
+    let mut parent = resource.parent_id.as_deref().and_then(|id| by_id.get(id));
+
+    while let Some(group) = parent.filter(|p| p.kind == "request_group") {
+        segments.push(group.name.clone());
+        parent = group.parent_id.as_deref().and_then(|id| by_id.get(id));
+    }
+
+    if segments.is_empty() {
+        None
+    } else {
+        segments.reverse();
+        Some(segments.join("/"))
+    }
+}
+
+fn flatten_environment(
+    resource: &InsomniaResource,
+    report: &mut ImportReport,
+) -> BTreeMap<String, String> {
+    let mut variables = BTreeMap::new();
+    let Some(data) = resource.data.as_ref().and_then(|d| d.as_object()) else {
+        return variables;
+    };
+
+    for (key, value) in data {
+        match value {
+            serde_json::Value::String(s) => {
+                variables.insert(key.clone(), s.clone());
+            }
+            serde_json::Value::Number(_) | serde_json::Value::Bool(_) => {
+                variables.insert(key.clone(), value.to_string());
+            }
+            serde_json::Value::Null => {}
+            _ => report.warn(format!(
+                "Environment '{}': nested value '{}' is not supported",
+                resource.name, key
+            )),
+        }
+    }
+
+    variables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_EXPORT: &str = r#"{
+        "_type": "export",
+        "__export_format": 4,
+        "resources": [
+            {"_id": "wrk_1", "_type": "workspace", "name": "Shop API"},
+            {"_id": "fld_1", "_type": "request_group", "parentId": "wrk_1", "name": "Users"},
+            {"_id": "fld_2", "_type": "request_group", "parentId": "fld_1", "name": "Admin"},
+            {
+                "_id": "req_1", "_type": "request", "parentId": "fld_2",
+                "name": "Create admin", "method": "POST",
+                "url": "{{ _.baseUrl }}/admins",
+                "headers": [
+                    {"name": "Accept", "value": "application/json"},
+                    {"name": "X-Debug", "value": "1", "disabled": true}
+                ],
+                "parameters": [{"name": "notify", "value": "{{ notify }}"}],
+                "body": {"mimeType": "application/json", "text": "{\"name\": \"{{ _.adminName }}\"}"},
+                "authentication": {"type": "bearer", "token": "{{ _.token }}"},
+                "description": "Creates an admin user"
+            },
+            {
+                "_id": "req_2", "_type": "request", "parentId": "wrk_1",
+                "name": "Login", "method": "POST", "url": "{{ _.baseUrl }}/login",
+                "body": {
+                    "mimeType": "application/x-www-form-urlencoded",
+                    "params": [{"name": "user", "value": "ada"}, {"name": "pass", "value": "a b"}]
+                },
+                "authentication": {"type": "basic", "username": "ada", "password": "secret"}
+            },
+            {"_id": "env_1", "_type": "environment", "parentId": "wrk_1", "name": "Base Environment",
+             "data": {"baseUrl": "http://localhost:8080", "retries": 3, "nested": {"a": 1}}},
+            {"_id": "env_2", "_type": "environment", "parentId": "env_1", "name": "Staging",
+             "data": {"baseUrl": "https://staging.example.com"}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_import_insomnia_requests() {
+        let result = import_insomnia(SAMPLE_EXPORT).unwrap();
+        assert_eq!(result.requests.len(), 2);
+
+        let create = &result.requests[0];
+        assert_eq!(create.folder.as_deref(), Some("Users/Admin"));
+        assert_eq!(create.url, "{{baseUrl}}/admins");
+        assert_eq!(create.query.get("notify"), Some(&"{{notify}}".to_string()));
+        assert_eq!(
+            create.headers.get("Authorization"),
+            Some(&"Bearer {{token}}".to_string())
+        );
+        assert!(!create.headers.contains_key("X-Debug"));
+        match &create.body {
+            Some(RequestBody::Json(json)) => assert_eq!(json["name"], "{{adminName}}"),
+            other => panic!("expected JSON body, got {:?}", other),
+        }
+
+        let login = &result.requests[1];
+        assert_eq!(login.folder, None);
+        assert_eq!(
+            login.headers.get("Authorization"),
+            Some(&"Basic YWRhOnNlY3JldA==".to_string())
+        );
+        assert!(matches!(&login.body, Some(RequestBody::Text(t)) if t == "user=ada&pass=a+b"));
+    }
+
+    #[test]
+    fn test_import_insomnia_templated_auth_and_form() {
+        let export = r#"{
+            "_type": "export",
+            "__export_format": 4,
+            "resources": [
+                {
+                    "_id": "req_1", "_type": "request", "parentId": "wrk_1",
+                    "name": "Token", "method": "POST", "url": "{{ _.baseUrl }}/token",
+                    "body": {
+                        "mimeType": "application/x-www-form-urlencoded",
+                        "params": [{"name": "refresh token", "value": "{{ _.refresh }}"}]
+                    },
+                    "authentication": {"type": "basic", "username": "{{ _.user }}", "password": "{{ _.pass }}"}
+                }
+            ]
+        }"#;
+        let result = import_insomnia(export).unwrap();
+        let token = &result.requests[0];
+        // Encoding the templates would send the wrong credentials
+        assert!(!token.headers.contains_key("Authorization"));
+        assert!(
+            result
+                .report
+                .warnings
+                .iter()
+                .any(|w| w.contains("Token") && w.contains("basic auth"))
+        );
+        assert!(
+            matches!(&token.body, Some(RequestBody::Text(t)) if t == "refresh+token={{refresh}}")
+        );
+    }
+
+    #[test]
+    fn test_import_insomnia_environments() {
+        let result = import_insomnia(SAMPLE_EXPORT).unwrap();
+        assert_eq!(result.environments.len(), 2);
+
+        let base = &result.environments[0];
+        assert_eq!(base.name, None);
+        assert_eq!(base.variables.get("retries"), Some(&"3".to_string()));
+        assert!(!base.variables.contains_key("nested"));
+
+        let staging = &result.environments[1];
+        assert_eq!(staging.name.as_deref(), Some("Staging"));
+        assert!(result.report.warnings.iter().any(|w| w.contains("nested")));
+    }
+}
@@ -1,19 +1,35 @@
+pub mod bruno;
+pub mod clipboard;
 pub mod collection;
+pub mod config;
+pub mod directory;
 pub mod discovery;
+pub mod har;
 pub mod history;
+pub mod import;
+pub mod insomnia;
+pub mod validate;
 pub mod variables;
 
+pub use bruno::*;
+pub use clipboard::*;
 pub use collection::*;
+pub use config::*;
+pub use directory::*;
 pub use discovery::*;
+pub use har::*;
 pub use history::*;
+pub use import::*;
+pub use insomnia::*;
+pub use validate::*;
 pub use variables::*;
 
 use color_eyre::Result;
 use std::path::Path;
 
-pub async fn export_last_response(path: &Path) -> Result<()> {
-    let history = load_history().await?;
-    if let Some(last_response) = history.entries.last() {
+pub async fn export_last_response(path: &Path, collection_path: &Path) -> Result<()> {
+    let history = load_history(collection_path).await?;
+    if let Some(last_response) = history.get_latest() {
         let content = serde_json::to_string_pretty(&last_response.response)?;
         tokio::fs::write(path, content).await?;
         println!("Response exported to {}", path.display());
@@ -22,3 +38,49 @@ pub async fn export_last_response(path: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+/// Prints the differences between two history entries, by id or id prefix
+pub async fn print_history_diff(collection_path: &Path, old: &str, new: &str) -> Result<()> {
+    let history = load_history(collection_path).await?;
+    let (old, new) = (history.find(old)?, history.find(new)?);
+    println!("--- {}", old.summary());
+    println!("+++ {}", new.summary());
+    print!(
+        "{}",
+        crate::core::diff_responses(&old.response, &new.response)
+    );
+    Ok(())
+}
+
+pub async fn export_history_har(
+    path: &Path,
+    collection_path: &Path,
+    request_name: Option<&str>,
+    limit: Option<usize>,
+) -> Result<()> {
+    let history = load_history(collection_path).await?;
+    let collection = load_collection(collection_path)?;
+    let interpolator = load_interpolator_with_context(collection_path).await?;
+
+    let request = request_name
+        .map(|key| crate::core::find_request(&collection, key))
+        .transpose()?;
+
+    let mut entries: Vec<&HistoryEntry> = match request {
+        Some(request) => history.get_by_request(request),
+        None => history.entries().iter().collect(),
+    };
+    if let Some(limit) = limit {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+
+    let written = export_har(&entries, &collection, &interpolator, path)?;
+    println!("Exported {} history entries to {}", written, path.display());
+    if written < entries.len() {
+        println!(
+            "Skipped {} entries whose request is no longer in the collection",
+            entries.len() - written
+        );
+    }
+    Ok(())
+}
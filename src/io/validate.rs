@@ -0,0 +1,704 @@
+use crate::core::{Request, VariableInterpolator};
+use color_eyre::{Report, Result, eyre::WrapErr};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem in a collection file, located by 1-based line and column
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub position: Option<(usize, usize)>,
+    pub request: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some((line, column)) = self.position {
+            write!(f, ":{}:{}", line, column)?;
+        }
+        write!(f, ": {}: ", self.severity)?;
+        if let Some(request) = &self.request {
+            write!(f, "request '{}': ", request)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Everything `validate_collection` found
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub requests: usize,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{}", diagnostic)?;
+        }
+        writeln!(
+            f,
+            "Checked {} request(s): {} error(s), {} warning(s)",
+            self.requests,
+            self.errors(),
+            self.warnings()
+        )
+    }
+}
+
+/// Checks a collection file or directory for parse errors, duplicate request
+/// names, unknown fields and variables that are not in `defined` or the
+/// process environment
+pub fn validate_collection(path: &Path, defined: &HashSet<String>) -> Result<ValidationReport> {
+    let documents = if path.is_dir() {
+        crate::io::request_files(path)?
+            .into_iter()
+            .map(|file| Document::read(&file, false))
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        vec![Document::read(path, true)?]
+    };
+
+    let interpolator = VariableInterpolator::new();
+    let mut report = ValidationReport::default();
+    let mut names: HashMap<String, Diagnostic> = HashMap::new();
+
+    // Variables set by `extract` count as defined once their request has run
+    let extracted: HashSet<String> = documents
+        .iter()
+        .filter_map(|document| document.entries().ok())
+        .flatten()
+        .filter_map(|entry| entry.value.get("extract")?.as_object().cloned())
+        .flat_map(|extract| extract.into_iter().map(|(name, _)| name))
+        .collect();
+
+    for document in &documents {
+        let entries = match document.entries() {
+            Ok(entries) => entries,
+            Err(diagnostic) => {
+                report.diagnostics.push(diagnostic);
+                continue;
+            }
+        };
+
+        for entry in &entries {
+            report.requests += 1;
+            let name = document.request_name(entry);
+            let diagnostic = |severity, key: Option<&str>, message: String| Diagnostic {
+                severity,
+                file: document.path.clone(),
+                position: Some(document.position(entry.offset(key))),
+                request: name.clone(),
+                message,
+            };
+
+            if let Some(error) = document.request_error(entry) {
+                report.diagnostics.push(error);
+            }
+
+            if let Some(object) = entry.value.as_object() {
+                for key in object.keys() {
+                    if !Request::FIELDS.contains(&key.as_str()) {
+                        report.diagnostics.push(diagnostic(
+                            Severity::Warning,
+                            Some(key),
+                            format!(
+                                "unknown field `{}`, expected one of {}",
+                                key,
+                                Request::FIELDS.join(", ")
+                            ),
+                        ));
+                    }
+                }
+            }
+
+            if let Some(name) = &name {
+                let here = diagnostic(Severity::Error, Some("name"), String::new());
+                match names.get(name) {
+                    Some(first) => report.diagnostics.push(Diagnostic {
+                        message: format!(
+                            "duplicate request name, first defined at {}",
+                            location(first)
+                        ),
+                        ..here
+                    }),
+                    None => {
+                        names.insert(name.clone(), here);
+                    }
+                }
+            }
+
+            if let Some(extract) = entry.value.get("extract").and_then(|e| e.as_object()) {
+                for (variable, path) in extract {
+                    if let Some(Err(error)) = path.as_str().map(crate::core::Query::parse) {
+                        report.diagnostics.push(diagnostic(
+                            Severity::Error,
+                            Some("extract"),
+                            format!("invalid query for `{}`: {}", variable, error),
+                        ));
+                    }
+                }
+            }
+
+            let mut reported = HashSet::new();
+            for field in ["url", "headers", "query", "body"] {
+                let Some(value) = entry.value.get(field) else {
+                    continue;
+                };
+                for text in strings(value) {
+                    for variable in interpolator.variables_in(text) {
+                        if !defined.contains(&variable)
+                            && !extracted.contains(&variable)
+                            && std::env::var(&variable).is_err()
+                            && reported.insert(variable.clone())
+                        {
+                            report.diagnostics.push(diagnostic(
+                                Severity::Warning,
+                                Some(field),
+                                format!(
+                                    "variable `{{{{{}}}}}` is not defined in any env file or environment",
+                                    variable
+                                ),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Error for a collection (`is_collection_file`) or request file that failed
+/// to load, pointing at the line and request that caused it
+pub(crate) fn parse_error(path: &Path, content: &str, is_collection_file: bool) -> Report {
+    let document = Document {
+        path: path.to_path_buf(),
+        content: content.to_string(),
+        yaml: crate::io::is_yaml(path),
+        is_collection_file,
+    };
+
+    let diagnostic = match document.entries() {
+        Err(diagnostic) => Some(diagnostic),
+        Ok(entries) => entries.iter().find_map(|e| document.request_error(e)),
+    };
+    match diagnostic {
+        Some(diagnostic) => Report::msg(diagnostic.to_string()),
+        None => Report::msg(format!("Failed to parse {}", path.display())),
+    }
+}
+
+fn location(diagnostic: &Diagnostic) -> String {
+    match diagnostic.position {
+        Some((line, column)) => format!("{}:{}:{}", diagnostic.file.display(), line, column),
+        None => diagnostic.file.display().to_string(),
+    }
+}
+
+fn strings(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(s) => vec![s.as_str()],
+        Value::Array(items) => items.iter().flat_map(strings).collect(),
+        Value::Object(map) => map
+            .iter()
+            .flat_map(|(key, value)| {
+                let mut found = vec![key.as_str()];
+                found.extend(strings(value));
+                found
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A collection file holding a list of requests, or a directory collection
+/// file holding one
+struct Document {
+    path: PathBuf,
+    content: String,
+    yaml: bool,
+    is_collection_file: bool,
+}
+
+/// One request in a document with the byte ranges it and its keys occupy.
+/// `span` is `None` when the text could not be matched up with the parsed
+/// value, in which case positions fall back to the start of the file.
+struct Entry {
+    value: Value,
+    span: Option<Span>,
+}
+
+impl Entry {
+    fn offset(&self, key: Option<&str>) -> usize {
+        let Some(span) = &self.span else {
+            return 0;
+        };
+        key.and_then(|key| {
+            span.keys
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, offset)| *offset)
+        })
+        .unwrap_or(span.start)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Span {
+    start: usize,
+    end: usize,
+    keys: Vec<(String, usize)>,
+}
+
+impl Span {
+    fn new(start: usize) -> Self {
+        Self {
+            start,
+            ..Default::default()
+        }
+    }
+}
+
+impl Document {
+    fn read(path: &Path, is_collection_file: bool) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read collection file: {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            content,
+            yaml: crate::io::is_yaml(path),
+            is_collection_file,
+        })
+    }
+
+    fn entries(&self) -> std::result::Result<Vec<Entry>, Diagnostic> {
+        let value: Value = if self.yaml {
+            serde_yaml::from_str(&self.content).map_err(|e| self.yaml_diagnostic(&e, 0))?
+        } else {
+            serde_json::from_str(&self.content).map_err(|e| self.json_diagnostic(&e, 0))?
+        };
+
+        let values = match value {
+            Value::Array(values) if self.is_collection_file => values,
+            _ if self.is_collection_file => {
+                return Err(Diagnostic {
+                    severity: Severity::Error,
+                    file: self.path.clone(),
+                    position: Some((1, 1)),
+                    request: None,
+                    message: "expected a list of requests".to_string(),
+                });
+            }
+            value => vec![value],
+        };
+
+        let spans = if self.yaml {
+            yaml_spans(&self.content, self.is_collection_file)
+        } else {
+            json_spans(&self.content, self.is_collection_file)
+        };
+        let matched = spans.len() == values.len();
+
+        Ok(values
+            .into_iter()
+            .zip(spans.into_iter().map(Some).chain(std::iter::repeat(None)))
+            .map(|(value, span)| Entry {
+                value,
+                span: span.filter(|_| matched),
+            })
+            .collect())
+    }
+
+    /// Request files in a directory collection may leave the name out and
+    /// take it from the file name instead
+    fn request_name(&self, entry: &Entry) -> Option<String> {
+        match entry.value.get("name").and_then(Value::as_str) {
+            Some(name) => Some(name.to_string()),
+            None if !self.is_collection_file && entry.value.is_object() => self
+                .path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned()),
+            None => None,
+        }
+    }
+
+    fn request_error(&self, entry: &Entry) -> Option<Diagnostic> {
+        let mut value = entry.value.clone();
+        if let (Some(object), Some(name)) = (value.as_object_mut(), self.request_name(entry)) {
+            object.entry("name").or_insert(Value::from(name));
+        }
+        let message = serde_json::from_value::<Request>(value).err()?.to_string();
+
+        // Re-parse the request's own text to find where serde stopped
+        let position = entry
+            .span
+            .as_ref()
+            .and_then(|span| self.typed_error(span))
+            .filter(|d| d.message.ends_with(&message))
+            .and_then(|d| d.position)
+            .unwrap_or_else(|| self.position(entry.offset(None)));
+
+        Some(Diagnostic {
+            severity: Severity::Error,
+            file: self.path.clone(),
+            position: Some(position),
+            request: self.request_name(entry),
+            message,
+        })
+    }
+
+    fn typed_error(&self, span: &Span) -> Option<Diagnostic> {
+        let text = &self.content[span.start..span.end];
+        if self.yaml {
+            // Blank out the sequence dash so the item parses as a mapping
+            // without shifting any columns
+            let text = match text.strip_prefix('-') {
+                Some(rest) if self.is_collection_file => format!(" {}", rest),
+                _ => text.to_string(),
+            };
+            let error = serde_yaml::from_str::<Request>(&text).err()?;
+            Some(self.yaml_diagnostic(&error, span.start))
+        } else {
+            let error = serde_json::from_str::<Request>(text).err()?;
+            Some(self.json_diagnostic(&error, span.start))
+        }
+    }
+
+    fn json_diagnostic(&self, error: &serde_json::Error, base: usize) -> Diagnostic {
+        let position = (error.line() > 0).then(|| (error.line(), error.column()));
+        self.located(error.to_string(), position, base)
+    }
+
+    fn yaml_diagnostic(&self, error: &serde_yaml::Error, base: usize) -> Diagnostic {
+        let position = error.location().map(|l| (l.line(), l.column()));
+        self.located(error.to_string(), position, base)
+    }
+
+    /// Turns a parser error for the text starting at byte `base` into a
+    /// diagnostic positioned in the whole file
+    fn located(
+        &self,
+        message: String,
+        position: Option<(usize, usize)>,
+        base: usize,
+    ) -> Diagnostic {
+        let message = match message.rsplit_once(" at line ") {
+            Some((message, _)) if position.is_some() => message.to_string(),
+            _ => message,
+        };
+        let position = position.map(|(line, column)| {
+            let (base_line, base_column) = self.position(base);
+            if line == 1 {
+                (base_line, base_column + column - 1)
+            } else {
+                (base_line + line - 1, column)
+            }
+        });
+
+        Diagnostic {
+            severity: Severity::Error,
+            file: self.path.clone(),
+            position: position.or(Some((1, 1))),
+            request: None,
+            message,
+        }
+    }
+
+    fn position(&self, offset: usize) -> (usize, usize) {
+        let before = &self.content[..offset.min(self.content.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        (line, before[line_start..].chars().count() + 1)
+    }
+}
+
+/// Byte ranges of the requests in a JSON document along with where their
+/// top-level keys start. With `in_list` the requests are the items of the
+/// outer array, otherwise the document itself is the request.
+fn json_spans(content: &str, in_list: bool) -> Vec<Span> {
+    let entry_depth = usize::from(in_list);
+    let mut spans = Vec::new();
+    let mut current: Option<Span> = None;
+    let mut depth = 0usize;
+    let mut chars = content.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                let mut text = String::new();
+                let mut end = content.len();
+                let mut escaped = false;
+                for (j, c) in chars.by_ref() {
+                    if escaped {
+                        escaped = false;
+                        text.push(c);
+                    } else if c == '\\' {
+                        escaped = true;
+                    } else if c == '"' {
+                        end = j + 1;
+                        break;
+                    } else {
+                        text.push(c);
+                    }
+                }
+
+                if depth == entry_depth && current.is_none() {
+                    current = Some(Span::new(i));
+                } else if depth == entry_depth + 1
+                    && let Some(span) = current.as_mut()
+                    && content[end..].trim_start().starts_with(':')
+                {
+                    span.keys.push((text, i));
+                }
+            }
+            '[' | '{' => {
+                if depth == entry_depth && current.is_none() {
+                    current = Some(Span::new(i));
+                }
+                depth += 1;
+            }
+            ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                if depth <= entry_depth
+                    && let Some(mut span) = current.take()
+                {
+                    span.end = if depth == entry_depth { i + 1 } else { i };
+                    spans.push(span);
+                }
+            }
+            ',' if depth == entry_depth => {
+                if let Some(mut span) = current.take() {
+                    span.end = i;
+                    spans.push(span);
+                }
+            }
+            c if c.is_whitespace() => {}
+            _ => {
+                if depth == entry_depth && current.is_none() {
+                    current = Some(Span::new(i));
+                }
+            }
+        }
+    }
+
+    if let Some(mut span) = current {
+        span.end = content.len();
+        spans.push(span);
+    }
+    spans
+}
+
+/// Same as `json_spans` for block-style YAML, where list items start with a
+/// `-` in the first column
+fn yaml_spans(content: &str, in_list: bool) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut key_indent = None;
+    let mut offset = 0;
+
+    if !in_list {
+        spans.push(Span::new(0));
+    }
+
+    for line in content.split_inclusive('\n') {
+        let mut text = line.trim_end().to_string();
+        if in_list && (text == "-" || text.starts_with("- ")) {
+            if let Some(last) = spans.last_mut() {
+                last.end = offset;
+            }
+            spans.push(Span::new(offset));
+            key_indent = None;
+            text.replace_range(..1, " ");
+        }
+
+        let indent = text.len() - text.trim_start().len();
+        let body = text.trim_start();
+        if let Some(span) = spans.last_mut()
+            && !body.starts_with('#')
+            && key_indent.is_none_or(|expected| expected == indent)
+            && let Some(key) = yaml_key(body)
+        {
+            key_indent = Some(indent);
+            span.keys.push((key, offset + indent));
+        }
+
+        offset += line.len();
+    }
+
+    if let Some(last) = spans.last_mut() {
+        last.end = content.len();
+    }
+    spans
+}
+
+fn yaml_key(line: &str) -> Option<String> {
+    let (key, rest) = line.split_once(':')?;
+    if !(rest.is_empty() || rest.starts_with(' ')) || key.is_empty() {
+        return None;
+    }
+    Some(
+        key.trim()
+            .trim_matches(|c| c == '"' || c == '\'')
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn messages(report: &ValidationReport) -> Vec<String> {
+        report.diagnostics.iter().map(|d| d.to_string()).collect()
+    }
+
+    #[test]
+    fn test_validate_json_collection() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("collection.json");
+        std::fs::write(
+            &path,
+            r#"[
+  {
+    "name": "Get Users",
+    "method": "GETT",
+    "url": "{{baseUrl}}/users"
+  },
+  {
+    "name": "Get Users",
+    "method": "GET",
+    "url": "https://example.com",
+    "header": {}
+  }
+]"#,
+        )
+        .unwrap();
+
+        let defined = HashSet::from(["baseUrl".to_string()]);
+        let report = validate_collection(&path, &defined).unwrap();
+        let messages = messages(&report);
+        let file = path.display();
+
+        assert_eq!(report.requests, 2);
+        assert_eq!(report.errors(), 2);
+        assert_eq!(report.warnings(), 1);
+        assert!(messages[0].starts_with(&format!(
+            "{}:4:20: error: request 'Get Users': unknown variant `GETT`",
+            file
+        )));
+        assert!(messages[1].starts_with(&format!(
+            "{}:11:5: warning: request 'Get Users': unknown field `header`",
+            file
+        )));
+        assert_eq!(
+            messages[2],
+            format!(
+                "{}:8:5: error: request 'Get Users': duplicate request name, first defined at {}:3:5",
+                file, file
+            )
+        );
+    }
+
+    #[test]
+    fn test_validate_yaml_missing_field_and_variables() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("collection.yaml");
+        std::fs::write(
+            &path,
+            "- name: Health\n  method: GET\n  url: https://example.com\n- name: Login\n  method: POST\n  headers:\n    Authorization: Bearer {{netbook_test_token}}\n",
+        )
+        .unwrap();
+
+        let report = validate_collection(&path, &HashSet::new()).unwrap();
+        let messages = messages(&report);
+        let file = path.display();
+
+        assert_eq!(report.errors(), 1);
+        assert!(messages[0].starts_with(&format!(
+            "{}:4:3: error: request 'Login': missing field `url`",
+            file
+        )));
+        assert_eq!(
+            messages[1],
+            format!(
+                "{}:6:3: warning: request 'Login': variable `{{{{netbook_test_token}}}}` is not defined in any env file or environment",
+                file
+            )
+        );
+    }
+
+    #[test]
+    fn test_validate_extract() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("collection.yaml");
+        std::fs::write(
+            &path,
+            "- name: Login\n  method: POST\n  url: https://example.com/login\n  extract:\n    token: $.auth.token\n    broken: $.items[\n- name: Me\n  method: GET\n  url: https://example.com/me\n  headers:\n    Authorization: Bearer {{token}}\n",
+        )
+        .unwrap();
+
+        let report = validate_collection(&path, &HashSet::new()).unwrap();
+        let messages = messages(&report);
+
+        assert_eq!(report.errors(), 1);
+        assert_eq!(report.warnings(), 0);
+        assert!(messages[0].starts_with(&format!(
+            "{}:4:3: error: request 'Login': invalid query for `broken`: Missing ']'",
+            path.display()
+        )));
+    }
+
+    #[test]
+    fn test_load_collection_reports_location() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("collection.json");
+        std::fs::write(
+            &path,
+            "[\n  {\"name\": \"A\", \"method\": \"GET\", \"url\": \"x\"},\n  {\"name\": \"B\", \"method\": \"GETT\", \"url\": \"x\"}\n]",
+        )
+        .unwrap();
+
+        let error = crate::io::load_collection(&path).unwrap_err().to_string();
+        assert!(error.starts_with(&format!(
+            "{}:3:32: error: request 'B': unknown variant `GETT`",
+            path.display()
+        )));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::core::VariableInterpolator;
 use color_eyre::Result;
 use directories::ProjectDirs;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 pub fn get_variables_file_path() -> Option<PathBuf> {
@@ -32,14 +32,65 @@ pub async fn load_variables() -> Result<HashMap<String, String>> {
     Ok(HashMap::new())
 }
 
+/// Names of the environments in `.netbook/.env.<name>`, sorted
+pub fn list_environments(collection_path: impl AsRef<std::path::Path>) -> Vec<String> {
+    let netbook_dir = crate::io::get_netbook_dir(collection_path.as_ref());
+    let mut names: Vec<String> = std::fs::read_dir(&netbook_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            name.strip_prefix(".env.").map(str::to_string)
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Every variable name an env file, a named environment or the saved
+/// variables define for this collection
+pub async fn defined_variables(
+    collection_path: impl AsRef<std::path::Path>,
+) -> Result<HashSet<String>> {
+    let collection_path = collection_path.as_ref();
+    let mut files = VariableInterpolator::env_file_paths(collection_path);
+
+    let netbook_dir = crate::io::get_netbook_dir(collection_path);
+    files.extend(
+        list_environments(collection_path)
+            .iter()
+            .map(|name| netbook_dir.join(format!(".env.{}", name))),
+    );
+
+    let mut names: HashSet<String> = load_variables().await?.into_keys().collect();
+    for file in files {
+        if let Ok(content) = std::fs::read_to_string(&file) {
+            names.extend(crate::core::parse_env_content(&content).into_keys());
+        }
+    }
+    Ok(names)
+}
+
 pub async fn load_interpolator_with_context(
     collection_path: impl AsRef<std::path::Path>,
+) -> Result<VariableInterpolator> {
+    load_interpolator_for_environment(collection_path, None).await
+}
+
+pub async fn load_interpolator_for_environment(
+    collection_path: impl AsRef<std::path::Path>,
+    environment: Option<&str>,
 ) -> Result<VariableInterpolator> {
     let mut interpolator = VariableInterpolator::new();
 
     // Load from .netbook.env file
     interpolator.load_env_file(&collection_path)?;
 
+    // Layer the selected environment (.netbook/.env.<name>) on top
+    if let Some(environment) = environment {
+        interpolator.load_environment(&collection_path, environment)?;
+    }
+
     // Load saved variables
     let saved_vars = load_variables().await?;
     for (key, value) in saved_vars {
@@ -30,6 +30,12 @@ mod integration_tests {
             query: std::collections::HashMap::new(),
             body: None,
             notes: Some("Integration test request".to_string()),
+            folder: None,
+            source: None,
+            disabled_headers: std::collections::HashMap::new(),
+            disabled_query: std::collections::HashMap::new(),
+            extract: std::collections::HashMap::new(),
+            id: uuid::Uuid::new_v4(),
         }];
 
         // Save collection
@@ -68,6 +74,12 @@ mod integration_tests {
                 .collect(),
             body: None,
             notes: None,
+            folder: None,
+            source: None,
+            disabled_headers: std::collections::HashMap::new(),
+            disabled_query: std::collections::HashMap::new(),
+            extract: std::collections::HashMap::new(),
+            id: uuid::Uuid::new_v4(),
         };
 
         let interpolated = interpolator.interpolate_request(&request);
@@ -94,11 +106,12 @@ mod integration_tests {
             body: serde_json::json!({"message": "success"}),
             timing: crate::core::ResponseTiming::default(),
             timestamp: chrono::Utc::now(),
+            sent: None,
         };
 
         history.add_entry("Test Request".to_string(), response.clone());
 
-        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries().len(), 1);
         assert_eq!(history.get_latest().unwrap().request_name, "Test Request");
         assert_eq!(history.get_by_request_name("Test Request").len(), 1);
     }
@@ -118,6 +131,12 @@ mod integration_tests {
                 serde_json::json!({"test": "data"}),
             )),
             notes: Some("Test notes".to_string()),
+            folder: None,
+            source: None,
+            disabled_headers: std::collections::HashMap::new(),
+            disabled_query: std::collections::HashMap::new(),
+            extract: std::collections::HashMap::new(),
+            id: uuid::Uuid::new_v4(),
         };
 
         // Test JSON serialization
@@ -143,6 +162,12 @@ mod integration_tests {
             query: std::collections::HashMap::new(),
             body: None,
             notes: None,
+            folder: None,
+            source: None,
+            disabled_headers: std::collections::HashMap::new(),
+            disabled_query: std::collections::HashMap::new(),
+            extract: std::collections::HashMap::new(),
+            id: uuid::Uuid::new_v4(),
         }];
 
         // Test JSON
@@ -155,4 +180,39 @@ mod integration_tests {
         let loaded_yaml = crate::io::load_collection(&yaml_path).unwrap();
         assert_eq!(loaded_yaml.len(), 1);
     }
+
+    #[test]
+    fn test_find_request_by_id_or_name() {
+        let request = |name: &str| crate::core::Request {
+            name: name.to_string(),
+            method: crate::core::HttpMethod::Get,
+            url: "https://example.com".to_string(),
+            headers: std::collections::HashMap::new(),
+            query: std::collections::HashMap::new(),
+            body: None,
+            notes: None,
+            folder: None,
+            source: None,
+            disabled_headers: std::collections::HashMap::new(),
+            disabled_query: std::collections::HashMap::new(),
+            extract: std::collections::HashMap::new(),
+            id: uuid::Uuid::new_v4(),
+        };
+        let collection = vec![request("Health"), request("Login"), request("Login")];
+
+        let found = crate::core::find_request(&collection, "Health").unwrap();
+        assert_eq!(found.id, collection[0].id);
+
+        let id = collection[2].id.to_string();
+        let found = crate::core::find_request(&collection, &id).unwrap();
+        assert_eq!(found.id, collection[2].id);
+
+        let error = crate::core::find_request(&collection, "Login").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("2 requests are named 'Login'")
+        );
+        assert!(crate::core::find_request(&collection, "Missing").is_err());
+    }
 }
@@ -99,6 +99,12 @@ mod tests {
             query: HashMap::new(),
             body: None,
             notes: None,
+            folder: None,
+            source: None,
+            disabled_headers: HashMap::new(),
+            disabled_query: HashMap::new(),
+            extract: HashMap::new(),
+            id: uuid::Uuid::new_v4(),
         };
 
         let response = Response {
@@ -109,6 +115,7 @@ mod tests {
             body: serde_json::json!({"message": "success"}),
             timing: ResponseTiming::default(),
             timestamp: Utc::now(),
+            sent: None,
         };
 
         plugin.before_request(&request).await;
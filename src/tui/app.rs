@@ -1,26 +1,47 @@
-use crate::core::RequestExecutor;
-use crate::tui::{AppEvent, AppMode, AppState, EventHandler};
+use crate::core::{CodeLanguage, Request, RequestExecutor};
+use crate::io::clear_history;
+use crate::tui::{
+    Action, AppEvent, AppMode, AppState, Command, EventHandler, FormField, ResponsePart,
+    ResponseTab, VariableEditField, YankTarget, watcher,
+};
 use crate::ui::{render_app, restore_terminal, setup_terminal};
 use color_eyre::Result;
-use crossterm::event::{KeyCode, KeyModifiers};
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Position;
 use std::path::Path;
 
+/// Lines (or tree nodes) the response pane moves per wheel notch
+const MOUSE_SCROLL_LINES: isize = 3;
+
 pub struct TuiApp {
     state: AppState,
     event_handler: EventHandler,
     executor: RequestExecutor,
+    watcher: tokio::task::JoinHandle<()>,
+    /// Set when an external program has drawn over the screen, so the next
+    /// frame is drawn in full
+    redraw: bool,
 }
 
 impl TuiApp {
-    pub async fn new(collection_path: impl AsRef<Path>) -> Result<Self> {
-        let state = AppState::new(collection_path.as_ref().to_path_buf()).await?;
+    pub async fn new(
+        collection_path: impl AsRef<Path>,
+        environment: Option<String>,
+    ) -> Result<Self> {
+        let state = AppState::new(collection_path.as_ref().to_path_buf(), environment).await?;
         let event_handler = EventHandler::new();
         let executor = RequestExecutor::new();
+        let watcher = watcher::spawn_watcher(
+            watcher::watched_paths(&state.collection_path, state.environment.as_deref()),
+            event_handler.get_sender(),
+        );
 
         Ok(Self {
             state,
             event_handler,
             executor,
+            watcher,
+            redraw: false,
         })
     }
 
@@ -28,6 +49,7 @@ impl TuiApp {
         let mut terminal = setup_terminal()?;
 
         let result = self.run_app(&mut terminal).await;
+        self.watcher.abort();
 
         restore_terminal(&mut terminal)?;
 
@@ -40,6 +62,9 @@ impl TuiApp {
     ) -> Result<()> {
         loop {
             // Render UI
+            if std::mem::take(&mut self.redraw) {
+                terminal.clear()?;
+            }
             terminal.draw(|frame| render_app(frame, &self.state))?;
 
             // Handle events
@@ -50,32 +75,41 @@ impl TuiApp {
                             break;
                         }
                     }
+                    AppEvent::Mouse(mouse) => self.handle_mouse_event(mouse).await?,
                     AppEvent::ExecutionStarted => {
                         self.state.is_executing = true;
                         self.state.status_message = "Executing request...".to_string();
                     }
-                    AppEvent::ExecutionCompleted(response) => {
+                    AppEvent::ExecutionCompleted(response, sent) => {
                         self.state.is_executing = false;
                         self.state.status_message = format!(
                             "Request completed - Status: {} ({}ms)",
                             response.status, response.timing.total_ms
                         );
 
-                        // Save to history
-                        if let Some(request) = self.state.get_current_request() {
-                            let _ = self
-                                .state
-                                .save_response_to_history(request.name.clone(), response.clone())
-                                .await;
+                        // The sent request came with the response: the
+                        // selection may have moved while it was running
+                        let extracted =
+                            self.state.interpolator.apply_extract(&sent, &response.body);
+                        if !extracted.is_empty() {
+                            self.state.status_message +=
+                                &format!(" - set {}", extracted.join(", "));
                         }
 
-                        self.state.current_response = Some(response);
+                        // Save to history
+                        let _ = self
+                            .state
+                            .save_response_to_history(&sent, (*response).clone())
+                            .await;
+
+                        self.state.set_response(*response);
                     }
                     AppEvent::ExecutionFailed(error) => {
                         self.state.is_executing = false;
                         self.state.status_message = format!("Request failed: {}", error);
                         self.state.current_response = None;
                     }
+                    AppEvent::FilesChanged => self.state.reload_from_disk(),
                     AppEvent::Quit => break,
                 }
             }
@@ -88,60 +122,311 @@ impl TuiApp {
         Ok(())
     }
 
-    async fn handle_key_event(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
+    /// Clicks select requests, response tabs and history entries; the
+    /// wheel scrolls whatever is under the pointer
+    async fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<()> {
+        if self.state.show_help {
+            if let MouseEventKind::Down(_) = mouse.kind {
+                self.state.show_help = false;
+            }
+            return Ok(());
+        }
+
+        let areas = self.state.screen_areas.get();
+        let position = Position::new(mouse.column, mouse.row);
+        let click = mouse.kind == MouseEventKind::Down(MouseButton::Left);
+        let scroll = match mouse.kind {
+            MouseEventKind::ScrollUp => -1,
+            MouseEventKind::ScrollDown => 1,
+            _ => 0,
+        };
+
         match self.state.mode {
-            AppMode::Normal => self.handle_normal_mode_keys(key).await,
-            AppMode::Filter => self.handle_filter_mode_keys(key),
-            AppMode::Variables => self.handle_variables_mode_keys(key).await,
-            AppMode::History => self.handle_history_mode_keys(key),
-            AppMode::Command => self.handle_command_mode_keys(key).await,
+            AppMode::Normal | AppMode::Response if click => {
+                if let Some(index) = areas.request_at(position) {
+                    if index < self.state.filtered_indices.len() {
+                        self.state.selected_request_index = index;
+                        self.state.mode = AppMode::Normal;
+                    }
+                } else if let Some(tab) = areas.response_tab_at(position) {
+                    self.state.response_tab = ResponseTab::ALL[tab];
+                } else if areas.response.contains(position) && self.state.current_response.is_some()
+                {
+                    self.state.mode = AppMode::Response;
+                }
+            }
+            AppMode::Normal | AppMode::Response if scroll != 0 => {
+                if areas.requests.contains(position) {
+                    if scroll < 0 {
+                        self.state.move_selection_up();
+                    } else {
+                        self.state.move_selection_down();
+                    }
+                } else if areas.response.contains(position) && self.state.current_response.is_some()
+                {
+                    self.state.scroll_response(scroll * MOUSE_SCROLL_LINES);
+                }
+            }
+            AppMode::History if click => match areas.history_at(position) {
+                // A second click on the selected entry opens it, like Enter
+                Some(index) if index == self.state.history_selected_index => {
+                    self.handle_history_mode_action(Action::Select).await?;
+                }
+                Some(index) if index < self.state.visible_history().len() => {
+                    self.state.history_selected_index = index;
+                }
+                Some(_) => {}
+                None if !areas.history.contains(position) => {
+                    self.state.mode = AppMode::Normal;
+                }
+                None => {}
+            },
+            AppMode::Diff if scroll != 0 => {
+                self.state.scroll_diff(scroll * MOUSE_SCROLL_LINES, false);
+            }
+            AppMode::HistoryDetail if scroll != 0 => {
+                self.state
+                    .scroll_history_detail(scroll * MOUSE_SCROLL_LINES, false);
+            }
+            AppMode::History if scroll != 0 => {
+                let action = if scroll < 0 { Action::Up } else { Action::Down };
+                self.handle_history_mode_action(action).await?;
+            }
+            _ => {}
         }
+        Ok(())
     }
 
-    async fn handle_normal_mode_keys(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
-        match key.code {
-            KeyCode::Char('q') => {
-                self.state.should_quit = true;
-                return Ok(true);
+    async fn handle_key_event(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
+        if self.state.show_help {
+            self.state.show_help = false;
+            return Ok(false);
+        }
+
+        // Modes without a keymap get the raw key, e.g. to type text
+        let action = self.state.keymap.action(&self.state.mode, &key);
+        if action == Some(Action::Help) {
+            self.state.show_help = true;
+            return Ok(false);
+        }
+
+        match (self.state.mode.clone(), action) {
+            (AppMode::Normal, Some(action)) => self.handle_normal_mode_action(action).await,
+            (AppMode::Filter, _) => self.handle_filter_mode_keys(key),
+            (AppMode::Variables, Some(action)) => self.handle_variables_mode_action(action).await,
+            (AppMode::History, Some(action)) => self.handle_history_mode_action(action).await,
+            (AppMode::Diff, Some(action)) => self.handle_diff_mode_action(action),
+            (AppMode::HistoryFilter, _) => self.handle_history_filter_keys(key),
+            (AppMode::HistoryDetail, Some(action)) => {
+                self.handle_history_detail_action(action).await
             }
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            (AppMode::Command, _) => self.handle_command_mode_keys(key).await,
+            (AppMode::Codegen, Some(action)) => self.handle_codegen_mode_action(action),
+            (AppMode::NewRequest | AppMode::RenameRequest, _) => self.handle_name_prompt_keys(key),
+            (AppMode::ConfirmDelete, Some(action)) => self.handle_confirm_delete_action(action),
+            (AppMode::Form, Some(action)) => self.handle_form_mode_action(action),
+            (AppMode::FormEdit, _) => self.handle_form_edit_mode_keys(key),
+            (AppMode::VariableEdit, _) => self.handle_variable_edit_mode_keys(key).await,
+            (AppMode::Response, Some(action)) => self.handle_response_mode_action(action),
+            (AppMode::SaveJsonValue | AppMode::AddExtract, _) => {
+                self.handle_json_name_prompt_keys(key)
+            }
+            (AppMode::ResponseSearch, _) => self.handle_response_search_keys(key),
+            (AppMode::ResponseQuery, _) => self.handle_response_query_keys(key),
+            (AppMode::SaveResponse, _) => self.handle_save_response_keys(key),
+            _ => Ok(false),
+        }
+    }
+
+    async fn handle_normal_mode_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Quit => {
                 self.state.should_quit = true;
                 return Ok(true);
             }
-            KeyCode::Up | KeyCode::Char('k') => {
+            Action::Up => {
                 self.state.move_selection_up();
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Action::Down => {
                 self.state.move_selection_down();
             }
-            KeyCode::Enter => {
+            Action::Run => {
                 self.execute_current_request().await?;
             }
-            KeyCode::Char('/') => {
+            Action::Filter => {
                 self.state.mode = AppMode::Filter;
                 self.state.filter_text.clear();
             }
-            KeyCode::Char('v') => {
+            Action::Variables => {
                 self.state.mode = AppMode::Variables;
             }
-            KeyCode::Char('h') => {
+            Action::History => {
                 self.state.mode = AppMode::History;
             }
-            KeyCode::Char(':') => {
+            Action::Command => {
+                self.state.command_input.clear();
+                self.state.command_completions.clear();
                 self.state.mode = AppMode::Command;
             }
-            KeyCode::Char('e') => {
+            Action::Edit => {
+                self.state.open_form();
+            }
+            Action::EditExternal => {
                 self.edit_current_request().await?;
             }
-            KeyCode::Tab => {
+            Action::Codegen if self.state.get_current_request().is_some() => {
+                self.state.mode = AppMode::Codegen;
+            }
+            Action::Yank => self.yank(YankTarget::Code(CodeLanguage::Curl)),
+            Action::NewRequest => {
+                self.state.name_input.clear();
+                self.state.mode = AppMode::NewRequest;
+            }
+            Action::Duplicate => {
+                self.state.duplicate_current_request();
+            }
+            Action::Rename => {
+                if let Some(request) = self.state.get_current_request() {
+                    self.state.name_input = request.name.clone();
+                    self.state.mode = AppMode::RenameRequest;
+                }
+            }
+            Action::Delete if self.state.get_current_request().is_some() => {
+                self.state.mode = AppMode::ConfirmDelete;
+            }
+            Action::MoveRequestUp => {
+                self.state.move_current_request(true);
+            }
+            Action::MoveRequestDown => {
+                self.state.move_current_request(false);
+            }
+            Action::NextTab => {
+                self.state.next_response_tab();
+            }
+            Action::PreviousTab => {
+                self.state.previous_response_tab();
+            }
+            Action::FocusResponse if self.state.current_response.is_some() => {
+                self.state.mode = AppMode::Response;
+            }
+            Action::PageUp => self.state.page_response(false),
+            Action::PageDown => self.state.page_response(true),
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_response_mode_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back => {
+                self.state.mode = AppMode::Normal;
+            }
+            Action::NextTab => {
                 self.state.next_response_tab();
             }
-            KeyCode::BackTab => {
+            Action::PreviousTab => {
                 self.state.previous_response_tab();
             }
-            KeyCode::Char('s') if self.state.current_response.is_some() => {
-                // Save response variable - simplified for now
-                self.state.status_message = "Response variable saved (simplified)".to_string();
+            Action::Up => self.state.scroll_response(-1),
+            Action::Down => self.state.scroll_response(1),
+            Action::PageUp => self.state.page_response(false),
+            Action::PageDown => self.state.page_response(true),
+            Action::Top => self.state.scroll_response_to_end(false),
+            Action::Bottom => self.state.scroll_response_to_end(true),
+            Action::Search => {
+                self.state.response_search.clear();
+                self.state.mode = AppMode::ResponseSearch;
+            }
+            Action::NextMatch => self.state.search_response(true),
+            Action::PreviousMatch => self.state.search_response(false),
+            Action::Query => {
+                self.state.mode = AppMode::ResponseQuery;
+            }
+            Action::Toggle => self.state.toggle_selected_json_node(),
+            Action::ExpandAll => self.state.expand_all_json(),
+            Action::CollapseAll => self.state.collapse_all_json(),
+            Action::SaveValue => {
+                self.state.name_input = self.state.selected_json_variable_name();
+                self.state.mode = AppMode::SaveJsonValue;
+            }
+            Action::Extract => {
+                self.state.name_input = self.state.selected_json_variable_name();
+                self.state.mode = AppMode::AddExtract;
+            }
+            Action::Yank => self.yank(self.state.response_yank_target()),
+            Action::YankBody => self.yank(YankTarget::Body),
+            Action::SaveResponse => self.state.open_save_response(),
+            Action::OpenPager => self.open_response_externally(false)?,
+            Action::OpenEditor => self.open_response_externally(true)?,
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_response_search_keys(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
+        match key.code {
+            KeyCode::Esc => {
+                self.state.response_search.clear();
+                self.state.mode = AppMode::Response;
+            }
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Response;
+                self.state.search_response(true);
+            }
+            KeyCode::Backspace => {
+                self.state.response_search.pop();
+            }
+            KeyCode::Char(c) => {
+                self.state.response_search.push(c);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// The body is re-filtered on every keystroke
+    fn handle_response_query_keys(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
+        match key.code {
+            KeyCode::Esc => {
+                self.state.response_query.clear();
+                self.state.apply_response_query();
+                self.state.mode = AppMode::Response;
+            }
+            KeyCode::Enter => {
+                self.state.mode = AppMode::Response;
+            }
+            KeyCode::Backspace => {
+                self.state.response_query.pop();
+                self.state.apply_response_query();
+            }
+            KeyCode::Char(c) => {
+                self.state.response_query.push(c);
+                self.state.apply_response_query();
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_json_name_prompt_keys(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
+        match key.code {
+            KeyCode::Esc => {
+                self.state.mode = AppMode::Response;
+            }
+            KeyCode::Enter => {
+                let name = std::mem::take(&mut self.state.name_input);
+                if matches!(self.state.mode, AppMode::SaveJsonValue) {
+                    self.state.save_selected_json_value(&name);
+                } else {
+                    self.state.add_extract_for_selected(&name);
+                }
+                self.state.mode = AppMode::Response;
+            }
+            KeyCode::Backspace => {
+                self.state.name_input.pop();
+            }
+            KeyCode::Char(c) => {
+                self.state.name_input.push(c);
             }
             _ => {}
         }
@@ -170,45 +455,272 @@ impl TuiApp {
         Ok(false)
     }
 
-    async fn handle_variables_mode_keys(
+    async fn handle_variables_mode_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back => {
+                self.state.mode = AppMode::Normal;
+            }
+            Action::Up => self.state.move_variable_selection(false),
+            Action::Down => self.state.move_variable_selection(true),
+            Action::Edit => self.state.start_variable_edit(false),
+            Action::Add => self.state.start_variable_edit(true),
+            Action::Delete => {
+                if let Err(e) = self.state.delete_selected_variable().await {
+                    self.state.status_message = format!("Failed to save variables: {}", e);
+                }
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    async fn handle_variable_edit_mode_keys(
         &mut self,
         key: crossterm::event::KeyEvent,
     ) -> Result<bool> {
+        let Some(edit) = self.state.variable_edit.as_mut() else {
+            self.state.mode = AppMode::Variables;
+            return Ok(false);
+        };
+
         match key.code {
-            KeyCode::Esc | KeyCode::Char('v') => {
+            KeyCode::Esc => {
+                self.state.variable_edit = None;
+                self.state.mode = AppMode::Variables;
+            }
+            KeyCode::Enter => {
+                if let Err(e) = self.state.commit_variable_edit().await {
+                    self.state.status_message = format!("Failed to save variables: {}", e);
+                }
+            }
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                let forward = matches!(key.code, KeyCode::Tab | KeyCode::Down);
+                edit.cycle_focus(forward);
+            }
+            KeyCode::Char(' ') if edit.focus == VariableEditField::Persist => {
+                edit.persist = !edit.persist;
+            }
+            KeyCode::Backspace => match edit.focus {
+                VariableEditField::Key => {
+                    edit.key.pop();
+                }
+                VariableEditField::Value => {
+                    edit.value.pop();
+                }
+                VariableEditField::Persist => {}
+            },
+            KeyCode::Char(c) => match edit.focus {
+                VariableEditField::Key => edit.key.push(c),
+                VariableEditField::Value => edit.value.push(c),
+                VariableEditField::Persist => {}
+            },
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    async fn handle_history_mode_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back => {
                 self.state.mode = AppMode::Normal;
             }
-            // TODO: Add variable editing functionality
+            Action::Up if self.state.history_selected_index > 0 => {
+                self.state.history_selected_index -= 1;
+            }
+            Action::Down
+                if self.state.history_selected_index + 1 < self.state.visible_history().len() =>
+            {
+                self.state.history_selected_index += 1;
+            }
+            Action::Select => {
+                if let Some(entry) = self.state.selected_history_entry() {
+                    self.state.set_response(entry.response.clone());
+                    self.state.mode = AppMode::Normal;
+                }
+            }
+            Action::Mark => self.state.toggle_history_mark(),
+            Action::Diff => self.state.open_diff(),
+            Action::Filter => {
+                self.state.mode = AppMode::HistoryFilter;
+            }
+            Action::Details => self.state.open_history_detail(),
+            Action::Run => self.rerun_history_entry(),
+            Action::Delete => {
+                self.state.status_message = match self.state.delete_selected_history_entry().await {
+                    Ok(()) => "Deleted the history entry".to_string(),
+                    Err(e) => format!("Failed to delete the history entry: {}", e),
+                };
+            }
             _ => {}
         }
         Ok(false)
     }
 
-    fn handle_history_mode_keys(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
+    fn handle_history_filter_keys(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
         match key.code {
-            KeyCode::Esc | KeyCode::Char('h') => {
+            KeyCode::Enter => {
+                self.state.mode = AppMode::History;
+            }
+            KeyCode::Esc => {
+                self.state.mode = AppMode::History;
+                self.state.update_history_filter(String::new());
+            }
+            KeyCode::Backspace => {
+                let mut text = self.state.history_filter_text.clone();
+                text.pop();
+                self.state.update_history_filter(text);
+            }
+            KeyCode::Char(c) => {
+                let text = format!("{}{}", self.state.history_filter_text, c);
+                self.state.update_history_filter(text);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    async fn handle_history_detail_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back => {
+                self.state.history_detail = None;
+                self.state.mode = AppMode::History;
+            }
+            Action::Up => self.state.scroll_history_detail(-1, false),
+            Action::Down => self.state.scroll_history_detail(1, false),
+            Action::PageUp => self.state.scroll_history_detail(-1, true),
+            Action::PageDown => self.state.scroll_history_detail(1, true),
+            Action::Top => self.state.scroll_history_detail(isize::MIN, false),
+            Action::Bottom => self.state.scroll_history_detail(isize::MAX, false),
+            Action::Select | Action::Run => {
+                self.state.history_detail = None;
+                return self.handle_history_mode_action(action).await;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_diff_mode_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back => {
+                self.state.diff_view = None;
+                self.state.mode = AppMode::History;
+            }
+            Action::Up => self.state.scroll_diff(-1, false),
+            Action::Down => self.state.scroll_diff(1, false),
+            Action::PageUp => self.state.scroll_diff(-1, true),
+            Action::PageDown => self.state.scroll_diff(1, true),
+            Action::Top => self.state.scroll_diff(isize::MIN, false),
+            Action::Bottom => self.state.scroll_diff(isize::MAX, false),
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_codegen_mode_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Back => {
                 self.state.mode = AppMode::Normal;
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                if self.state.history_selected_index > 0 {
-                    self.state.history_selected_index -= 1;
-                }
+            Action::Right => {
+                self.state.codegen_language = self.state.codegen_language.next();
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                if self.state.history_selected_index + 1 < self.state.history.entries.len() {
-                    self.state.history_selected_index += 1;
-                }
+            Action::Left => {
+                self.state.codegen_language = self.state.codegen_language.previous();
+            }
+            Action::Yank => self.yank(YankTarget::Code(self.state.codegen_language)),
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_form_mode_action(&mut self, action: Action) -> Result<bool> {
+        let Some(form) = self.state.form.as_mut() else {
+            self.state.mode = AppMode::Normal;
+            return Ok(false);
+        };
+
+        let on_method = form.focus == FormField::Method;
+        let in_table = form.rows().is_some();
+        match action {
+            Action::Discard => self.state.discard_form(),
+            Action::Save => self.state.save_form(),
+            Action::NextField => form.focus_next(),
+            Action::PreviousField => form.focus_previous(),
+            Action::Left if on_method => form.cycle_method(false),
+            Action::Right | Action::Toggle | Action::Edit if on_method => form.cycle_method(true),
+            Action::Up => form.move_row(false),
+            Action::Down => form.move_row(true),
+            Action::Left if in_table => form.value_column = false,
+            Action::Right if in_table => form.value_column = true,
+            Action::AddRow if in_table => {
+                form.add_row();
+                self.state.mode = AppMode::FormEdit;
+            }
+            Action::RemoveRow if in_table => form.remove_row(),
+            Action::Toggle if in_table => form.toggle_row(),
+            Action::Edit if form.active_text().is_some() => {
+                self.state.mode = AppMode::FormEdit;
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_form_edit_mode_keys(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
+        let Some(form) = self.state.form.as_mut() else {
+            self.state.mode = AppMode::Normal;
+            return Ok(false);
+        };
+
+        match key.code {
+            KeyCode::Esc => self.state.mode = AppMode::Form,
+            KeyCode::Enter if form.focus == FormField::Body => form.insert_char('\n'),
+            // Enter on a key moves on to its value
+            KeyCode::Enter | KeyCode::Tab if form.rows().is_some() && !form.value_column => {
+                form.value_column = true;
+            }
+            KeyCode::Enter => self.state.mode = AppMode::Form,
+            KeyCode::Backspace => form.delete_char(),
+            KeyCode::Char(c) => form.insert_char(c),
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_name_prompt_keys(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
+        match key.code {
+            KeyCode::Esc => {
+                self.state.mode = AppMode::Normal;
             }
             KeyCode::Enter => {
-                if let Some(entry) = self
-                    .state
-                    .history
-                    .entries
-                    .get(self.state.history_selected_index)
-                {
-                    self.state.current_response = Some(entry.response.clone());
-                    self.state.mode = AppMode::Normal;
+                let name = std::mem::take(&mut self.state.name_input);
+                if matches!(self.state.mode, AppMode::NewRequest) {
+                    self.state.add_request(&name);
+                } else {
+                    self.state.rename_current_request(&name);
                 }
+                self.state.mode = AppMode::Normal;
+            }
+            KeyCode::Backspace => {
+                self.state.name_input.pop();
+            }
+            KeyCode::Char(c) => {
+                self.state.name_input.push(c);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    fn handle_confirm_delete_action(&mut self, action: Action) -> Result<bool> {
+        match action {
+            Action::Confirm => {
+                self.state.delete_current_request();
+                self.state.mode = AppMode::Normal;
+            }
+            Action::Cancel => {
+                self.state.mode = AppMode::Normal;
             }
             _ => {}
         }
@@ -220,63 +732,287 @@ impl TuiApp {
             KeyCode::Esc => {
                 self.state.mode = AppMode::Normal;
             }
-            KeyCode::Char('e') => {
-                self.edit_current_request().await?;
+            KeyCode::Enter => {
+                let input = std::mem::take(&mut self.state.command_input);
+                self.state.command_completions.clear();
                 self.state.mode = AppMode::Normal;
+                return self.run_command(&input).await;
+            }
+            KeyCode::Tab => self.state.complete_command(),
+            KeyCode::Backspace if self.state.command_input.is_empty() => {
+                self.state.mode = AppMode::Normal;
+            }
+            KeyCode::Backspace => {
+                self.state.command_input.pop();
+                self.state.command_completions.clear();
+            }
+            KeyCode::Char(c) => {
+                self.state.command_input.push(c);
+                self.state.command_completions.clear();
             }
             _ => {}
         }
         Ok(false)
     }
 
+    fn save_response(&mut self, part: ResponsePart, path: &Path) {
+        self.state.status_message = match self.state.save_response(part, path) {
+            Ok(()) => format!("Saved the {} to {}", part.label(), path.display()),
+            Err(e) => format!("Failed to save the {}: {}", part.label(), e),
+        };
+    }
+
+    fn handle_save_response_keys(&mut self, key: crossterm::event::KeyEvent) -> Result<bool> {
+        match key.code {
+            KeyCode::Esc => {
+                self.state.mode = AppMode::Response;
+            }
+            KeyCode::Enter => {
+                let path = std::mem::take(&mut self.state.name_input);
+                self.state.mode = AppMode::Response;
+                if !path.trim().is_empty() {
+                    self.save_response(self.state.save_part, Path::new(path.trim()));
+                }
+            }
+            KeyCode::Tab => self.state.cycle_save_part(),
+            KeyCode::Backspace => {
+                self.state.name_input.pop();
+            }
+            KeyCode::Char(c) => {
+                self.state.name_input.push(c);
+            }
+            _ => {}
+        }
+        Ok(false)
+    }
+
+    /// Copies `target` to the clipboard, saying what was copied
+    fn yank(&mut self, target: YankTarget) {
+        let copied = self.state.yank_text(&target).and_then(|(text, what)| {
+            self.state.clipboard.copy(&text)?;
+            Ok(what)
+        });
+        self.state.status_message = match copied {
+            Ok(what) => format!("Copied {} ({})", what, self.state.clipboard.name()),
+            Err(e) => format!("Failed to copy: {}", e),
+        };
+    }
+
+    /// Runs a `:` command; errors are shown in the status bar
+    async fn run_command(&mut self, input: &str) -> Result<bool> {
+        let command = match Command::parse(input) {
+            Ok(command) => command,
+            Err(e) => {
+                self.state.status_message = e.to_string();
+                return Ok(false);
+            }
+        };
+
+        match command {
+            Command::Run(name) => {
+                if let Some(name) = name {
+                    match crate::core::find_request(&self.state.collection, &name) {
+                        Ok(request) => self.state.select_request(request.id),
+                        Err(e) => {
+                            self.state.status_message = e.to_string();
+                            return Ok(false);
+                        }
+                    }
+                }
+                self.execute_current_request().await?;
+            }
+            Command::Env(name) => match self.state.switch_environment(name) {
+                Ok(()) => {
+                    // The watched env files depend on the environment
+                    self.watcher.abort();
+                    self.watcher = watcher::spawn_watcher(
+                        watcher::watched_paths(
+                            &self.state.collection_path,
+                            self.state.environment.as_deref(),
+                        ),
+                        self.event_handler.get_sender(),
+                    );
+                }
+                Err(e) => self.state.status_message = e.to_string(),
+            },
+            Command::Set { key, value } => {
+                self.state.status_message = format!("Set {} for this session", key);
+                self.state.set_variable(key, value);
+            }
+            Command::Export {
+                language,
+                path: None,
+            } => {
+                if self.state.get_current_request().is_some() {
+                    self.state.codegen_language = language;
+                    self.state.mode = AppMode::Codegen;
+                }
+            }
+            Command::Export {
+                language,
+                path: Some(path),
+            } => {
+                self.state.status_message = match self.state.export_code(language, &path) {
+                    Ok(()) => format!("Wrote {} to {}", language.label(), path.display()),
+                    Err(e) => format!("Failed to export: {}", e),
+                };
+            }
+            Command::SaveResponse { part, path } => self.save_response(part, &path),
+            Command::Yank(target) => self.yank(target),
+            Command::Filter(text) => {
+                self.state.update_filter(text);
+                self.state.status_message = format!(
+                    "{} of {} requests shown",
+                    self.state.filtered_indices.len(),
+                    self.state.collection.len()
+                );
+            }
+            Command::ClearHistory => {
+                self.state.history.clear();
+                self.state.history_selected_index = 0;
+                self.state.status_message = match clear_history(&self.state.collection_path).await {
+                    Ok(()) => "Cleared response history".to_string(),
+                    Err(e) => format!("Failed to clear history: {}", e),
+                };
+            }
+            Command::Reload => {
+                self.state.status_message = "Collection and env files are up to date".to_string();
+                self.state.reload_from_disk();
+            }
+            Command::Edit => self.state.open_form(),
+            Command::EditExternal => self.edit_current_request().await?,
+            Command::Quit => {
+                self.state.should_quit = true;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     async fn execute_current_request(&mut self) -> Result<()> {
         if let Some(request) = self.state.get_current_request() {
-            if self.state.is_executing {
-                return Ok(());
-            }
+            let sent = self.state.interpolator.interpolate_request(request);
+            self.send_request(sent);
+        }
+
+        Ok(())
+    }
 
-            let request = request.clone();
-            let interpolator = self.state.interpolator.clone();
-            let executor = self.executor.clone();
+    /// Sends a request whose variables are already interpolated, in the
+    /// background
+    fn send_request(&mut self, sent: Request) {
+        if self.state.is_executing {
+            return;
+        }
 
-            // Get the event sender from the event handler
-            let tx = self.event_handler.get_sender();
+        let executor = self.executor.clone();
 
-            // Execute in background
-            tokio::spawn(async move {
-                let _ = tx.send(AppEvent::ExecutionStarted);
+        // Get the event sender from the event handler
+        let tx = self.event_handler.get_sender();
 
-                match executor
-                    .execute_with_interpolator(&request, &interpolator)
-                    .await
-                {
-                    Ok(response) => {
-                        let _ = tx.send(AppEvent::ExecutionCompleted(response));
-                    }
-                    Err(e) => {
-                        let _ = tx.send(AppEvent::ExecutionFailed(e.to_string()));
-                    }
+        // Execute in background
+        tokio::spawn(async move {
+            let _ = tx.send(AppEvent::ExecutionStarted);
+
+            match executor.send(&sent).await {
+                Ok(response) => {
+                    let _ = tx.send(AppEvent::ExecutionCompleted(
+                        Box::new(response),
+                        Box::new(sent),
+                    ));
+                }
+                Err(e) => {
+                    let _ = tx.send(AppEvent::ExecutionFailed(e.to_string()));
                 }
-            });
+            }
+        });
+
+        // Set executing state immediately
+        self.state.is_executing = true;
+        self.state.status_message = "Starting request execution...".to_string();
+    }
 
-            // Set executing state immediately
-            self.state.is_executing = true;
-            self.state.status_message = "Starting request execution...".to_string();
+    /// Sends the request of the selected history entry again, with the
+    /// values it was sent with rather than the current variables. History
+    /// keeps no secrets, so those are interpolated again from the request
+    /// in the collection.
+    fn rerun_history_entry(&mut self) {
+        let Some(entry) = self.state.selected_history_entry() else {
+            return;
+        };
+        let Some(mut sent) = entry.request.clone() else {
+            self.state.status_message =
+                "The entry is older than netbook keeping the sent request".to_string();
+            return;
+        };
+        if let Some(request) = self.state.collection.iter().find(|r| entry.is_for(r)) {
+            let current = self.state.interpolator.interpolate_request(request);
+            crate::core::restore_secrets(&mut sent, &current);
         }
+        self.state.mode = AppMode::Normal;
+        self.send_request(sent);
+    }
+
+    /// Runs `command` (`$EDITOR` and the like, which may include
+    /// arguments) on `file` with the TUI suspended, and redraws it after
+    fn run_suspended(&mut self, command: &str, file: &Path) -> Result<std::process::ExitStatus> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No program to open {}", file.display()))?;
 
+        let mut terminal = setup_terminal()?;
+        restore_terminal(&mut terminal)?;
+        let status = std::process::Command::new(program)
+            .args(words)
+            .arg(file)
+            .status();
+        setup_terminal()?;
+        self.redraw = true;
+
+        status.map_err(|e| color_eyre::eyre::eyre!("Could not run {}: {}", program, e))
+    }
+
+    /// Shows the response body in `$PAGER` (`less` if unset) or `$EDITOR`
+    fn open_response_externally(&mut self, editor: bool) -> Result<()> {
+        let Some(response) = &self.state.current_response else {
+            self.state.status_message = "No response yet".to_string();
+            return Ok(());
+        };
+        let temp_file = std::env::temp_dir().join(format!(
+            "netbook_response_{}.{}",
+            response.id,
+            self.state.response_format.extension()
+        ));
+        std::fs::write(
+            &temp_file,
+            self.state.response_part_text(ResponsePart::Body)?,
+        )?;
+
+        let command = if editor {
+            std::env::var("EDITOR")
+                .or_else(|_| std::env::var("VISUAL"))
+                .unwrap_or_else(|_| "vi".to_string())
+        } else {
+            std::env::var("PAGER").unwrap_or_else(|_| "less".to_string())
+        };
+        match self.run_suspended(&command, &temp_file) {
+            Ok(status) if !status.success() => {
+                self.state.status_message = format!("{} exited with {}", command, status);
+            }
+            Ok(_) => {}
+            Err(e) => self.state.status_message = e.to_string(),
+        }
+
+        let _ = std::fs::remove_file(&temp_file);
         Ok(())
     }
 
     async fn edit_current_request(&mut self) -> Result<()> {
-        use std::process::Command;
-
         if let Some(request) = self.state.get_current_request() {
+            let request_id = request.id;
             let request_name = request.name.clone();
 
-            // Save current terminal state
-            let mut terminal = crate::ui::setup_terminal()?;
-            crate::ui::restore_terminal(&mut terminal)?;
-
             // Create temporary file with the request
             let temp_dir = std::env::temp_dir();
             let temp_file =
@@ -290,50 +1026,54 @@ impl TuiApp {
                 .unwrap_or_else(|_| "vi".to_string());
 
             // Open editor (blocking)
-            let status = Command::new(&editor).arg(&temp_file).status()?;
-
-            if status.success() {
-                // Read back the edited content
-                if let Ok(edited_content) = std::fs::read_to_string(&temp_file) {
-                    if let Ok(edited_request) =
-                        serde_json::from_str::<crate::core::Request>(&edited_content)
-                    {
-                        // Update the collection
-                        if let Some(req) = self
-                            .state
-                            .collection
-                            .iter_mut()
-                            .find(|r| r.name == request_name)
+            match self.run_suspended(&editor, &temp_file) {
+                Ok(status) if status.success() => {
+                    // Read back the edited content
+                    if let Ok(edited_content) = std::fs::read_to_string(&temp_file) {
+                        if let Ok(mut edited_request) =
+                            serde_json::from_str::<crate::core::Request>(&edited_content)
                         {
-                            *req = edited_request;
-                        }
+                            let edited_request_name = edited_request.name.clone();
+                            // Update the collection
+                            if let Some(req) = self
+                                .state
+                                .collection
+                                .iter_mut()
+                                .find(|r| r.id == request_id)
+                            {
+                                // The id is what links history to the request, so
+                                // keep it even if it was changed in the editor
+                                edited_request.id = req.id;
+                                edited_request.source = req.source.take();
+                                *req = edited_request;
+                            }
 
-                        // Save the updated collection
-                        if let Err(e) = crate::io::save_collection(
-                            &self.state.collection,
-                            &self.state.collection_path,
-                        ) {
-                            self.state.status_message = format!("Failed to save: {}", e);
+                            // Save the updated collection
+                            if let Err(e) = crate::io::save_collection(
+                                &self.state.collection,
+                                &self.state.collection_path,
+                            ) {
+                                self.state.status_message = format!("Failed to save: {}", e);
+                            } else {
+                                self.state.status_message =
+                                    format!("✓ Updated request '{}'", edited_request_name);
+                            }
                         } else {
                             self.state.status_message =
-                                format!("✓ Updated request '{}'", request_name);
+                                "Error: Invalid JSON in edited file".to_string();
                         }
                     } else {
-                        self.state.status_message =
-                            "Error: Invalid JSON in edited file".to_string();
+                        self.state.status_message = "Error: Could not read edited file".to_string();
                     }
-                } else {
-                    self.state.status_message = "Error: Could not read edited file".to_string();
                 }
-            } else {
-                self.state.status_message = "Editor exited with error".to_string();
+                Ok(_) => {
+                    self.state.status_message = "Editor exited with error".to_string();
+                }
+                Err(e) => self.state.status_message = e.to_string(),
             }
 
             // Clean up temp file
             let _ = std::fs::remove_file(&temp_file);
-
-            // Restore terminal for TUI
-            let _terminal = crate::ui::setup_terminal()?;
         } else {
             self.state.status_message = "No request selected".to_string();
         }
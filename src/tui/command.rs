@@ -0,0 +1,355 @@
+use crate::core::CodeLanguage;
+use crate::tui::ResponsePart;
+use clap::ValueEnum;
+use color_eyre::{Result, eyre::eyre};
+use std::path::PathBuf;
+
+/// A command typed on the `:` command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Runs the named request, or the selected one
+    Run(Option<String>),
+    /// Switches to `.netbook/.env.<name>`, or back to the base env files
+    Env(Option<String>),
+    /// Sets a session variable
+    Set {
+        key: String,
+        value: String,
+    },
+    /// Writes the request as code to a file, or shows it
+    Export {
+        language: CodeLanguage,
+        path: Option<PathBuf>,
+    },
+    /// Writes the response, or part of it, to a file
+    SaveResponse {
+        part: ResponsePart,
+        path: PathBuf,
+    },
+    /// Copies to the clipboard
+    Yank(YankTarget),
+    /// Filters the request list; an empty filter clears it
+    Filter(String),
+    ClearHistory,
+    Reload,
+    Edit,
+    EditExternal,
+    Quit,
+}
+
+/// What `:yank` and the copy keys put on the clipboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YankTarget {
+    /// The response body, JSON pretty-printed
+    Body,
+    /// The node selected in the JSON tree
+    Value,
+    /// All response headers, one per line
+    Headers,
+    /// One response header's value, by case-insensitive name
+    Header(String),
+    /// The request that produced the response, with secrets redacted
+    Request,
+    /// The selected request as code
+    Code(CodeLanguage),
+}
+
+const YANK_TARGETS: &[&str] = &["body", "value", "headers", "header", "request", "curl"];
+
+/// Command names with their arguments and what they do, in the order
+/// they are completed
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+    ("run", "[request]", "Run the selected or named request"),
+    (
+        "env",
+        "[name]",
+        "Switch environment; no name for the base env files",
+    ),
+    ("set", "<name>=<value>", "Set a session variable"),
+    (
+        "export",
+        "<language> [file]",
+        "Show the request as code, or write it to a file",
+    ),
+    (
+        "save-response",
+        "[body|headers|full] <file>",
+        "Write the response, or part of it, to a file",
+    ),
+    (
+        "yank",
+        "body|value|headers|header <name>|request|curl",
+        "Copy to the clipboard",
+    ),
+    (
+        "filter",
+        "[text]",
+        "Filter the request list; no text clears it",
+    ),
+    ("clear-history", "", "Delete all response history"),
+    ("reload", "", "Re-read the collection and env files"),
+    ("edit", "", "Edit the request in the inline editor"),
+    ("editor", "", "Edit the request as JSON in $EDITOR"),
+    ("quit", "", "Quit"),
+];
+
+impl Command {
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim().trim_start_matches(':').trim_start();
+        let (name, args) = input
+            .split_once(char::is_whitespace)
+            .map_or((input, ""), |(name, args)| (name, args.trim()));
+        let arg = Some(args).filter(|a| !a.is_empty()).map(str::to_string);
+
+        let usage = || {
+            let (name, args, _) = COMMANDS.iter().find(|(n, ..)| *n == name).unwrap();
+            eyre!("usage: :{} {}", name, args)
+        };
+        let no_args = |command: Command| match arg {
+            Some(_) => Err(eyre!(":{} takes no arguments", name)),
+            None => Ok(command),
+        };
+
+        match name {
+            "run" => Ok(Command::Run(arg)),
+            "env" => Ok(Command::Env(arg)),
+            "set" => {
+                let (key, value) = args.split_once('=').ok_or_else(usage)?;
+                let key = key.trim();
+                if key.is_empty() {
+                    return Err(usage());
+                }
+                Ok(Command::Set {
+                    key: key.to_string(),
+                    value: value.trim().to_string(),
+                })
+            }
+            "export" => {
+                let (language, path) = args
+                    .split_once(char::is_whitespace)
+                    .map_or((args, ""), |(l, p)| (l, p.trim()));
+                if language.is_empty() {
+                    return Err(usage());
+                }
+                let language = CodeLanguage::from_str(language, true).map_err(|_| {
+                    eyre!(
+                        "unknown language '{}' (expected one of: {})",
+                        language,
+                        language_names().join(", ")
+                    )
+                })?;
+                Ok(Command::Export {
+                    language,
+                    path: Some(path).filter(|p| !p.is_empty()).map(PathBuf::from),
+                })
+            }
+            "save-response" => {
+                let (part, path) = match args.split_once(char::is_whitespace) {
+                    Some(("body", path)) => (ResponsePart::Body, path.trim()),
+                    Some(("headers", path)) => (ResponsePart::Headers, path.trim()),
+                    Some(("full", path)) => (ResponsePart::Full, path.trim()),
+                    _ => (ResponsePart::Body, args),
+                };
+                if path.is_empty() {
+                    return Err(usage());
+                }
+                Ok(Command::SaveResponse {
+                    part,
+                    path: path.into(),
+                })
+            }
+            "yank" => {
+                let (target, name) = args
+                    .split_once(char::is_whitespace)
+                    .map_or((args, ""), |(t, n)| (t, n.trim()));
+                let target = match (target, name) {
+                    ("body", "") => YankTarget::Body,
+                    ("value", "") => YankTarget::Value,
+                    ("headers", "") => YankTarget::Headers,
+                    ("header", name) if !name.is_empty() => YankTarget::Header(name.to_string()),
+                    ("request", "") => YankTarget::Request,
+                    ("curl", "") => YankTarget::Code(CodeLanguage::Curl),
+                    _ => return Err(usage()),
+                };
+                Ok(Command::Yank(target))
+            }
+            "filter" => Ok(Command::Filter(args.to_string())),
+            "clear-history" => no_args(Command::ClearHistory),
+            "reload" => no_args(Command::Reload),
+            "edit" | "e" => no_args(Command::Edit),
+            "editor" | "E" => no_args(Command::EditExternal),
+            "quit" | "q" => no_args(Command::Quit),
+            "" => Err(eyre!("type a command, e.g. :run")),
+            name => Err(eyre!("unknown command ':{}'", name)),
+        }
+    }
+}
+
+fn language_names() -> Vec<String> {
+    CodeLanguage::value_variants()
+        .iter()
+        .filter_map(|l| l.to_possible_value())
+        .map(|v| v.get_name().to_string())
+        .collect()
+}
+
+/// Values offered when completing a command's argument
+#[derive(Debug, Default)]
+pub struct CompletionSource {
+    pub requests: Vec<String>,
+    pub environments: Vec<String>,
+    /// Header names of the current response
+    pub headers: Vec<String>,
+}
+
+/// Candidates for the command name, or for the argument once the name is
+/// complete. Each candidate is the whole command line it would produce.
+pub fn complete(input: &str, source: &CompletionSource) -> Vec<String> {
+    let Some((name, arg)) = input.split_once(' ') else {
+        return COMMANDS
+            .iter()
+            .map(|(name, ..)| *name)
+            .filter(|name| name.starts_with(input))
+            .map(str::to_string)
+            .collect();
+    };
+
+    let values: Vec<String> = match name {
+        "run" => source.requests.clone(),
+        "env" => source.environments.clone(),
+        // Only the language; the file name is free text
+        "export" if !arg.contains(' ') => language_names(),
+        "save-response" if !arg.contains(' ') => ["body", "headers", "full"]
+            .iter()
+            .map(|part| part.to_string())
+            .collect(),
+        "yank" => match arg.split_once(' ') {
+            None => YANK_TARGETS.iter().map(|t| t.to_string()).collect(),
+            Some(("header", _)) => source
+                .headers
+                .iter()
+                .map(|h| format!("header {}", h))
+                .collect(),
+            Some(_) => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    let arg_lower = arg.to_lowercase();
+    values
+        .into_iter()
+        .filter(|value| value.to_lowercase().starts_with(&arg_lower))
+        .map(|value| format!("{} {}", name, value))
+        .collect()
+}
+
+/// Longest prefix shared by all `candidates`
+pub fn common_prefix(candidates: &[String]) -> String {
+    let Some(first) = candidates.first() else {
+        return String::new();
+    };
+    let mut prefix = first.as_str();
+    for candidate in &candidates[1..] {
+        let length = prefix
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(prefix.len().min(candidate.len()), |((i, _), _)| i);
+        prefix = &prefix[..length];
+    }
+    prefix.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            Command::parse(":run Get Users").unwrap(),
+            Command::Run(Some("Get Users".to_string()))
+        );
+        assert_eq!(Command::parse("env").unwrap(), Command::Env(None));
+        assert_eq!(
+            Command::parse("set token = abc=def").unwrap(),
+            Command::Set {
+                key: "token".to_string(),
+                value: "abc=def".to_string()
+            }
+        );
+        assert_eq!(
+            Command::parse("export python out.py").unwrap(),
+            Command::Export {
+                language: CodeLanguage::Python,
+                path: Some(PathBuf::from("out.py"))
+            }
+        );
+        assert_eq!(
+            Command::parse("save-response body.json").unwrap(),
+            Command::SaveResponse {
+                part: ResponsePart::Body,
+                path: PathBuf::from("body.json")
+            }
+        );
+        assert_eq!(
+            Command::parse("save-response full out/last.json").unwrap(),
+            Command::SaveResponse {
+                part: ResponsePart::Full,
+                path: PathBuf::from("out/last.json")
+            }
+        );
+        assert_eq!(
+            Command::parse("filter").unwrap(),
+            Command::Filter(String::new())
+        );
+        assert_eq!(
+            Command::parse("yank header content-type").unwrap(),
+            Command::Yank(YankTarget::Header("content-type".to_string()))
+        );
+        assert_eq!(
+            Command::parse("yank curl").unwrap(),
+            Command::Yank(YankTarget::Code(CodeLanguage::Curl))
+        );
+        assert_eq!(Command::parse("q").unwrap(), Command::Quit);
+
+        let error = |input: &str| Command::parse(input).unwrap_err().to_string();
+        assert_eq!(error("frobnicate"), "unknown command ':frobnicate'");
+        assert_eq!(error("set token"), "usage: :set <name>=<value>");
+        assert_eq!(
+            error("save-response"),
+            "usage: :save-response [body|headers|full] <file>"
+        );
+        assert!(error("export cobol").starts_with("unknown language 'cobol'"));
+        assert!(error("yank header").starts_with("usage: :yank"));
+        assert_eq!(error("reload now"), ":reload takes no arguments");
+    }
+
+    #[test]
+    fn test_complete() {
+        let source = CompletionSource {
+            requests: vec!["Get Users".to_string(), "Get Posts".to_string()],
+            environments: vec!["staging".to_string(), "prod".to_string()],
+            headers: vec!["Content-Type".to_string(), "ETag".to_string()],
+        };
+        assert_eq!(complete("re", &source), ["reload"]);
+        assert_eq!(complete("e", &source), ["env", "export", "edit", "editor"]);
+        assert_eq!(complete("env st", &source), ["env staging"]);
+        assert_eq!(
+            complete("run get", &source),
+            ["run Get Users", "run Get Posts"]
+        );
+        assert_eq!(complete("export py", &source), ["export python"]);
+        assert!(complete("export python out", &source).is_empty());
+        assert_eq!(
+            complete("yank head", &source),
+            ["yank headers", "yank header"]
+        );
+        assert_eq!(
+            complete("yank header content", &source),
+            ["yank header Content-Type"]
+        );
+
+        assert_eq!(common_prefix(&complete("run get", &source)), "run Get ");
+        assert_eq!(common_prefix(&complete("e", &source)), "e");
+    }
+}
@@ -1,13 +1,16 @@
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 use std::time::Duration;
 use tokio::sync::mpsc;
 
 #[derive(Debug, Clone)]
 pub enum AppEvent {
     Key(KeyEvent),
+    Mouse(MouseEvent),
     ExecutionStarted,
-    ExecutionCompleted(crate::core::Response),
+    /// The response, and the request as it was sent
+    ExecutionCompleted(Box<crate::core::Response>, Box<crate::core::Request>),
     ExecutionFailed(String),
+    FilesChanged,
     Quit,
 }
 
@@ -31,10 +34,20 @@ impl EventHandler {
             loop {
                 if event::poll(Duration::from_millis(100)).unwrap_or(false)
                     && let Ok(event) = event::read()
-                    && let Event::Key(key_event) = event
-                    && event_tx.send(AppEvent::Key(key_event)).is_err()
                 {
-                    break;
+                    let event = match event {
+                        Event::Key(key_event) => Some(AppEvent::Key(key_event)),
+                        // Plain movement would redraw on every cell the pointer crosses
+                        Event::Mouse(mouse_event) if mouse_event.kind != MouseEventKind::Moved => {
+                            Some(AppEvent::Mouse(mouse_event))
+                        }
+                        _ => None,
+                    };
+                    if let Some(event) = event
+                        && event_tx.send(event).is_err()
+                    {
+                        break;
+                    }
                 }
                 tokio::time::sleep(Duration::from_millis(50)).await;
             }
@@ -0,0 +1,333 @@
+use crate::core::{HttpMethod, Request, RequestBody};
+use std::collections::HashMap;
+
+const METHODS: [HttpMethod; 7] = [
+    HttpMethod::Get,
+    HttpMethod::Post,
+    HttpMethod::Put,
+    HttpMethod::Patch,
+    HttpMethod::Delete,
+    HttpMethod::Head,
+    HttpMethod::Options,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormField {
+    Method,
+    Url,
+    Headers,
+    Query,
+    Body,
+}
+
+impl FormField {
+    pub fn next(self) -> Self {
+        match self {
+            FormField::Method => FormField::Url,
+            FormField::Url => FormField::Headers,
+            FormField::Headers => FormField::Query,
+            FormField::Query => FormField::Body,
+            FormField::Body => FormField::Method,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            FormField::Method => FormField::Body,
+            FormField::Url => FormField::Method,
+            FormField::Headers => FormField::Url,
+            FormField::Query => FormField::Headers,
+            FormField::Body => FormField::Query,
+        }
+    }
+}
+
+/// A header or query parameter row
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormRow {
+    pub key: String,
+    pub value: String,
+    pub enabled: bool,
+}
+
+/// In-TUI editing copy of a request; changes only reach the collection when
+/// the form is saved
+#[derive(Debug, Clone)]
+pub struct RequestForm {
+    pub request: Request,
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<FormRow>,
+    pub query: Vec<FormRow>,
+    pub body: String,
+    pub focus: FormField,
+    /// Selected row of the focused table
+    pub row: usize,
+    /// Whether the value (rather than the key) of the row is selected
+    pub value_column: bool,
+    pub dirty: bool,
+}
+
+impl RequestForm {
+    pub fn new(request: &Request) -> Self {
+        Self {
+            request: request.clone(),
+            method: request.method.clone(),
+            url: request.url.clone(),
+            headers: rows(&request.headers, &request.disabled_headers),
+            query: rows(&request.query, &request.disabled_query),
+            body: body_text(&request.body),
+            focus: FormField::Method,
+            row: 0,
+            value_column: false,
+            dirty: false,
+        }
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = self.focus.next();
+        self.row = 0;
+        self.value_column = false;
+    }
+
+    pub fn focus_previous(&mut self) {
+        self.focus = self.focus.previous();
+        self.row = 0;
+        self.value_column = false;
+    }
+
+    pub fn cycle_method(&mut self, forward: bool) {
+        let position = METHODS.iter().position(|m| *m == self.method).unwrap_or(0);
+        let next = if forward {
+            (position + 1) % METHODS.len()
+        } else {
+            (position + METHODS.len() - 1) % METHODS.len()
+        };
+        self.method = METHODS[next].clone();
+        self.dirty = true;
+    }
+
+    /// Rows of the focused table, if a table is focused
+    pub fn rows(&self) -> Option<&Vec<FormRow>> {
+        match self.focus {
+            FormField::Headers => Some(&self.headers),
+            FormField::Query => Some(&self.query),
+            _ => None,
+        }
+    }
+
+    fn rows_mut(&mut self) -> Option<&mut Vec<FormRow>> {
+        match self.focus {
+            FormField::Headers => Some(&mut self.headers),
+            FormField::Query => Some(&mut self.query),
+            _ => None,
+        }
+    }
+
+    pub fn move_row(&mut self, down: bool) {
+        let len = self.rows().map_or(0, Vec::len);
+        if down && self.row + 1 < len {
+            self.row += 1;
+        } else if !down && self.row > 0 {
+            self.row -= 1;
+        }
+    }
+
+    /// Appends an empty, enabled row and selects its key
+    pub fn add_row(&mut self) {
+        if let Some(rows) = self.rows_mut() {
+            rows.push(FormRow {
+                enabled: true,
+                ..Default::default()
+            });
+            self.row = rows.len() - 1;
+            self.value_column = false;
+            self.dirty = true;
+        }
+    }
+
+    pub fn remove_row(&mut self) {
+        let row = self.row;
+        if let Some(rows) = self.rows_mut()
+            && row < rows.len()
+        {
+            rows.remove(row);
+            self.row = row.min(rows.len().saturating_sub(1));
+            self.dirty = true;
+        }
+    }
+
+    pub fn toggle_row(&mut self) {
+        let row = self.row;
+        if let Some(entry) = self.rows_mut().and_then(|rows| rows.get_mut(row)) {
+            entry.enabled = !entry.enabled;
+            self.dirty = true;
+        }
+    }
+
+    /// Text the cursor is in, for fields that can be typed into
+    pub fn active_text(&mut self) -> Option<&mut String> {
+        let (row, value_column) = (self.row, self.value_column);
+        match self.focus {
+            FormField::Method => None,
+            FormField::Url => Some(&mut self.url),
+            FormField::Body => Some(&mut self.body),
+            FormField::Headers | FormField::Query => {
+                let entry = self.rows_mut()?.get_mut(row)?;
+                Some(if value_column {
+                    &mut entry.value
+                } else {
+                    &mut entry.key
+                })
+            }
+        }
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if let Some(text) = self.active_text() {
+            text.push(c);
+            self.dirty = true;
+        }
+    }
+
+    pub fn delete_char(&mut self) {
+        if let Some(text) = self.active_text()
+            && text.pop().is_some()
+        {
+            self.dirty = true;
+        }
+    }
+
+    /// The edited request. Rows without a key are dropped; a body that parses
+    /// as JSON is stored as JSON.
+    pub fn to_request(&self) -> Request {
+        let mut request = self.request.clone();
+        request.method = self.method.clone();
+        request.url = self.url.trim().to_string();
+        (request.headers, request.disabled_headers) = split_rows(&self.headers);
+        (request.query, request.disabled_query) = split_rows(&self.query);
+
+        let body = self.body.trim();
+        request.body = if self.body == body_text(&self.request.body) {
+            self.request.body.clone()
+        } else if body.is_empty() {
+            None
+        } else if let Ok(json) = serde_json::from_str(body) {
+            Some(RequestBody::Json(json))
+        } else {
+            Some(RequestBody::Text(self.body.clone()))
+        };
+        request
+    }
+}
+
+fn body_text(body: &Option<RequestBody>) -> String {
+    match body {
+        Some(RequestBody::Json(json)) => {
+            serde_json::to_string_pretty(json).unwrap_or_else(|_| json.to_string())
+        }
+        Some(RequestBody::Text(text)) => text.clone(),
+        None => String::new(),
+    }
+}
+
+fn rows(enabled: &HashMap<String, String>, disabled: &HashMap<String, String>) -> Vec<FormRow> {
+    let mut rows: Vec<FormRow> = enabled
+        .iter()
+        .map(|(k, v)| (k, v, true))
+        .chain(disabled.iter().map(|(k, v)| (k, v, false)))
+        .map(|(key, value, enabled)| FormRow {
+            key: key.clone(),
+            value: value.clone(),
+            enabled,
+        })
+        .collect();
+    rows.sort_by(|a, b| a.key.cmp(&b.key));
+    rows
+}
+
+fn split_rows(rows: &[FormRow]) -> (HashMap<String, String>, HashMap<String, String>) {
+    let mut enabled = HashMap::new();
+    let mut disabled = HashMap::new();
+    for row in rows {
+        let key = row.key.trim();
+        if key.is_empty() {
+            continue;
+        }
+        let target = if row.enabled {
+            &mut enabled
+        } else {
+            &mut disabled
+        };
+        target.insert(key.to_string(), row.value.clone());
+    }
+    (enabled, disabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> Request {
+        serde_json::from_value(serde_json::json!({
+            "name": "Create Post",
+            "method": "POST",
+            "url": "https://example.com/posts",
+            "headers": {"Accept": "application/json"},
+            "disabled_query": {"debug": "1"},
+            "body": {"title": "hello"}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_form_round_trip() {
+        let request = request();
+        let form = RequestForm::new(&request);
+
+        assert_eq!(form.query[0].key, "debug");
+        assert!(!form.query[0].enabled);
+        assert!(!form.dirty);
+
+        let edited = form.to_request();
+        assert_eq!(
+            serde_json::to_value(&edited).unwrap(),
+            serde_json::to_value(&request).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_form_edits() {
+        let mut form = RequestForm::new(&request());
+
+        form.cycle_method(false);
+        assert_eq!(form.method, HttpMethod::Get);
+
+        form.focus = FormField::Headers;
+        form.toggle_row();
+        form.add_row();
+        for c in "X-Trace".chars() {
+            form.insert_char(c);
+        }
+        form.value_column = true;
+        form.insert_char('1');
+
+        form.focus = FormField::Query;
+        form.row = 0;
+        form.toggle_row();
+
+        form.focus = FormField::Body;
+        form.body = "plain text".to_string();
+
+        let edited = form.to_request();
+        assert!(form.dirty);
+        assert_eq!(
+            edited.headers,
+            HashMap::from([("X-Trace".to_string(), "1".to_string())])
+        );
+        assert_eq!(edited.disabled_headers["Accept"], "application/json");
+        assert_eq!(edited.query["debug"], "1");
+        assert!(edited.disabled_query.is_empty());
+        assert!(matches!(edited.body, Some(RequestBody::Text(ref t)) if t == "plain text"));
+    }
+}
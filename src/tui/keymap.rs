@@ -0,0 +1,761 @@
+use crate::tui::AppMode;
+use color_eyre::{Result, eyre::eyre};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+/// Something a key can be bound to. Which actions exist depends on the mode;
+/// see `Keymap::defaults`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Help,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    NextTab,
+    PreviousTab,
+    Back,
+    Run,
+    Filter,
+    Variables,
+    History,
+    Command,
+    Edit,
+    EditExternal,
+    Codegen,
+    NewRequest,
+    Duplicate,
+    Rename,
+    Delete,
+    MoveRequestUp,
+    MoveRequestDown,
+    FocusResponse,
+    Search,
+    NextMatch,
+    PreviousMatch,
+    Query,
+    Toggle,
+    ExpandAll,
+    CollapseAll,
+    SaveValue,
+    Extract,
+    Yank,
+    YankBody,
+    SaveResponse,
+    OpenPager,
+    OpenEditor,
+    Mark,
+    Diff,
+    Details,
+    Add,
+    Select,
+    Save,
+    Discard,
+    NextField,
+    PreviousField,
+    AddRow,
+    RemoveRow,
+    Confirm,
+    Cancel,
+}
+
+impl Action {
+    /// Name used in the `keymap` section of the config
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Help => "help",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::NextTab => "next_tab",
+            Action::PreviousTab => "previous_tab",
+            Action::Back => "back",
+            Action::Run => "run",
+            Action::Filter => "filter",
+            Action::Variables => "variables",
+            Action::History => "history",
+            Action::Command => "command",
+            Action::Edit => "edit",
+            Action::EditExternal => "edit_external",
+            Action::Codegen => "codegen",
+            Action::NewRequest => "new_request",
+            Action::Duplicate => "duplicate",
+            Action::Rename => "rename",
+            Action::Delete => "delete",
+            Action::MoveRequestUp => "move_request_up",
+            Action::MoveRequestDown => "move_request_down",
+            Action::FocusResponse => "focus_response",
+            Action::Search => "search",
+            Action::NextMatch => "next_match",
+            Action::PreviousMatch => "previous_match",
+            Action::Query => "query",
+            Action::Toggle => "toggle",
+            Action::ExpandAll => "expand_all",
+            Action::CollapseAll => "collapse_all",
+            Action::SaveValue => "save_value",
+            Action::Extract => "extract",
+            Action::Yank => "yank",
+            Action::YankBody => "yank_body",
+            Action::SaveResponse => "save_response",
+            Action::OpenPager => "open_pager",
+            Action::OpenEditor => "open_editor",
+            Action::Mark => "mark",
+            Action::Diff => "diff",
+            Action::Details => "details",
+            Action::Add => "add",
+            Action::Select => "select",
+            Action::Save => "save",
+            Action::Discard => "discard",
+            Action::NextField => "next_field",
+            Action::PreviousField => "previous_field",
+            Action::AddRow => "add_row",
+            Action::RemoveRow => "remove_row",
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+        }
+    }
+
+    /// What the action does in `mode`, for the help overlay
+    pub fn description(self, mode: &AppMode) -> &'static str {
+        match (self, mode) {
+            (Action::Quit, _) => "Quit",
+            (Action::Help, _) => "Show these keys",
+            (Action::Up, AppMode::Normal) => "Previous request",
+            (Action::Down, AppMode::Normal) => "Next request",
+            (Action::Up, AppMode::Response) => "Previous node or line",
+            (Action::Down, AppMode::Response) => "Next node or line",
+            (Action::Up, AppMode::Form) => "Previous header/param row",
+            (Action::Down, AppMode::Form) => "Next header/param row",
+            (Action::Up, AppMode::Diff | AppMode::HistoryDetail) => "Scroll up",
+            (Action::Down, AppMode::Diff | AppMode::HistoryDetail) => "Scroll down",
+            (Action::Up, _) => "Previous entry",
+            (Action::Down, _) => "Next entry",
+            (Action::Left, AppMode::Codegen) => "Previous language",
+            (Action::Right, AppMode::Codegen) => "Next language",
+            (Action::Left, _) => "Previous method, or the row's key",
+            (Action::Right, _) => "Next method, or the row's value",
+            (Action::PageUp, AppMode::Normal) => "Scroll the response up",
+            (Action::PageDown, AppMode::Normal) => "Scroll the response down",
+            (Action::PageUp, _) => "Page up",
+            (Action::PageDown, _) => "Page down",
+            (Action::Top, _) => "Jump to the top",
+            (Action::Bottom, _) => "Jump to the bottom",
+            (Action::NextTab, _) => "Next response tab",
+            (Action::PreviousTab, _) => "Previous response tab",
+            (Action::Back, _) => "Back",
+            (Action::Run, AppMode::History | AppMode::HistoryDetail) => {
+                "Send the request again, with the values it was sent with"
+            }
+            (Action::Run, _) => "Run the selected request",
+            (Action::Filter, AppMode::History) => "Filter by request name, status class or date",
+            (Action::Filter, _) => "Filter requests",
+            (Action::Variables, _) => "Variables",
+            (Action::History, _) => "Response history",
+            (Action::Command, _) => "Command line",
+            (Action::Edit, AppMode::Variables) => "Change the selected variable",
+            (Action::Edit, AppMode::Form) => "Edit the selected field",
+            (Action::Edit, _) => "Edit the request",
+            (Action::EditExternal, _) => "Edit the request as JSON in $EDITOR",
+            (Action::Codegen, _) => "Copy as code",
+            (Action::NewRequest, _) => "New request",
+            (Action::Duplicate, _) => "Duplicate the request",
+            (Action::Rename, _) => "Rename the request",
+            (Action::Delete, AppMode::Variables) => "Delete a session or saved variable",
+            (Action::Delete, AppMode::History) => "Delete the entry",
+            (Action::Delete, _) => "Delete the request",
+            (Action::MoveRequestUp, _) => "Move the request up",
+            (Action::MoveRequestDown, _) => "Move the request down",
+            (Action::FocusResponse, _) => "Focus the response pane",
+            (Action::Search, _) => "Search the response",
+            (Action::NextMatch, _) => "Next match",
+            (Action::PreviousMatch, _) => "Previous match",
+            (Action::Query, _) => "Filter the body with JSONPath or jq",
+            (Action::Toggle, AppMode::Form) => "Enable/disable the row, or next method",
+            (Action::Toggle, _) => "Expand or collapse the node",
+            (Action::ExpandAll, _) => "Expand everything",
+            (Action::CollapseAll, _) => "Collapse everything",
+            (Action::SaveValue, _) => "Save the value to a session variable",
+            (Action::Extract, _) => "Extract the node on every run",
+            (Action::Yank, AppMode::Normal) => "Copy the request as curl",
+            (Action::Yank, AppMode::Codegen) => "Copy the code",
+            (Action::Yank, _) => "Copy the selected value, or the body or headers",
+            (Action::YankBody, _) => "Copy the response body",
+            (Action::SaveResponse, _) => "Write the body, headers or full response to a file",
+            (Action::OpenPager, _) => "Open the body in $PAGER",
+            (Action::OpenEditor, _) => "Open the body in $EDITOR",
+            (Action::Mark, _) => "Mark the entry to compare with",
+            (Action::Diff, _) => "Compare with the marked entry, or the current response",
+            (Action::Details, _) => "Show the request as it was sent, and the response",
+            (Action::Add, _) => "Add a variable",
+            (Action::Select, _) => "Show the response",
+            (Action::Save, _) => "Save to the collection",
+            (Action::Discard, _) => "Discard changes",
+            (Action::NextField, _) => "Next field",
+            (Action::PreviousField, _) => "Previous field",
+            (Action::AddRow, _) => "Add a header or query row",
+            (Action::RemoveRow, _) => "Remove the row",
+            (Action::Confirm, _) => "Delete",
+            (Action::Cancel, _) => "Cancel",
+        }
+    }
+
+    /// Short label in the status bar hints; actions without one are only
+    /// listed in the help overlay
+    fn hint(self, mode: &AppMode) -> Option<&'static str> {
+        Some(match (self, mode) {
+            (Action::Run, AppMode::History | AppMode::HistoryDetail) => "re-run",
+            (Action::Run, _) => "run",
+            (Action::Edit, AppMode::Normal) => "edit",
+            (Action::EditExternal, _) => "$EDITOR",
+            (Action::NewRequest, _) => "new",
+            (Action::FocusResponse, _) => "response",
+            (Action::Codegen, _) => "code",
+            (Action::Variables, _) => "variables",
+            (Action::History, _) => "history",
+            (Action::Filter, _) => "filter",
+            (Action::Help, _) => "help",
+            (Action::Quit, _) => "quit",
+            (Action::Toggle, AppMode::Response) => "expand/collapse",
+            (Action::Toggle, _) => "toggle",
+            (Action::Search, _) => "search",
+            (Action::Query, _) => "query",
+            (Action::SaveValue, _) => "save to variable",
+            (Action::Extract, _) => "extract on every run",
+            (Action::Yank, AppMode::Response | AppMode::Codegen) => "copy",
+            (Action::SaveResponse, _) => "write to file",
+            (Action::Mark, _) => "mark",
+            (Action::Diff, _) => "diff",
+            (Action::Details, _) => "details",
+            (Action::NextTab, _) => "tab",
+            (Action::Back, _) => "back",
+            (Action::Discard, _) => "discard",
+            (Action::Edit, _) => "edit",
+            (Action::Add | Action::AddRow, _) => "add",
+            (Action::Delete | Action::RemoveRow, _) => "delete",
+            (Action::Select, _) => "select",
+            (Action::Right, AppMode::Codegen) => "language",
+            (Action::NextField, _) => "field",
+            (Action::Save, _) => "save",
+            (Action::Confirm, _) => "delete",
+            (Action::Cancel, _) => "cancel",
+            _ => return None,
+        })
+    }
+}
+
+/// A key with its Ctrl/Alt modifiers, parsed from strings like `q`, `G`,
+/// `ctrl+s`, `enter` or `shift+tab`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut shift = false;
+        let mut rest = text;
+        // A lone `+` or `-` is a key, not a separator
+        while let Some((modifier, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                "shift" => shift = true,
+                _ => return Err(eyre!("unknown modifier `{}` in `{}`", modifier, text)),
+            }
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "tab" if shift => KeyCode::BackTab,
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(eyre!("unknown key `{}`", text)),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    /// Shift is ignored: it is already part of the character (`G`), and
+    /// terminals don't agree on reporting it
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let relevant = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        self.code == key.code && self.modifiers == key.modifiers & relevant
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{}", c),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::Delete => write!(f, "Del"),
+            code => write!(f, "{}", code),
+        }
+    }
+}
+
+/// Keys for each mode that is driven by single keys. Modes where text is
+/// typed (filter, prompts, search) keep fixed Enter/Esc/Backspace handling.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    modes: Vec<ModeKeys>,
+}
+
+#[derive(Debug, Clone)]
+struct ModeKeys {
+    /// Section of the `keymap` config
+    name: &'static str,
+    mode: AppMode,
+    bindings: Vec<(Action, Vec<KeyBinding>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let modes = Self::defaults()
+            .iter()
+            .map(|(name, mode, actions)| {
+                let bindings = actions
+                    .iter()
+                    .map(|(action, keys)| {
+                        let keys = keys
+                            .iter()
+                            .map(|key| KeyBinding::parse(key).expect("valid default key"))
+                            .collect();
+                        (*action, keys)
+                    })
+                    .collect();
+                ModeKeys {
+                    name,
+                    mode: mode.clone(),
+                    bindings,
+                }
+            })
+            .collect();
+        Self { modes }
+    }
+}
+
+type ModeDefaults = (
+    &'static str,
+    AppMode,
+    &'static [(Action, &'static [&'static str])],
+);
+
+impl Keymap {
+    /// Config section name, mode and the default keys of its actions, in
+    /// the order the help overlay lists them
+    pub fn defaults() -> &'static [ModeDefaults] {
+        use Action::*;
+        &[
+            (
+                "normal",
+                AppMode::Normal,
+                &[
+                    (Run, &["enter"]),
+                    (Up, &["up", "k"]),
+                    (Down, &["down", "j"]),
+                    (Edit, &["e"]),
+                    (EditExternal, &["E"]),
+                    (NewRequest, &["n"]),
+                    (Duplicate, &["y"]),
+                    (Rename, &["r"]),
+                    (Delete, &["d"]),
+                    (MoveRequestUp, &["K"]),
+                    (MoveRequestDown, &["J"]),
+                    (FocusResponse, &["l", "right"]),
+                    (NextTab, &["tab"]),
+                    (PreviousTab, &["backtab"]),
+                    (PageUp, &["pgup"]),
+                    (PageDown, &["pgdn"]),
+                    (Codegen, &["c"]),
+                    (Yank, &["Y"]),
+                    (Variables, &["v"]),
+                    (History, &["h"]),
+                    (Filter, &["/"]),
+                    (Command, &[":"]),
+                    (Help, &["?"]),
+                    (Quit, &["q", "ctrl+c"]),
+                ],
+            ),
+            (
+                "response",
+                AppMode::Response,
+                &[
+                    (Up, &["up", "k"]),
+                    (Down, &["down", "j"]),
+                    (PageUp, &["pgup"]),
+                    (PageDown, &["pgdn"]),
+                    (Top, &["home", "g"]),
+                    (Bottom, &["end", "G"]),
+                    (Toggle, &["enter", "space"]),
+                    (ExpandAll, &["E"]),
+                    (CollapseAll, &["C"]),
+                    (Search, &["/"]),
+                    (NextMatch, &["n"]),
+                    (PreviousMatch, &["N"]),
+                    (Query, &["f"]),
+                    (SaveValue, &["s"]),
+                    (Extract, &["x"]),
+                    (Yank, &["y"]),
+                    (YankBody, &["Y"]),
+                    (SaveResponse, &["w"]),
+                    (OpenPager, &["o"]),
+                    (OpenEditor, &["O"]),
+                    (NextTab, &["tab"]),
+                    (PreviousTab, &["backtab"]),
+                    (Help, &["?"]),
+                    (Back, &["esc", "left", "q"]),
+                ],
+            ),
+            (
+                "variables",
+                AppMode::Variables,
+                &[
+                    (Up, &["up", "k"]),
+                    (Down, &["down", "j"]),
+                    (Edit, &["enter", "e"]),
+                    (Add, &["a"]),
+                    (Delete, &["d", "delete"]),
+                    (Help, &["?"]),
+                    (Back, &["esc", "v"]),
+                ],
+            ),
+            (
+                "history",
+                AppMode::History,
+                &[
+                    (Up, &["up", "k"]),
+                    (Down, &["down", "j"]),
+                    (Select, &["enter"]),
+                    (Details, &["i", "tab"]),
+                    (Run, &["r"]),
+                    (Filter, &["/"]),
+                    (Mark, &["m"]),
+                    (Diff, &["D"]),
+                    (Delete, &["d", "delete"]),
+                    (Help, &["?"]),
+                    (Back, &["esc", "h"]),
+                ],
+            ),
+            (
+                "history_detail",
+                AppMode::HistoryDetail,
+                &[
+                    (Up, &["up", "k"]),
+                    (Down, &["down", "j"]),
+                    (PageUp, &["pgup"]),
+                    (PageDown, &["pgdn"]),
+                    (Top, &["home", "g"]),
+                    (Bottom, &["end", "G"]),
+                    (Select, &["enter"]),
+                    (Run, &["r"]),
+                    (Help, &["?"]),
+                    (Back, &["esc", "q", "i", "tab"]),
+                ],
+            ),
+            (
+                "diff",
+                AppMode::Diff,
+                &[
+                    (Up, &["up", "k"]),
+                    (Down, &["down", "j"]),
+                    (PageUp, &["pgup"]),
+                    (PageDown, &["pgdn"]),
+                    (Top, &["home", "g"]),
+                    (Bottom, &["end", "G"]),
+                    (Help, &["?"]),
+                    (Back, &["esc", "q", "D"]),
+                ],
+            ),
+            (
+                "codegen",
+                AppMode::Codegen,
+                &[
+                    (Right, &["tab", "right", "l"]),
+                    (Left, &["backtab", "left", "h"]),
+                    (Yank, &["y", "enter"]),
+                    (Help, &["?"]),
+                    (Back, &["esc", "c"]),
+                ],
+            ),
+            (
+                "form",
+                AppMode::Form,
+                &[
+                    (NextField, &["tab"]),
+                    (PreviousField, &["backtab"]),
+                    (Up, &["up", "k"]),
+                    (Down, &["down", "j"]),
+                    (Left, &["left", "h"]),
+                    (Right, &["right", "l"]),
+                    (Edit, &["enter", "i"]),
+                    (AddRow, &["a"]),
+                    (RemoveRow, &["x", "delete"]),
+                    (Toggle, &["space"]),
+                    (Save, &["w", "ctrl+s"]),
+                    (Help, &["?"]),
+                    (Discard, &["esc"]),
+                ],
+            ),
+            (
+                "confirm_delete",
+                AppMode::ConfirmDelete,
+                &[(Confirm, &["y", "enter"]), (Cancel, &["n", "esc"])],
+            ),
+        ]
+    }
+
+    /// The defaults with the keys from the config's `keymap` section. Keys
+    /// listed for an action replace its defaults; an empty list unbinds it.
+    pub fn from_config(config: &HashMap<String, HashMap<String, Vec<String>>>) -> Result<Self> {
+        let mut keymap = Self::default();
+        let mut sections: Vec<_> = config.iter().collect();
+        sections.sort_by_key(|(name, _)| *name);
+        for (section, actions) in sections {
+            let Some(ModeKeys { bindings, .. }) =
+                keymap.modes.iter_mut().find(|keys| keys.name == section)
+            else {
+                return Err(eyre!(
+                    "keymap: unknown mode `{}` (expected one of: {})",
+                    section,
+                    Self::defaults()
+                        .iter()
+                        .map(|(name, ..)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            };
+            let mut actions: Vec<_> = actions.iter().collect();
+            actions.sort_by_key(|(name, _)| *name);
+            for (name, keys) in actions {
+                let Some((_, bound)) = bindings.iter_mut().find(|(a, _)| a.name() == name) else {
+                    return Err(eyre!("keymap: unknown action `{}` in {}", name, section));
+                };
+                *bound = keys
+                    .iter()
+                    .map(|key| KeyBinding::parse(key))
+                    .collect::<Result<_>>()
+                    .map_err(|e| eyre!("keymap: {}.{}: {}", section, name, e))?;
+            }
+        }
+        keymap.check_conflicts()?;
+        Ok(keymap)
+    }
+
+    fn check_conflicts(&self) -> Result<()> {
+        for ModeKeys { name, bindings, .. } in &self.modes {
+            let mut seen: HashMap<KeyBinding, Action> = HashMap::new();
+            for (action, keys) in bindings {
+                for key in keys {
+                    if let Some(other) = seen.insert(*key, *action)
+                        && other != *action
+                    {
+                        return Err(eyre!(
+                            "keymap: `{}` is bound to both {} and {} in {}",
+                            key,
+                            other.name(),
+                            action.name(),
+                            name
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn bindings(&self, mode: &AppMode) -> &[(Action, Vec<KeyBinding>)] {
+        self.modes
+            .iter()
+            .find(|keys| keys.mode == *mode)
+            .map_or(&[], |keys| keys.bindings.as_slice())
+    }
+
+    /// The action `key` triggers in `mode`
+    pub fn action(&self, mode: &AppMode, key: &KeyEvent) -> Option<Action> {
+        self.bindings(mode)
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|k| k.matches(key)))
+            .map(|(action, _)| *action)
+    }
+
+    /// Keys bound to `action` in `mode`, e.g. `↑/k`
+    pub fn keys(&self, mode: &AppMode, action: Action) -> String {
+        self.bindings(mode)
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, keys)| {
+                keys.iter()
+                    .map(|key| key.to_string())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or_default()
+    }
+
+    /// Status bar text for `mode`, or `None` for modes without a keymap
+    pub fn hints(&self, mode: &AppMode) -> Option<String> {
+        let bindings = self.bindings(mode);
+        if bindings.is_empty() {
+            return None;
+        }
+        let hints: Vec<String> = bindings
+            .iter()
+            .filter_map(|(action, keys)| {
+                let key = keys.first()?;
+                Some(format!("{}: {}", key, action.hint(mode)?))
+            })
+            .collect();
+        Some(hints.join(" | "))
+    }
+
+    /// Every bound action in `mode` with its keys and description, for the
+    /// help overlay
+    pub fn help(&self, mode: &AppMode) -> Vec<(String, &'static str)> {
+        self.bindings(mode)
+            .iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(action, _)| (self.keys(mode, *action), action.description(mode)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_binding() {
+        let parse = |text: &str| KeyBinding::parse(text).unwrap();
+        assert_eq!(parse("q").code, KeyCode::Char('q'));
+        assert_eq!(parse("G").code, KeyCode::Char('G'));
+        assert_eq!(parse("shift+g").code, KeyCode::Char('G'));
+        assert_eq!(parse("shift+tab").code, KeyCode::BackTab);
+        assert_eq!(parse("PgDn").code, KeyCode::PageDown);
+        assert_eq!(parse("+").code, KeyCode::Char('+'));
+        assert_eq!(parse("f5").code, KeyCode::F(5));
+        let ctrl_s = parse("Ctrl+s");
+        assert_eq!(ctrl_s.modifiers, KeyModifiers::CONTROL);
+        assert_eq!(ctrl_s.to_string(), "Ctrl+S");
+        assert!(KeyBinding::parse("hyper+x").is_err());
+        assert!(KeyBinding::parse("enterr").is_err());
+
+        // Shift arrives with capitals but isn't part of the binding
+        assert!(parse("G").matches(&key(KeyCode::Char('G'), KeyModifiers::SHIFT)));
+        assert!(!parse("s").matches(&key(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn test_default_keymap() {
+        let keymap = Keymap::default();
+        keymap.check_conflicts().unwrap();
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            keymap.action(&AppMode::Normal, &key(KeyCode::Char('h'), none)),
+            Some(Action::History)
+        );
+        assert_eq!(
+            keymap.action(
+                &AppMode::Normal,
+                &key(KeyCode::Char('c'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.action(&AppMode::Filter, &key(KeyCode::Char('h'), none)),
+            None
+        );
+        assert_eq!(keymap.keys(&AppMode::Response, Action::Top), "Home/g");
+        assert!(
+            keymap
+                .hints(&AppMode::Normal)
+                .unwrap()
+                .starts_with("Enter: run | e: edit")
+        );
+        assert!(keymap.hints(&AppMode::Filter).is_none());
+    }
+
+    #[test]
+    fn test_keymap_from_config() {
+        let config = |yaml: &str| -> HashMap<String, HashMap<String, Vec<String>>> {
+            serde_yaml::from_str(yaml).unwrap()
+        };
+        let keymap =
+            Keymap::from_config(&config("normal:\n  history: [H]\n  filter: []\n")).unwrap();
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            keymap.action(&AppMode::Normal, &key(KeyCode::Char('H'), none)),
+            Some(Action::History)
+        );
+        assert_eq!(
+            keymap.action(&AppMode::Normal, &key(KeyCode::Char('h'), none)),
+            None
+        );
+        assert_eq!(
+            keymap.action(&AppMode::Normal, &key(KeyCode::Char('/'), none)),
+            None
+        );
+        assert!(!keymap.hints(&AppMode::Normal).unwrap().contains("filter"));
+
+        let error = |yaml: &str| Keymap::from_config(&config(yaml)).unwrap_err().to_string();
+        assert_eq!(
+            error("normal:\n  history: [j]\n"),
+            "keymap: `j` is bound to both down and history in normal"
+        );
+        assert!(error("nomal:\n  history: [H]\n").contains("unknown mode `nomal`"));
+        assert!(error("normal:\n  histroy: [H]\n").contains("unknown action `histroy`"));
+        assert!(error("normal:\n  history: [hyper+h]\n").contains("normal.history"));
+    }
+}
@@ -1,15 +1,22 @@
 pub mod app;
+pub mod command;
 pub mod events;
+pub mod form;
+pub mod keymap;
 pub mod state;
+pub mod watcher;
 
 pub use app::*;
+pub use command::*;
 pub use events::*;
+pub use form::*;
+pub use keymap::*;
 pub use state::*;
 
 use color_eyre::Result;
 use std::path::Path;
 
-pub async fn run_tui(collection_path: impl AsRef<Path>) -> Result<()> {
-    let app = TuiApp::new(collection_path).await?;
+pub async fn run_tui(collection_path: impl AsRef<Path>, environment: Option<String>) -> Result<()> {
+    let app = TuiApp::new(collection_path, environment).await?;
     app.run().await
 }
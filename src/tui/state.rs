@@ -1,27 +1,147 @@
-use crate::core::{Collection, Request, Response, VariableInterpolator};
-use crate::io::{ResponseHistory, load_history, save_history};
+use crate::core::{
+    CodeLanguage, Collection, Request, Response, ResponseDiff, VariableInterpolator,
+};
+use crate::io::{
+    Clipboard, HistoryEntry, HistoryFilter, ResponseHistory, append_to_history,
+    delete_history_entry, load_history,
+};
+use crate::tui::{CompletionSource, Keymap, RequestForm, YankTarget, common_prefix, complete};
+use crate::ui::components::{
+    JsonNode, json_child_path, json_container_paths, visible_json_nodes, wrap_text,
+};
+use crate::ui::{BodyFormat, ScreenAreas, Theme};
+use std::cell::Cell;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AppMode {
     Normal,
     Filter,
     Variables,
     History,
     Command,
+    Codegen,
+    NewRequest,
+    RenameRequest,
+    ConfirmDelete,
+    /// Moving between fields of the inline request editor
+    Form,
+    /// Typing into the focused field of the inline request editor
+    FormEdit,
+    /// Adding or changing a variable from the Variables modal
+    VariableEdit,
+    /// Moving through the JSON tree in the response pane
+    Response,
+    /// Naming a session variable for the selected JSON value
+    SaveJsonValue,
+    /// Naming an `extract` variable for the selected JSON node
+    AddExtract,
+    /// Typing a search in the response pane
+    ResponseSearch,
+    /// Typing a JSONPath/jq query that filters the response body
+    ResponseQuery,
+    /// Typing the file to write the response (or part of it) to
+    SaveResponse,
+    /// Comparing two responses picked in the History modal
+    Diff,
+    /// Typing a filter for the History modal
+    HistoryFilter,
+    /// The request and response of one entry from the History modal
+    HistoryDetail,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResponseTab {
     Pretty,
     Raw,
     Headers,
     Timeline,
+    /// What was sent, with secrets redacted
+    Request,
+}
+
+/// Two responses compared from the History modal
+#[derive(Debug, Clone)]
+pub struct DiffView {
+    pub old_label: String,
+    pub new_label: String,
+    pub diff: ResponseDiff,
+    /// First row shown
+    pub scroll: usize,
+    /// Rows that fit at the last render, for paging
+    pub page_height: Cell<usize>,
+}
+
+/// One history entry, opened from the History modal
+#[derive(Debug, Clone)]
+pub struct HistoryDetail {
+    pub entry_id: uuid::Uuid,
+    pub lines: Vec<DetailLine>,
+    /// First line shown
+    pub scroll: usize,
+    /// Lines that fit at the last render, for paging
+    pub page_height: Cell<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetailLine {
+    Heading(String),
+    Text(String),
+}
+
+/// What gets written when saving a response to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponsePart {
+    /// JSON pretty-printed, anything else as received
+    Body,
+    /// Header lines, sorted by name
+    Headers,
+    /// Status, headers, body and timing as JSON, like `netbook export`
+    Full,
+}
+
+impl ResponsePart {
+    pub fn label(self) -> &'static str {
+        match self {
+            ResponsePart::Body => "body",
+            ResponsePart::Headers => "headers",
+            ResponsePart::Full => "full response",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ResponsePart::Body => ResponsePart::Headers,
+            ResponsePart::Headers => ResponsePart::Full,
+            ResponsePart::Full => ResponsePart::Body,
+        }
+    }
+}
+
+impl ResponseTab {
+    pub const ALL: [ResponseTab; 5] = [
+        ResponseTab::Pretty,
+        ResponseTab::Raw,
+        ResponseTab::Headers,
+        ResponseTab::Timeline,
+        ResponseTab::Request,
+    ];
+
+    pub fn index(self) -> usize {
+        match self {
+            ResponseTab::Pretty => 0,
+            ResponseTab::Raw => 1,
+            ResponseTab::Headers => 2,
+            ResponseTab::Timeline => 3,
+            ResponseTab::Request => 4,
+        }
+    }
 }
 
 pub struct AppState {
     pub collection: Collection,
     pub collection_path: std::path::PathBuf,
+    pub environment: Option<String>,
     pub selected_request_index: usize,
     pub filter_text: String,
     pub filtered_indices: Vec<usize>,
@@ -33,8 +153,132 @@ pub struct AppState {
     pub interpolator: VariableInterpolator,
     pub history: ResponseHistory,
     pub history_selected_index: usize,
+    /// Entry marked as the old side of the next diff
+    pub history_mark: Option<uuid::Uuid>,
+    pub diff_view: Option<DiffView>,
+    /// Text typed into the History modal's filter bar
+    pub history_filter_text: String,
+    /// The last filter text that parsed
+    pub history_filter: HistoryFilter,
+    /// Why the filter text doesn't parse, while it doesn't
+    pub history_filter_error: Option<String>,
+    pub history_detail: Option<HistoryDetail>,
     pub should_quit: bool,
     pub json_tree_state: JsonTreeState,
+    pub codegen_language: CodeLanguage,
+    /// Text typed into the new/rename request prompt
+    pub name_input: String,
+    /// Inline editor for the selected request while in `Form`/`FormEdit`
+    pub form: Option<RequestForm>,
+    /// Variables stored with `save_variables`, as opposed to session-only ones
+    pub saved_variables: HashMap<String, String>,
+    pub variables_selected_index: usize,
+    pub variable_edit: Option<VariableEdit>,
+    /// First visible line of each response tab, by `ResponseTab::index`
+    pub response_scroll: [usize; 5],
+    /// Text searched for with `/` in the response pane
+    pub response_search: String,
+    /// JSONPath/jq expression filtering the displayed body
+    pub response_query: String,
+    /// Result of `response_query`: the filtered body, or why it failed
+    pub response_query_result: Option<Result<serde_json::Value, String>>,
+    /// Last query used for each request's responses in this session
+    pub response_queries: HashMap<uuid::Uuid, String>,
+    /// Body text shown in the Raw tab, formatted once per response
+    pub response_raw_text: String,
+    /// Format of the displayed body, from its Content-Type
+    pub response_format: BodyFormat,
+    /// Re-indented body for the Pretty tab when it isn't shown as a tree
+    pub response_pretty_text: String,
+    /// Width and height of the response body area at the last render, used
+    /// for wrapping and paging
+    pub response_viewport: Cell<(u16, u16)>,
+    /// Colors from the config file, read at startup
+    pub theme: Theme,
+    /// Keys from the config file, read at startup
+    pub keymap: Keymap,
+    /// Where copied text goes, from the config file
+    pub clipboard: Clipboard,
+    /// Whether the key help overlay is shown over the current mode
+    pub show_help: bool,
+    /// Areas of the last frame, for mouse clicks
+    pub screen_areas: Cell<ScreenAreas>,
+    /// Part of the response the `SaveResponse` prompt writes; the path is
+    /// typed into `name_input`
+    pub save_part: ResponsePart,
+    /// Text typed after `:` in `Command` mode
+    pub command_input: String,
+    /// Candidates from the last Tab when more than one matched
+    pub command_completions: Vec<String>,
+}
+
+/// Where the value of a variable comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariableSource {
+    /// Set in this session only
+    Session,
+    /// Set in memory and persisted for future sessions
+    Saved,
+    EnvFile(std::path::PathBuf),
+    /// Only defined in the process environment
+    Process,
+}
+
+impl std::fmt::Display for VariableSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariableSource::Session => write!(f, "session"),
+            VariableSource::Saved => write!(f, "saved"),
+            VariableSource::EnvFile(path) => write!(f, "{}", path.display()),
+            VariableSource::Process => write!(f, "process env"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VariableEntry {
+    pub key: String,
+    pub value: String,
+    pub source: VariableSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableEditField {
+    Key,
+    Value,
+    Persist,
+}
+
+/// The variable being added or changed in `AppMode::VariableEdit`
+#[derive(Debug, Clone)]
+pub struct VariableEdit {
+    pub key: String,
+    pub value: String,
+    pub is_new: bool,
+    pub persist: bool,
+    pub focus: VariableEditField,
+}
+
+impl VariableEdit {
+    /// Moves between the fields; an existing variable's key is fixed
+    pub fn cycle_focus(&mut self, forward: bool) {
+        let fields: &[VariableEditField] = if self.is_new {
+            &[
+                VariableEditField::Key,
+                VariableEditField::Value,
+                VariableEditField::Persist,
+            ]
+        } else {
+            &[VariableEditField::Value, VariableEditField::Persist]
+        };
+        let position = fields.iter().position(|f| *f == self.focus).unwrap_or(0);
+        let next = if forward {
+            (position + 1) % fields.len()
+        } else {
+            (position + fields.len() - 1) % fields.len()
+        };
+        self.focus = fields[next];
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -43,17 +287,50 @@ pub struct JsonTreeState {
     pub selected_path: Option<String>,
 }
 
+impl JsonTreeState {
+    /// State for a new response: the root is open and selected
+    pub fn reset(&mut self) {
+        self.expanded_paths = std::collections::HashSet::from([String::new()]);
+        self.selected_path = None;
+    }
+}
+
 impl AppState {
-    pub async fn new(collection_path: std::path::PathBuf) -> color_eyre::Result<Self> {
+    pub async fn new(
+        collection_path: std::path::PathBuf,
+        environment: Option<String>,
+    ) -> color_eyre::Result<Self> {
         let collection = crate::io::load_collection(&collection_path)?;
-        let interpolator = crate::io::load_interpolator_with_context(&collection_path).await?;
-        let history = load_history().await.unwrap_or_default();
+        let interpolator =
+            crate::io::load_interpolator_for_environment(&collection_path, environment.as_deref())
+                .await?;
+        let history = load_history(&collection_path).await.unwrap_or_default();
+        let saved_variables = crate::io::load_variables().await.unwrap_or_default();
+        // A broken config shouldn't keep the TUI from starting
+        let mut status_message = "Ready".to_string();
+        let config = crate::io::load_config().unwrap_or_else(|e| {
+            status_message = format!("Using the default config: {}", e);
+            crate::io::Config::default()
+        });
+        let theme = Theme::from_config(&config).unwrap_or_else(|e| {
+            status_message = format!("Using the default theme: {}", e);
+            Theme::default()
+        });
+        let keymap = Keymap::from_config(&config.keymap).unwrap_or_else(|e| {
+            status_message = format!("Using the default keys: {}", e);
+            Keymap::default()
+        });
+        let clipboard = Clipboard::from_config(&config).unwrap_or_else(|e| {
+            status_message = format!("Detecting the clipboard: {}", e);
+            Clipboard::detect()
+        });
 
         let filtered_indices = (0..collection.len()).collect();
 
         Ok(Self {
             collection,
             collection_path,
+            environment,
             selected_request_index: 0,
             filter_text: String::new(),
             filtered_indices,
@@ -61,12 +338,41 @@ impl AppState {
             response_tab: ResponseTab::Pretty,
             current_response: None,
             is_executing: false,
-            status_message: "Ready".to_string(),
+            status_message,
             interpolator,
             history,
             history_selected_index: 0,
+            history_mark: None,
+            diff_view: None,
+            history_filter_text: String::new(),
+            history_filter: HistoryFilter::default(),
+            history_filter_error: None,
+            history_detail: None,
             should_quit: false,
             json_tree_state: JsonTreeState::default(),
+            codegen_language: CodeLanguage::Curl,
+            name_input: String::new(),
+            form: None,
+            saved_variables,
+            variables_selected_index: 0,
+            variable_edit: None,
+            response_scroll: [0; 5],
+            response_search: String::new(),
+            response_query: String::new(),
+            response_query_result: None,
+            response_queries: HashMap::new(),
+            response_raw_text: String::new(),
+            response_format: BodyFormat::Text,
+            response_pretty_text: String::new(),
+            response_viewport: Cell::new((80, 20)),
+            theme,
+            keymap,
+            clipboard,
+            show_help: false,
+            screen_areas: Cell::default(),
+            save_part: ResponsePart::Body,
+            command_input: String::new(),
+            command_completions: Vec::new(),
         })
     }
 
@@ -96,6 +402,10 @@ impl AppState {
             for (i, request) in self.collection.iter().enumerate() {
                 if request.name.to_lowercase().contains(&filter_lower)
                     || request.url.to_lowercase().contains(&filter_lower)
+                    || request
+                        .folder
+                        .as_ref()
+                        .is_some_and(|f| f.to_lowercase().contains(&filter_lower))
                     || request
                         .method
                         .to_string()
@@ -111,6 +421,583 @@ impl AppState {
         self.selected_request_index = 0;
     }
 
+    /// Re-reads the collection and env files after they changed on disk,
+    /// keeping the selected request and in-memory variables. Parse errors are
+    /// reported in the status bar and leave the current state untouched.
+    pub fn reload_from_disk(&mut self) {
+        let mut reloaded = Vec::new();
+
+        match self.reload_collection() {
+            Ok(true) => reloaded.push("collection"),
+            Ok(false) => {}
+            Err(e) => {
+                self.status_message = format!("Failed to reload collection: {}", e);
+                return;
+            }
+        }
+
+        match self.reload_variables() {
+            Ok(true) => reloaded.push("variables"),
+            Ok(false) => {}
+            Err(e) => {
+                self.status_message = format!("Failed to reload env files: {}", e);
+                return;
+            }
+        }
+
+        if !reloaded.is_empty() {
+            self.status_message = format!("Reloaded {} from disk", reloaded.join(" and "));
+        }
+    }
+
+    /// Returns `Ok(false)` when the file on disk matches what is loaded
+    fn reload_collection(&mut self) -> color_eyre::Result<bool> {
+        let collection = crate::io::load_collection(&self.collection_path)?;
+        if serde_json::to_value(&collection)? == serde_json::to_value(&self.collection)? {
+            return Ok(false);
+        }
+
+        let selected = self.get_current_request().map(|r| r.id);
+        self.collection = collection;
+        self.update_filter(self.filter_text.clone());
+
+        if let Some(position) = selected.and_then(|id| {
+            self.filtered_indices
+                .iter()
+                .position(|&i| self.collection[i].id == id)
+        }) {
+            self.selected_request_index = position;
+        }
+        Ok(true)
+    }
+
+    fn reload_variables(&mut self) -> color_eyre::Result<bool> {
+        let mut interpolator = VariableInterpolator::new();
+        interpolator.load_env_file(&self.collection_path)?;
+        if let Some(environment) = &self.environment {
+            interpolator.load_environment(&self.collection_path, environment)?;
+        }
+        if interpolator.env_vars == self.interpolator.env_vars {
+            return Ok(false);
+        }
+
+        interpolator.in_memory = std::mem::take(&mut self.interpolator.in_memory);
+        self.interpolator = interpolator;
+        Ok(true)
+    }
+
+    /// Switches to `.netbook/.env.<name>`, or to the base env files for
+    /// `None`, keeping in-memory variables
+    pub fn switch_environment(&mut self, environment: Option<String>) -> color_eyre::Result<()> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        if let Err(e) = self.reload_variables() {
+            self.environment = previous;
+            return Err(e);
+        }
+        self.status_message = match &self.environment {
+            Some(name) => format!("Switched to environment '{}'", name),
+            None => "Switched to the base env files".to_string(),
+        };
+        Ok(())
+    }
+
+    /// Selects the request with `id`, clearing the filter if it hides it
+    pub fn select_request(&mut self, id: uuid::Uuid) {
+        let position = |state: &Self| {
+            state
+                .filtered_indices
+                .iter()
+                .position(|&i| state.collection[i].id == id)
+        };
+        if position(self).is_none() {
+            self.update_filter(String::new());
+        }
+        if let Some(position) = position(self) {
+            self.selected_request_index = position;
+        }
+    }
+
+    /// Writes the current response body to `path`: JSON pretty-printed,
+    /// anything else as received
+    pub fn save_response(
+        &self,
+        part: ResponsePart,
+        path: &std::path::Path,
+    ) -> color_eyre::Result<()> {
+        std::fs::write(path, self.response_part_text(part)?)?;
+        Ok(())
+    }
+
+    pub fn response_part_text(&self, part: ResponsePart) -> color_eyre::Result<String> {
+        let response = self
+            .current_response
+            .as_ref()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No response yet"))?;
+        Ok(match part {
+            ResponsePart::Body => value_text(&response.body)?,
+            ResponsePart::Headers => {
+                let mut headers: Vec<_> = response.headers.iter().collect();
+                headers.sort();
+                let lines: Vec<String> = headers
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect();
+                lines.join("\n")
+            }
+            ResponsePart::Full => serde_json::to_string_pretty(response)?,
+        })
+    }
+
+    /// File name offered by the save prompt, in the working directory
+    pub fn suggested_response_path(&self, part: ResponsePart) -> String {
+        match part {
+            ResponsePart::Body => format!("response.{}", self.response_format.extension()),
+            ResponsePart::Headers => "headers.txt".to_string(),
+            ResponsePart::Full => "response-full.json".to_string(),
+        }
+    }
+
+    /// Entries listed in the History modal: those passing the filter,
+    /// newest first
+    pub fn visible_history(&self) -> Vec<&HistoryEntry> {
+        let mut entries = self.history.filter(&self.history_filter);
+        entries.reverse();
+        entries
+    }
+
+    /// The entry under the cursor in the History modal
+    pub fn selected_history_entry(&self) -> Option<&HistoryEntry> {
+        self.visible_history()
+            .get(self.history_selected_index)
+            .copied()
+    }
+
+    /// Applies the filter text once it parses; until then the previous
+    /// filter stays and the error is shown
+    pub fn update_history_filter(&mut self, text: String) {
+        match HistoryFilter::parse(&text) {
+            Ok(filter) => {
+                self.history_filter = filter;
+                self.history_filter_error = None;
+            }
+            Err(e) => self.history_filter_error = Some(e.to_string()),
+        }
+        self.history_filter_text = text;
+        self.history_selected_index = 0;
+    }
+
+    pub fn open_history_detail(&mut self) {
+        let Some(entry) = self.selected_history_entry() else {
+            return;
+        };
+        self.history_detail = Some(HistoryDetail {
+            entry_id: entry.id,
+            lines: detail_lines(entry),
+            scroll: 0,
+            page_height: Cell::new(0),
+        });
+        self.mode = AppMode::HistoryDetail;
+    }
+
+    pub fn scroll_history_detail(&mut self, delta: isize, page: bool) {
+        if let Some(view) = &mut self.history_detail {
+            let height = view.page_height.get().max(1);
+            let delta = if page { delta * height as isize } else { delta };
+            let max = view.lines.len().saturating_sub(height);
+            view.scroll = view.scroll.saturating_add_signed(delta).min(max);
+        }
+    }
+
+    /// Removes the selected entry from the History modal and the history
+    /// file
+    pub async fn delete_selected_history_entry(&mut self) -> color_eyre::Result<()> {
+        let Some(id) = self.selected_history_entry().map(|entry| entry.id) else {
+            return Ok(());
+        };
+        self.history.remove(id);
+        if self.history_mark == Some(id) {
+            self.history_mark = None;
+        }
+        let count = self.visible_history().len();
+        self.history_selected_index = self.history_selected_index.min(count.saturating_sub(1));
+        delete_history_entry(&self.collection_path, id).await
+    }
+
+    /// Marks the selected entry as the old side of a diff, or unmarks it
+    pub fn toggle_history_mark(&mut self) {
+        let Some(id) = self.selected_history_entry().map(|entry| entry.id) else {
+            return;
+        };
+        self.history_mark = (self.history_mark != Some(id)).then_some(id);
+    }
+
+    /// Compares the marked entry with the selected one, or without a mark,
+    /// the selected entry with the current response
+    pub fn open_diff(&mut self) {
+        let Some(selected) = self.selected_history_entry() else {
+            return;
+        };
+        let marked = self
+            .history_mark
+            .and_then(|id| self.history.get(id))
+            .filter(|marked| marked.id != selected.id);
+        let (old_label, new_label, diff) = match (marked, &self.current_response) {
+            (Some(marked), _) => (
+                marked.summary(),
+                selected.summary(),
+                crate::core::diff_responses(&marked.response, &selected.response),
+            ),
+            (None, Some(current)) => (
+                selected.summary(),
+                "current response".to_string(),
+                crate::core::diff_responses(&selected.response, current),
+            ),
+            (None, None) => {
+                self.status_message =
+                    "Mark an entry to compare with, or load a response first".to_string();
+                return;
+            }
+        };
+        self.diff_view = Some(DiffView {
+            old_label,
+            new_label,
+            diff,
+            scroll: 0,
+            page_height: Cell::new(10),
+        });
+        self.mode = AppMode::Diff;
+    }
+
+    /// Scrolls the diff by `delta` rows, or pages when `page` is set
+    pub fn scroll_diff(&mut self, delta: isize, page: bool) {
+        if let Some(view) = &mut self.diff_view {
+            let height = view.page_height.get().max(1);
+            let delta = if page { delta * height as isize } else { delta };
+            let max = view.diff.len().saturating_sub(height);
+            view.scroll = view.scroll.saturating_add_signed(delta).min(max);
+        }
+    }
+
+    pub fn open_save_response(&mut self) {
+        if self.current_response.is_none() {
+            self.status_message = "No response yet".to_string();
+            return;
+        }
+        self.save_part = ResponsePart::Body;
+        self.name_input = self.suggested_response_path(self.save_part);
+        self.mode = AppMode::SaveResponse;
+    }
+
+    /// Switches the prompt to the next part, along with the suggested file
+    /// name unless another one was typed
+    pub fn cycle_save_part(&mut self) {
+        let suggested = self.suggested_response_path(self.save_part);
+        self.save_part = self.save_part.next();
+        if self.name_input == suggested {
+            self.name_input = self.suggested_response_path(self.save_part);
+        }
+    }
+
+    /// The text to copy for `target`, and what it is for the status bar
+    pub fn yank_text(&self, target: &YankTarget) -> color_eyre::Result<(String, String)> {
+        use color_eyre::eyre::eyre;
+
+        if let YankTarget::Code(language) = target {
+            let request = self
+                .get_current_request()
+                .ok_or_else(|| eyre!("No request selected"))?;
+            let interpolated = self.interpolator.interpolate_request(request);
+            return Ok((
+                crate::core::generate_code(&interpolated, *language),
+                format!("'{}' as {}", request.name, language.label()),
+            ));
+        }
+
+        let response = self
+            .current_response
+            .as_ref()
+            .ok_or_else(|| eyre!("No response yet"))?;
+        match target {
+            YankTarget::Body => Ok((
+                self.response_part_text(ResponsePart::Body)?,
+                "the response body".to_string(),
+            )),
+            YankTarget::Value => {
+                let node = self
+                    .selected_json_node()
+                    .filter(|_| self.shows_json_tree())
+                    .ok_or_else(|| eyre!("No JSON value selected"))?;
+                let path = display_json_path(&self.json_node_expression(&node.path));
+                Ok((value_text(node.value)?, path))
+            }
+            YankTarget::Headers => Ok((
+                self.response_part_text(ResponsePart::Headers)?,
+                "the response headers".to_string(),
+            )),
+            YankTarget::Header(name) => response
+                .headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(key, value)| (value.clone(), format!("the {} header", key)))
+                .ok_or_else(|| eyre!("No '{}' header in the response", name)),
+            YankTarget::Request => response
+                .sent
+                .as_ref()
+                .map(|sent| (sent.to_string(), "the sent request".to_string()))
+                .ok_or_else(|| eyre!("The request wasn't recorded for this response")),
+            YankTarget::Code(_) => unreachable!(),
+        }
+    }
+
+    /// What `y` copies in the response pane: the tree's selected value, or
+    /// the text of the other tabs
+    pub fn response_yank_target(&self) -> YankTarget {
+        match self.response_tab {
+            _ if self.shows_json_tree() => YankTarget::Value,
+            ResponseTab::Headers => YankTarget::Headers,
+            ResponseTab::Request => YankTarget::Request,
+            _ => YankTarget::Body,
+        }
+    }
+
+    /// Writes the selected request, interpolated, as `language` code
+    pub fn export_code(
+        &self,
+        language: CodeLanguage,
+        path: &std::path::Path,
+    ) -> color_eyre::Result<()> {
+        let request = self
+            .get_current_request()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No request selected"))?;
+        let interpolated = self.interpolator.interpolate_request(request);
+        std::fs::write(path, crate::core::generate_code(&interpolated, language))?;
+        Ok(())
+    }
+
+    /// Completes the word being typed on the command line. A single match
+    /// is filled in; several are narrowed to their common prefix and listed.
+    pub fn complete_command(&mut self) {
+        let source = CompletionSource {
+            requests: self.collection.iter().map(|r| r.name.clone()).collect(),
+            environments: crate::io::list_environments(&self.collection_path),
+            headers: self.current_response.as_ref().map_or_else(Vec::new, |r| {
+                let mut names: Vec<String> = r.headers.keys().cloned().collect();
+                names.sort();
+                names
+            }),
+        };
+        let candidates = complete(&self.command_input, &source);
+        self.command_completions.clear();
+        match candidates.as_slice() {
+            [] => {}
+            [only] => {
+                self.command_input = only.clone();
+                // Leave the cursor where the argument goes
+                let takes_args = crate::tui::COMMANDS
+                    .iter()
+                    .any(|(name, args, _)| name == only && !args.is_empty());
+                if takes_args {
+                    self.command_input.push(' ');
+                }
+            }
+            _ => {
+                let prefix = common_prefix(&candidates);
+                if prefix.len() > self.command_input.len() {
+                    self.command_input = prefix;
+                }
+                self.command_completions = candidates;
+            }
+        }
+    }
+
+    /// Adds a GET request after the selected one, in the same folder. It
+    /// starts from the folder's defaults: the headers every request there
+    /// sends and the start of the URL they share.
+    pub fn add_request(&mut self, name: &str) {
+        let Some(name) = self.available_name(name, None) else {
+            return;
+        };
+        let folder = self.get_current_request().and_then(|r| r.folder.clone());
+        let siblings: Vec<&Request> = self
+            .collection
+            .iter()
+            .filter(|r| r.folder == folder)
+            .collect();
+        let request = Request {
+            id: uuid::Uuid::new_v4(),
+            name,
+            method: crate::core::HttpMethod::Get,
+            url: shared_url_base(&siblings),
+            headers: shared_headers(&siblings),
+            query: HashMap::new(),
+            body: None,
+            notes: None,
+            folder,
+            source: None,
+            disabled_headers: HashMap::new(),
+            disabled_query: HashMap::new(),
+            extract: HashMap::new(),
+        };
+        self.insert_after_selection(request, "Created");
+    }
+
+    pub fn duplicate_current_request(&mut self) {
+        let Some(current) = self.get_current_request() else {
+            return;
+        };
+        let names = self.collection.iter().map(|r| r.name.clone()).collect();
+        let mut copy = current.clone();
+        copy.id = uuid::Uuid::new_v4();
+        copy.name = crate::io::unique_name(&format!("{} (copy)", current.name), &names);
+        copy.source = None;
+        self.insert_after_selection(copy, "Duplicated as");
+    }
+
+    pub fn rename_current_request(&mut self, name: &str) {
+        let Some(index) = self.current_index() else {
+            return;
+        };
+        let id = self.collection[index].id;
+        let Some(name) = self.available_name(name, Some(id)) else {
+            return;
+        };
+        let old_name = std::mem::replace(&mut self.collection[index].name, name.clone());
+        self.persist_collection(format!("Renamed '{}' to '{}'", old_name, name), id);
+    }
+
+    pub fn delete_current_request(&mut self) {
+        let Some(index) = self.current_index() else {
+            return;
+        };
+        if let Err(e) = crate::io::remove_request_file(&self.collection[index]) {
+            self.status_message = format!("Failed to delete: {}", e);
+            return;
+        }
+        let removed = self.collection.remove(index);
+
+        // Keep the cursor on the request that took the deleted one's place
+        let next = self
+            .collection
+            .get(index)
+            .or_else(|| index.checked_sub(1).and_then(|i| self.collection.get(i)))
+            .map_or(removed.id, |r| r.id);
+        self.persist_collection(format!("Deleted '{}'", removed.name), next);
+    }
+
+    /// Swaps the selected request with its visible neighbour
+    pub fn move_current_request(&mut self, up: bool) {
+        if self.collection_path.is_dir() {
+            self.status_message =
+                "Requests in a directory collection are ordered by file name".to_string();
+            return;
+        }
+        let Some(index) = self.current_index() else {
+            return;
+        };
+        let neighbour = if up {
+            self.selected_request_index.checked_sub(1)
+        } else {
+            Some(self.selected_request_index + 1)
+        };
+        let Some(&other) = neighbour.and_then(|i| self.filtered_indices.get(i)) else {
+            return;
+        };
+
+        self.collection.swap(index, other);
+        let request = &self.collection[other];
+        let message = format!(
+            "Moved '{}' {}",
+            request.name,
+            if up { "up" } else { "down" }
+        );
+        self.persist_collection(message, request.id);
+    }
+
+    pub fn open_form(&mut self) {
+        if let Some(request) = self.get_current_request() {
+            self.form = Some(RequestForm::new(request));
+            self.mode = AppMode::Form;
+        }
+    }
+
+    /// Writes the form back into the collection and saves it
+    pub fn save_form(&mut self) {
+        self.mode = AppMode::Normal;
+        let Some(form) = self.form.take() else {
+            return;
+        };
+        let request = form.to_request();
+        let Some(slot) = self.collection.iter_mut().find(|r| r.id == request.id) else {
+            self.status_message = format!("Request '{}' no longer exists", request.name);
+            return;
+        };
+        *slot = request.clone();
+        self.persist_collection(format!("✓ Updated request '{}'", request.name), request.id);
+    }
+
+    pub fn discard_form(&mut self) {
+        self.mode = AppMode::Normal;
+        if self.form.take().is_some_and(|form| form.dirty) {
+            self.status_message = "Discarded changes".to_string();
+        }
+    }
+
+    fn current_index(&self) -> Option<usize> {
+        self.filtered_indices
+            .get(self.selected_request_index)
+            .copied()
+    }
+
+    /// Trimmed `name` if it is non-empty and no other request uses it;
+    /// otherwise explains why in the status bar
+    fn available_name(&mut self, name: &str, renaming: Option<uuid::Uuid>) -> Option<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            self.status_message = "Request name cannot be empty".to_string();
+            return None;
+        }
+        if self
+            .collection
+            .iter()
+            .any(|r| r.name == name && Some(r.id) != renaming)
+        {
+            self.status_message = format!("A request named '{}' already exists", name);
+            return None;
+        }
+        Some(name.to_string())
+    }
+
+    fn insert_after_selection(&mut self, request: Request, verb: &str) {
+        let index = self
+            .current_index()
+            .map_or(self.collection.len(), |i| i + 1);
+        let message = format!("{} '{}'", verb, request.name);
+        let id = request.id;
+        self.collection.insert(index, request);
+
+        // Clear the filter so the new request is visible
+        self.filter_text.clear();
+        self.persist_collection(message, id);
+    }
+
+    /// Saves the collection, then re-applies the filter and selects `select`
+    fn persist_collection(&mut self, message: String, select: uuid::Uuid) {
+        self.update_filter(self.filter_text.clone());
+        if let Some(position) = self
+            .filtered_indices
+            .iter()
+            .position(|&i| self.collection[i].id == select)
+        {
+            self.selected_request_index = position;
+        }
+
+        self.status_message =
+            match crate::io::save_collection(&self.collection, &self.collection_path) {
+                Ok(()) => message,
+                Err(e) => format!("Failed to save collection: {}", e),
+            };
+    }
+
     pub fn move_selection_up(&mut self) {
         if self.selected_request_index > 0 {
             self.selected_request_index -= 1;
@@ -128,26 +1015,29 @@ impl AppState {
             ResponseTab::Pretty => ResponseTab::Raw,
             ResponseTab::Raw => ResponseTab::Headers,
             ResponseTab::Headers => ResponseTab::Timeline,
-            ResponseTab::Timeline => ResponseTab::Pretty,
+            ResponseTab::Timeline => ResponseTab::Request,
+            ResponseTab::Request => ResponseTab::Pretty,
         };
     }
 
     pub fn previous_response_tab(&mut self) {
         self.response_tab = match self.response_tab {
-            ResponseTab::Pretty => ResponseTab::Timeline,
+            ResponseTab::Pretty => ResponseTab::Request,
             ResponseTab::Raw => ResponseTab::Pretty,
             ResponseTab::Headers => ResponseTab::Raw,
             ResponseTab::Timeline => ResponseTab::Headers,
+            ResponseTab::Request => ResponseTab::Timeline,
         };
     }
 
+    /// Records the response to `sent`, the request as it was sent
     pub async fn save_response_to_history(
         &mut self,
-        request_name: String,
+        sent: &Request,
         response: Response,
     ) -> color_eyre::Result<()> {
-        self.history.add_entry(request_name, response);
-        save_history(&self.history).await?;
+        let entry = self.history.push(HistoryEntry::sent(sent, response));
+        append_to_history(&self.collection_path, entry).await?;
         Ok(())
     }
 
@@ -159,20 +1049,556 @@ impl AppState {
         self.interpolator.get_variable(key)
     }
 
-    pub fn get_all_variables(&self) -> HashMap<String, String> {
-        let mut all_vars = HashMap::new();
+    /// Every variable with its effective value and where that value comes
+    /// from, sorted by name. Process environment variables are only listed
+    /// when a request in the collection refers to them.
+    pub fn get_all_variables(&self) -> Vec<VariableEntry> {
+        let interpolator = &self.interpolator;
+        let mut entries: HashMap<String, VariableEntry> = HashMap::new();
+
+        for request in &self.collection {
+            let referenced = std::iter::once(request.url.clone())
+                .chain(request.headers.values().cloned())
+                .chain(request.query.values().cloned())
+                .chain(request.body.iter().map(|b| b.to_string()));
+            for text in referenced {
+                for key in interpolator.variables_in(&text) {
+                    if let Ok(value) = std::env::var(&key) {
+                        let source = VariableSource::Process;
+                        entries.insert(key.clone(), VariableEntry { key, value, source });
+                    }
+                }
+            }
+        }
+
+        for (key, value) in &interpolator.env_vars {
+            let source = interpolator
+                .env_sources
+                .get(key)
+                .map_or(VariableSource::Process, |path| {
+                    VariableSource::EnvFile(path.clone())
+                });
+            entries.insert(
+                key.clone(),
+                VariableEntry {
+                    key: key.clone(),
+                    value: value.clone(),
+                    source,
+                },
+            );
+        }
+
+        for (key, value) in &interpolator.in_memory {
+            let source = if self.saved_variables.get(key) == Some(value) {
+                VariableSource::Saved
+            } else {
+                VariableSource::Session
+            };
+            entries.insert(
+                key.clone(),
+                VariableEntry {
+                    key: key.clone(),
+                    value: value.clone(),
+                    source,
+                },
+            );
+        }
+
+        let mut entries: Vec<VariableEntry> = entries.into_values().collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        entries
+    }
+
+    pub fn selected_variable(&self) -> Option<VariableEntry> {
+        self.get_all_variables()
+            .into_iter()
+            .nth(self.variables_selected_index)
+    }
+
+    pub fn move_variable_selection(&mut self, down: bool) {
+        let count = self.get_all_variables().len();
+        if down && self.variables_selected_index + 1 < count {
+            self.variables_selected_index += 1;
+        } else if !down && self.variables_selected_index > 0 {
+            self.variables_selected_index -= 1;
+        }
+    }
+
+    /// Opens the editor for a new variable, or for the selected one
+    pub fn start_variable_edit(&mut self, new: bool) {
+        let edit = if new {
+            VariableEdit {
+                key: String::new(),
+                value: String::new(),
+                is_new: true,
+                persist: false,
+                focus: VariableEditField::Key,
+            }
+        } else {
+            let Some(entry) = self.selected_variable() else {
+                return;
+            };
+            VariableEdit {
+                persist: entry.source == VariableSource::Saved,
+                key: entry.key,
+                value: entry.value,
+                is_new: false,
+                focus: VariableEditField::Value,
+            }
+        };
+        self.variable_edit = Some(edit);
+        self.mode = AppMode::VariableEdit;
+    }
+
+    /// Applies the edit as an in-memory variable, persisting it with
+    /// `save_variables` when asked to. Env files are never modified; a new
+    /// value for one of their variables overrides it instead.
+    pub async fn commit_variable_edit(&mut self) -> color_eyre::Result<()> {
+        let Some(edit) = self.variable_edit.take() else {
+            return Ok(());
+        };
+        self.mode = AppMode::Variables;
 
-        // Add env file variables
-        for (k, v) in &self.interpolator.env_vars {
-            all_vars.insert(k.clone(), format!("(env) {}", v));
+        let key = edit.key.trim().to_string();
+        if !is_variable_name(&key) {
+            self.status_message =
+                format!("Invalid variable name '{}': use letters, digits and _", key);
+            return Ok(());
         }
 
-        // Add in-memory variables (these override env)
-        for (k, v) in &self.interpolator.in_memory {
-            all_vars.insert(k.clone(), v.clone());
+        self.interpolator
+            .set_variable(key.clone(), edit.value.clone());
+        let was_saved = self.saved_variables.contains_key(&key);
+        if edit.persist {
+            self.saved_variables.insert(key.clone(), edit.value);
+        } else {
+            self.saved_variables.remove(&key);
+        }
+        if edit.persist || was_saved {
+            crate::io::save_variables(&self.saved_variables).await?;
         }
 
-        all_vars
+        self.select_variable(&key);
+        self.status_message = if edit.persist {
+            format!("Saved variable '{}'", key)
+        } else {
+            format!("Set '{}' for this session", key)
+        };
+        Ok(())
+    }
+
+    /// Removes the selected in-memory variable. Env file and process
+    /// variables have to be removed at their source.
+    pub async fn delete_selected_variable(&mut self) -> color_eyre::Result<()> {
+        let Some(entry) = self.selected_variable() else {
+            return Ok(());
+        };
+        match &entry.source {
+            VariableSource::Session | VariableSource::Saved => {
+                self.interpolator.remove_variable(&entry.key);
+                if self.saved_variables.remove(&entry.key).is_some() {
+                    crate::io::save_variables(&self.saved_variables).await?;
+                }
+                self.status_message = format!("Deleted variable '{}'", entry.key);
+                let count = self.get_all_variables().len();
+                self.variables_selected_index =
+                    self.variables_selected_index.min(count.saturating_sub(1));
+            }
+            source => {
+                self.status_message =
+                    format!("'{}' comes from {}; remove it there", entry.key, source);
+            }
+        }
+        Ok(())
+    }
+
+    fn select_variable(&mut self, key: &str) {
+        if let Some(index) = self.get_all_variables().iter().position(|e| e.key == key) {
+            self.variables_selected_index = index;
+        }
+    }
+
+    /// Shows a response, filtered by the last query used for its request
+    pub fn set_response(&mut self, response: Response) {
+        self.response_query = response
+            .request_id
+            .and_then(|id| self.response_queries.get(&id).cloned())
+            .unwrap_or_default();
+        self.current_response = Some(response);
+        self.apply_response_query();
+    }
+
+    /// The body as displayed: the query's result while it has one
+    pub fn response_body(&self) -> Option<&serde_json::Value> {
+        match &self.response_query_result {
+            Some(Ok(value)) => Some(value),
+            _ => self.current_response.as_ref().map(|r| &r.body),
+        }
+    }
+
+    /// Re-runs `response_query` and resets the views of the body. An empty
+    /// query shows the whole body; an invalid one keeps showing it too, with
+    /// the error in the query bar.
+    pub fn apply_response_query(&mut self) {
+        let Some(response) = &self.current_response else {
+            return;
+        };
+        let query = self.response_query.trim();
+        self.response_query_result = (!query.is_empty())
+            .then(|| crate::core::query_value(&response.body, query).map_err(|e| e.to_string()));
+        if let Some(id) = response.request_id {
+            if query.is_empty() {
+                self.response_queries.remove(&id);
+            } else {
+                self.response_queries.insert(id, query.to_string());
+            }
+        }
+
+        self.response_raw_text = match self.response_body() {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            Some(body) => serde_json::to_string_pretty(body).unwrap_or_else(|_| body.to_string()),
+            None => String::new(),
+        };
+        self.response_format = match (&self.response_query_result, self.response_body()) {
+            (None, Some(serde_json::Value::String(text))) => {
+                BodyFormat::detect(&response.headers, text)
+            }
+            _ => BodyFormat::Json,
+        };
+        self.response_pretty_text = self.response_format.pretty_print(&self.response_raw_text);
+        self.json_tree_state.reset();
+        self.response_scroll = [0; 5];
+    }
+
+    /// Whether the Pretty tab is showing the body as a JSON tree; text
+    /// bodies (XML, HTML, ...) are shown as highlighted text instead
+    pub fn shows_json_tree(&self) -> bool {
+        self.response_tab == ResponseTab::Pretty
+            && self.response_body().is_some_and(|body| !body.is_string())
+    }
+
+    /// Query selecting the given node of the displayed body from the full
+    /// response body, for variables and `extract`
+    fn json_node_expression(&self, path: &str) -> String {
+        let query = self.response_query.trim();
+        let (Some(Ok(_)), Some(response)) = (&self.response_query_result, &self.current_response)
+        else {
+            return path.to_string();
+        };
+        let single =
+            crate::core::run_query(&response.body, query).is_ok_and(|values| values.len() == 1);
+        // Several results are shown as one array, which `[...]` rebuilds
+        let query = if single {
+            query.to_string()
+        } else {
+            format!("[{}]", query)
+        };
+        if path.is_empty() {
+            query
+        } else {
+            format!("{} | {}", query, path)
+        }
+    }
+
+    /// Lines of a text tab (Raw, Headers, Timeline, Request), wrapped to the pane
+    pub fn response_text_lines(&self) -> Vec<String> {
+        match self.response_text() {
+            Some((text, _)) => wrap_text(&text, self.response_viewport.get().0 as usize),
+            None => Vec::new(),
+        }
+    }
+
+    /// Unwrapped text of the current text tab and the format to highlight it with
+    pub fn response_text(&self) -> Option<(String, BodyFormat)> {
+        let response = self.current_response.as_ref()?;
+        let text = match self.response_tab {
+            ResponseTab::Pretty if self.shows_json_tree() => return None,
+            ResponseTab::Pretty => {
+                return Some((self.response_pretty_text.clone(), self.response_format));
+            }
+            ResponseTab::Raw => {
+                return Some((self.response_raw_text.clone(), self.response_format));
+            }
+            ResponseTab::Headers => {
+                let mut headers: Vec<_> = response.headers.iter().collect();
+                headers.sort();
+                let mut lines = vec![format!("Status: {}", response.status), String::new()];
+                lines.extend(headers.iter().map(|(k, v)| format!("{}: {}", k, v)));
+                lines.join("\n")
+            }
+            ResponseTab::Timeline => [
+                format!("Total Time: {}ms", response.timing.total_ms),
+                format!(
+                    "Timestamp: {}",
+                    response.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+                ),
+                String::new(),
+                "Detailed timing information not available".to_string(),
+                "(reqwest doesn't expose detailed timing)".to_string(),
+            ]
+            .join("\n"),
+            ResponseTab::Request => match &response.sent {
+                Some(sent) => sent.to_string(),
+                None => "The request wasn't recorded for this response".to_string(),
+            },
+        };
+        Some((text, BodyFormat::Text))
+    }
+
+    fn response_page_height(&self) -> usize {
+        (self.response_viewport.get().1 as usize).max(1)
+    }
+
+    /// Moves the tree cursor in the Pretty tab, or scrolls the other tabs
+    pub fn scroll_response(&mut self, delta: isize) {
+        if self.shows_json_tree() {
+            self.move_json_selection(delta);
+            return;
+        }
+        let max = self
+            .response_text_lines()
+            .len()
+            .saturating_sub(self.response_page_height());
+        let scroll = &mut self.response_scroll[self.response_tab.index()];
+        *scroll = scroll.saturating_add_signed(delta).min(max);
+    }
+
+    pub fn page_response(&mut self, down: bool) {
+        let page = self.response_page_height() as isize;
+        self.scroll_response(if down { page } else { -page });
+    }
+
+    /// Jumps to the first (`g`) or last (`G`) line or node
+    pub fn scroll_response_to_end(&mut self, bottom: bool) {
+        self.scroll_response(if bottom { isize::MAX } else { isize::MIN });
+    }
+
+    /// Jumps to the next (or previous) match of `response_search`, wrapping
+    /// around. In the Pretty tab collapsed nodes are searched too, and
+    /// opened to reveal the match.
+    pub fn search_response(&mut self, forward: bool) {
+        let needle = self.response_search.to_lowercase();
+        let Some(body) = self.response_body().filter(|_| !needle.is_empty()) else {
+            return;
+        };
+
+        let (matches, current) = if self.shows_json_tree() {
+            let nodes = visible_json_nodes(body, &json_container_paths(body));
+            let selected = self.json_tree_state.selected_path.as_deref().unwrap_or("");
+            let current = nodes.iter().position(|n| n.path == selected).unwrap_or(0);
+            let matches: Vec<(usize, String)> = nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| node.matches(&needle))
+                .map(|(i, node)| (i, node.path.clone()))
+                .collect();
+            (matches, current)
+        } else {
+            let matches = self
+                .response_text_lines()
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.to_lowercase().contains(&needle))
+                .map(|(i, _)| (i, String::new()))
+                .collect();
+            (matches, self.response_scroll[self.response_tab.index()])
+        };
+
+        if matches.is_empty() {
+            self.status_message = format!("No matches for '{}'", self.response_search);
+            return;
+        }
+        let position = if forward {
+            matches.iter().position(|(i, _)| *i > current).unwrap_or(0)
+        } else {
+            matches
+                .iter()
+                .rposition(|(i, _)| *i < current)
+                .unwrap_or(matches.len() - 1)
+        };
+        let (line, path) = matches[position].clone();
+
+        if self.shows_json_tree() {
+            self.select_json_path(path);
+        } else {
+            let max = self
+                .response_text_lines()
+                .len()
+                .saturating_sub(self.response_page_height());
+            self.response_scroll[self.response_tab.index()] = line.min(max);
+        }
+        self.status_message = format!(
+            "Match {} of {} for '{}'",
+            position + 1,
+            matches.len(),
+            self.response_search
+        );
+    }
+
+    /// Selects a node, opening its ancestors so it is visible
+    fn select_json_path(&mut self, path: String) {
+        let mut ancestor = String::new();
+        self.json_tree_state.expanded_paths.insert(String::new());
+        for segment in path.split('.').filter(|s| !s.is_empty()) {
+            ancestor = json_child_path(&ancestor, segment);
+            if ancestor != path {
+                self.json_tree_state.expanded_paths.insert(ancestor.clone());
+            }
+        }
+        self.json_tree_state.selected_path = Some(path);
+        self.scroll_to_json_selection();
+    }
+
+    /// Scrolls the Pretty tab just enough to show the selected node
+    fn scroll_to_json_selection(&mut self) {
+        let selected = self.selected_json_index();
+        let height = self.response_page_height();
+        let scroll = &mut self.response_scroll[ResponseTab::Pretty.index()];
+        if selected < *scroll {
+            *scroll = selected;
+        } else if selected >= *scroll + height {
+            *scroll = selected + 1 - height;
+        }
+    }
+
+    /// Nodes of the response body's tree that are currently visible
+    pub fn json_tree_nodes(&self) -> Vec<JsonNode<'_>> {
+        self.response_body()
+            .map(|body| visible_json_nodes(body, &self.json_tree_state.expanded_paths))
+            .unwrap_or_default()
+    }
+
+    /// Row of the selected node among `json_tree_nodes`
+    pub fn selected_json_index(&self) -> usize {
+        let selected = self.json_tree_state.selected_path.as_deref().unwrap_or("");
+        self.json_tree_nodes()
+            .iter()
+            .position(|node| node.path == selected)
+            .unwrap_or(0)
+    }
+
+    pub fn selected_json_node(&self) -> Option<JsonNode<'_>> {
+        let index = self.selected_json_index();
+        self.json_tree_nodes().into_iter().nth(index)
+    }
+
+    pub fn move_json_selection(&mut self, delta: isize) {
+        let nodes = self.json_tree_nodes();
+        if nodes.is_empty() {
+            return;
+        }
+        let index = self
+            .selected_json_index()
+            .saturating_add_signed(delta)
+            .min(nodes.len() - 1);
+        let path = nodes[index].path.clone();
+        self.json_tree_state.selected_path = Some(path);
+        self.scroll_to_json_selection();
+    }
+
+    pub fn toggle_selected_json_node(&mut self) {
+        if let Some(node) = self.selected_json_node().filter(JsonNode::is_container) {
+            let path = node.path;
+            self.toggle_json_node(path);
+            self.scroll_to_json_selection();
+        }
+    }
+
+    pub fn expand_all_json(&mut self) {
+        if let Some(body) = self.response_body() {
+            self.json_tree_state.expanded_paths = json_container_paths(body);
+        }
+    }
+
+    /// Closes everything below the root, keeping the selection on the
+    /// top-level node that contained it
+    pub fn collapse_all_json(&mut self) {
+        let selected = self.json_tree_state.selected_path.take();
+        self.json_tree_state.reset();
+        self.json_tree_state.selected_path = selected
+            .as_deref()
+            .and_then(|path| path.split('.').next())
+            .map(str::to_string);
+        self.scroll_to_json_selection();
+    }
+
+    /// Suggested variable name for the selected node: its key, or
+    /// `response` for the root and array elements
+    pub fn selected_json_variable_name(&self) -> String {
+        let key = self
+            .selected_json_node()
+            .filter(|node| !node.in_array)
+            .and_then(|node| node.key)
+            .unwrap_or_else(|| "response".to_string());
+        key.chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    /// Sets a session variable to the selected JSON value
+    pub fn save_selected_json_value(&mut self, name: &str) {
+        let name = name.trim();
+        if !is_variable_name(name) {
+            self.status_message = format!(
+                "Invalid variable name '{}': use letters, digits and _",
+                name
+            );
+            return;
+        }
+        let Some(path) = self.selected_json_node().map(|node| node.path) else {
+            return;
+        };
+        let expression = self.json_node_expression(&path);
+        let Some(value) = self.current_response.as_ref().and_then(|response| {
+            self.interpolator
+                .extract_from_response_path(&response.body, &expression)
+        }) else {
+            return;
+        };
+
+        self.interpolator.set_variable(name.to_string(), value);
+        self.status_message = format!("Set '{}' from {}", name, display_json_path(&expression));
+    }
+
+    /// Adds the selected node to the `extract` map of the request that
+    /// produced the response, so the variable is refreshed on every run
+    pub fn add_extract_for_selected(&mut self, name: &str) {
+        let name = name.trim();
+        if !is_variable_name(name) {
+            self.status_message = format!(
+                "Invalid variable name '{}': use letters, digits and _",
+                name
+            );
+            return;
+        }
+        let Some(path) = self
+            .selected_json_node()
+            .map(|node| self.json_node_expression(&node.path))
+        else {
+            return;
+        };
+        let request_id = self.current_response.as_ref().and_then(|r| r.request_id);
+        let Some(request) = self
+            .collection
+            .iter_mut()
+            .find(|r| Some(r.id) == request_id)
+        else {
+            self.status_message = "The request for this response no longer exists".to_string();
+            return;
+        };
+
+        request.extract.insert(name.to_string(), path.clone());
+        let message = format!(
+            "'{}' now extracts {} from {}",
+            request.name,
+            name,
+            display_json_path(&path)
+        );
+        self.save_selected_json_value(name);
+        let selected = self
+            .get_current_request()
+            .map_or(uuid::Uuid::nil(), |r| r.id);
+        self.persist_collection(message, selected);
     }
 
     pub fn toggle_json_node(&mut self, path: String) {
@@ -188,6 +1614,108 @@ impl AppState {
     }
 }
 
+/// Whether `name` can be used in a `{{name}}` placeholder
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// A JSON tree path (or query) as shown to the user, `$` being the whole
+/// body
+pub fn display_json_path(path: &str) -> String {
+    if path.is_empty() {
+        "$".to_string()
+    } else if path
+        .chars()
+        .all(|c| c.is_alphanumeric() || "_-.".contains(c))
+    {
+        format!("$.{}", path)
+    } else {
+        path.to_string()
+    }
+}
+
+/// Strings as they are, anything else as pretty-printed JSON
+fn value_text(value: &serde_json::Value) -> serde_json::Result<String> {
+    match value {
+        serde_json::Value::String(text) => Ok(text.clone()),
+        value => serde_json::to_string_pretty(value),
+    }
+}
+
+/// The start of the URL all `requests` share, up to a `/`, such as
+/// `{{baseUrl}}/`
+fn shared_url_base(requests: &[&Request]) -> String {
+    let Some((first, rest)) = requests.split_first() else {
+        return "https://example.com".to_string();
+    };
+    let mut prefix = first.url.as_str();
+    for request in rest {
+        let mut len = prefix
+            .bytes()
+            .zip(request.url.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !prefix.is_char_boundary(len) {
+            len -= 1;
+        }
+        prefix = &prefix[..len];
+    }
+    match prefix.rfind('/') {
+        Some(end) => prefix[..=end].to_string(),
+        None if prefix.is_empty() => "https://example.com".to_string(),
+        None => prefix.to_string(),
+    }
+}
+
+/// Headers every one of `requests` sends with the same value
+fn shared_headers(requests: &[&Request]) -> HashMap<String, String> {
+    let Some((first, rest)) = requests.split_first() else {
+        return HashMap::new();
+    };
+    first
+        .headers
+        .iter()
+        .filter(|(name, value)| rest.iter().all(|r| r.headers.get(*name) == Some(value)))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// The History detail view: the request as it was sent, then the response
+fn detail_lines(entry: &HistoryEntry) -> Vec<DetailLine> {
+    let text = |line: &str| DetailLine::Text(line.to_string());
+    let headers = |headers: &HashMap<String, String>| {
+        let mut headers: Vec<_> = headers.iter().collect();
+        headers.sort();
+        headers
+            .into_iter()
+            .map(|(name, value)| DetailLine::Text(format!("{}: {}", name, value)))
+            .collect::<Vec<_>>()
+    };
+
+    let mut lines = vec![text(&entry.summary()), text("")];
+    lines.push(DetailLine::Heading("Request".to_string()));
+    match &entry.response.sent {
+        Some(sent) => lines.extend(sent.to_string().lines().map(text)),
+        None => lines.push(text("Not recorded for entries this old")),
+    }
+
+    let response = &entry.response;
+    lines.extend([text(""), DetailLine::Heading("Response".to_string())]);
+    lines.push(DetailLine::Text(format!(
+        "{} ({}ms)",
+        response.status, response.timing.total_ms
+    )));
+    lines.extend(headers(&response.headers));
+    lines.push(text(""));
+    lines.extend(
+        value_text(&response.body)
+            .unwrap_or_default()
+            .lines()
+            .map(text),
+    );
+    lines
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,6 +1732,12 @@ mod tests {
                 query: HashMap::new(),
                 body: None,
                 notes: Some("Get all users".to_string()),
+                folder: None,
+                source: None,
+                disabled_headers: HashMap::new(),
+                disabled_query: HashMap::new(),
+                extract: HashMap::new(),
+                id: uuid::Uuid::new_v4(),
             },
             Request {
                 name: "Create Post".to_string(),
@@ -213,19 +1747,24 @@ mod tests {
                 query: HashMap::new(),
                 body: None,
                 notes: Some("Create a new post".to_string()),
+                folder: None,
+                source: None,
+                disabled_headers: HashMap::new(),
+                disabled_query: HashMap::new(),
+                extract: HashMap::new(),
+                id: uuid::Uuid::new_v4(),
             },
         ]
     }
 
-    #[test]
-    fn test_filter_requests() {
-        let collection = create_test_requests();
-        let mut state = AppState {
+    fn test_state(collection: Collection) -> AppState {
+        AppState {
+            filtered_indices: (0..collection.len()).collect(),
             collection,
             collection_path: std::path::PathBuf::from("test.json"),
+            environment: None,
             selected_request_index: 0,
             filter_text: String::new(),
-            filtered_indices: (0..2).collect(),
             mode: AppMode::Normal,
             response_tab: ResponseTab::Pretty,
             current_response: None,
@@ -234,9 +1773,44 @@ mod tests {
             interpolator: VariableInterpolator::new(),
             history: ResponseHistory::default(),
             history_selected_index: 0,
+            history_mark: None,
+            diff_view: None,
+            history_filter_text: String::new(),
+            history_filter: HistoryFilter::default(),
+            history_filter_error: None,
+            history_detail: None,
             should_quit: false,
             json_tree_state: JsonTreeState::default(),
-        };
+            codegen_language: CodeLanguage::Curl,
+            name_input: String::new(),
+            form: None,
+            saved_variables: HashMap::new(),
+            variables_selected_index: 0,
+            variable_edit: None,
+            response_scroll: [0; 5],
+            response_search: String::new(),
+            response_query: String::new(),
+            response_query_result: None,
+            response_queries: HashMap::new(),
+            response_raw_text: String::new(),
+            response_format: BodyFormat::Text,
+            response_pretty_text: String::new(),
+            response_viewport: Cell::new((80, 20)),
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+            clipboard: Clipboard::Osc52,
+            show_help: false,
+            screen_areas: Cell::default(),
+            save_part: ResponsePart::Body,
+            command_input: String::new(),
+            command_completions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_filter_requests() {
+        let collection = create_test_requests();
+        let mut state = test_state(collection);
 
         // Test filtering by name
         state.update_filter("Get".to_string());
@@ -256,23 +1830,7 @@ mod tests {
     #[test]
     fn test_selection_navigation() {
         let collection = create_test_requests();
-        let mut state = AppState {
-            collection,
-            collection_path: std::path::PathBuf::from("test.json"),
-            selected_request_index: 0,
-            filter_text: String::new(),
-            filtered_indices: (0..2).collect(),
-            mode: AppMode::Normal,
-            response_tab: ResponseTab::Pretty,
-            current_response: None,
-            is_executing: false,
-            status_message: "Ready".to_string(),
-            interpolator: VariableInterpolator::new(),
-            history: ResponseHistory::default(),
-            history_selected_index: 0,
-            should_quit: false,
-            json_tree_state: JsonTreeState::default(),
-        };
+        let mut state = test_state(collection);
 
         // Test moving down
         state.move_selection_down();
@@ -290,4 +1848,568 @@ mod tests {
         state.move_selection_down();
         assert_eq!(state.selected_request_index, 1);
     }
+
+    #[test]
+    fn test_complete_command() {
+        let mut state = test_state(create_test_requests());
+
+        state.command_input = "ru".to_string();
+        state.complete_command();
+        assert_eq!(state.command_input, "run ");
+        assert!(state.command_completions.is_empty());
+
+        state.command_input = "run c".to_string();
+        state.complete_command();
+        assert_eq!(state.command_input, "run Create Post");
+
+        state.command_input = "ed".to_string();
+        state.complete_command();
+        assert_eq!(state.command_input, "edit");
+        assert_eq!(state.command_completions, ["edit", "editor"]);
+    }
+
+    #[test]
+    fn test_select_request_clears_filter() {
+        let collection = create_test_requests();
+        let id = collection[1].id;
+        let mut state = test_state(collection);
+
+        state.update_filter("Get".to_string());
+        state.select_request(id);
+        assert!(state.filter_text.is_empty());
+        assert_eq!(state.get_current_request().unwrap().name, "Create Post");
+    }
+
+    #[test]
+    fn test_reload_keeps_selection() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("collection.json");
+        let mut collection = create_test_requests();
+        crate::io::save_collection(&collection, &path).unwrap();
+
+        let mut state = test_state(collection.clone());
+        state.collection_path = path.clone();
+        state.set_variable("session".to_string(), "kept".to_string());
+        state.move_selection_down();
+
+        // Insert a request ahead of the selected one
+        let mut added = collection[0].clone();
+        added.name = "List Posts".to_string();
+        collection.insert(0, added);
+        crate::io::save_collection(&collection, &path).unwrap();
+        std::fs::write(dir.path().join(".env"), "token=abc\n").unwrap();
+
+        state.reload_from_disk();
+        assert_eq!(state.collection.len(), 3);
+        assert_eq!(state.get_current_request().unwrap().name, "Create Post");
+        assert_eq!(state.get_variable("token"), Some("abc".to_string()));
+        assert_eq!(state.get_variable("session"), Some("kept".to_string()));
+
+        std::fs::write(&path, "[{\"name\": ").unwrap();
+        state.reload_from_disk();
+        assert!(
+            state
+                .status_message
+                .starts_with("Failed to reload collection")
+        );
+        assert_eq!(state.collection.len(), 3);
+    }
+
+    #[test]
+    fn test_request_crud_is_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("collection.json");
+        let mut state = test_state(create_test_requests());
+        state.collection_path = path.clone();
+        let saved = || {
+            crate::io::load_collection(&path)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.name)
+                .collect::<Vec<_>>()
+        };
+
+        // New requests start from what the folder's requests share
+        for request in &mut state.collection {
+            request
+                .headers
+                .insert("Authorization".to_string(), "Bearer {{token}}".to_string());
+        }
+        state.collection[0]
+            .headers
+            .insert("X-Only".to_string(), "1".to_string());
+        state.add_request("Health");
+        let health = state.get_current_request().unwrap();
+        assert_eq!(health.name, "Health");
+        assert_eq!(health.url, "https://api.example.com/");
+        assert_eq!(
+            health.headers,
+            HashMap::from([("Authorization".to_string(), "Bearer {{token}}".to_string())])
+        );
+        assert_eq!(saved(), vec!["Get Users", "Health", "Create Post"]);
+
+        state.duplicate_current_request();
+        state.rename_current_request("Create Post");
+        assert!(state.status_message.contains("already exists"));
+        state.rename_current_request("Ready");
+        assert_eq!(saved(), vec!["Get Users", "Health", "Ready", "Create Post"]);
+
+        state.move_current_request(true);
+        state.move_current_request(true);
+        assert_eq!(state.selected_request_index, 0);
+        assert_eq!(saved(), vec!["Ready", "Get Users", "Health", "Create Post"]);
+
+        state.delete_current_request();
+        assert_eq!(state.get_current_request().unwrap().name, "Get Users");
+        assert_eq!(saved(), vec!["Get Users", "Health", "Create Post"]);
+    }
+
+    #[tokio::test]
+    async fn test_variable_sources_and_session_edits() {
+        let mut collection = create_test_requests();
+        collection[0].url = "{{baseUrl}}/users?path={{PATH}}".to_string();
+        let mut state = test_state(collection);
+        state
+            .interpolator
+            .env_vars
+            .insert("baseUrl".to_string(), "https://a".to_string());
+        state
+            .interpolator
+            .env_sources
+            .insert("baseUrl".to_string(), ".netbook/.env".into());
+        state
+            .interpolator
+            .set_variable("token".to_string(), "abc".to_string());
+        state
+            .saved_variables
+            .insert("token".to_string(), "abc".to_string());
+
+        let sources: Vec<(String, String)> = state
+            .get_all_variables()
+            .into_iter()
+            .map(|e| (e.key, e.source.to_string()))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                ("PATH".to_string(), "process env".to_string()),
+                ("baseUrl".to_string(), ".netbook/.env".to_string()),
+                ("token".to_string(), "saved".to_string()),
+            ]
+        );
+
+        // Overriding an env file value keeps it in memory for this session
+        state.variables_selected_index = 1;
+        state.start_variable_edit(false);
+        state.variable_edit.as_mut().unwrap().value = "https://b".to_string();
+        state.commit_variable_edit().await.unwrap();
+        let selected = state.selected_variable().unwrap();
+        assert_eq!(selected.value, "https://b");
+        assert_eq!(selected.source, VariableSource::Session);
+
+        state.start_variable_edit(true);
+        state.variable_edit.as_mut().unwrap().key = "bad name".to_string();
+        state.commit_variable_edit().await.unwrap();
+        assert!(state.status_message.starts_with("Invalid variable name"));
+
+        // Deleting the override falls back to the env file value
+        state.delete_selected_variable().await.unwrap();
+        let entry = state.selected_variable().unwrap();
+        assert_eq!(entry.value, "https://a");
+        state.delete_selected_variable().await.unwrap();
+        assert!(state.status_message.contains("remove it there"));
+    }
+
+    #[test]
+    fn test_json_tree_navigation_and_extract() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = test_state(create_test_requests());
+        state.collection_path = dir.path().join("collection.json");
+        let request_id = state.collection[1].id;
+        state.set_response(Response {
+            id: uuid::Uuid::new_v4(),
+            request_id: Some(request_id),
+            status: 201,
+            headers: HashMap::new(),
+            body: serde_json::json!({"data": {"id": 7, "tags": ["a"]}, "ok": true}),
+            timing: crate::core::ResponseTiming::default(),
+            timestamp: chrono::Utc::now(),
+            sent: None,
+        });
+
+        // Only the root is open at first
+        assert_eq!(state.json_tree_nodes().len(), 3);
+        state.move_json_selection(1);
+        state.toggle_selected_json_node();
+        state.move_json_selection(1);
+        assert_eq!(
+            state.json_tree_state.selected_path.as_deref(),
+            Some("data.id")
+        );
+        assert_eq!(state.selected_json_variable_name(), "id");
+
+        state.save_selected_json_value("postId");
+        assert_eq!(
+            state.interpolator.get_variable("postId"),
+            Some("7".to_string())
+        );
+
+        state.add_extract_for_selected("postId");
+        let saved = crate::io::load_collection(&state.collection_path).unwrap();
+        assert_eq!(saved[1].extract["postId"], "data.id");
+
+        state.expand_all_json();
+        assert_eq!(state.json_tree_nodes().len(), 6);
+        state.collapse_all_json();
+        assert_eq!(state.json_tree_state.selected_path.as_deref(), Some("data"));
+        assert_eq!(state.json_tree_nodes().len(), 3);
+    }
+
+    #[test]
+    fn test_response_scrolling_and_search() {
+        let mut state = test_state(create_test_requests());
+        state.response_viewport.set((40, 3));
+        let items: Vec<_> = (0..10).map(|i| serde_json::json!({"id": i})).collect();
+        state.set_response(Response {
+            id: uuid::Uuid::new_v4(),
+            request_id: None,
+            status: 200,
+            headers: HashMap::new(),
+            body: serde_json::json!({"items": items, "next": "page-2"}),
+            timing: crate::core::ResponseTiming::default(),
+            timestamp: chrono::Utc::now(),
+            sent: None,
+        });
+
+        // Text tabs scroll by page and stop at the last full page
+        state.response_tab = ResponseTab::Raw;
+        let lines = state.response_text_lines().len();
+        state.page_response(true);
+        assert_eq!(state.response_scroll[1], 3);
+        state.scroll_response_to_end(true);
+        assert_eq!(state.response_scroll[1], lines - 3);
+
+        state.response_search = "\"ID\": 4".to_string();
+        state.search_response(false);
+        let line = state.response_scroll[1];
+        assert!(state.response_text_lines()[line].contains("\"id\": 4"));
+        assert!(state.status_message.starts_with("Match 1 of 1"));
+
+        // The tree search opens collapsed nodes to reveal matches
+        state.response_tab = ResponseTab::Pretty;
+        state.response_search = "id".to_string();
+        state.search_response(true);
+        assert_eq!(
+            state.json_tree_state.selected_path.as_deref(),
+            Some("items.0.id")
+        );
+        state.search_response(false);
+        assert_eq!(
+            state.json_tree_state.selected_path.as_deref(),
+            Some("items.9.id")
+        );
+        assert_eq!(state.response_scroll[0], state.selected_json_index() - 2);
+        assert!(state.status_message.starts_with("Match 10 of 10"));
+    }
+
+    #[test]
+    fn test_response_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = test_state(create_test_requests());
+        state.collection_path = dir.path().join("collection.json");
+        let response = Response {
+            id: uuid::Uuid::new_v4(),
+            request_id: Some(state.collection[0].id),
+            status: 200,
+            headers: HashMap::new(),
+            body: serde_json::json!({"items": [{"id": 1}, {"id": 2}, {"id": 3}]}),
+            timing: crate::core::ResponseTiming::default(),
+            timestamp: chrono::Utc::now(),
+            sent: None,
+        };
+        state.set_response(response.clone());
+
+        state.response_query = "$.items[?(@.id > 1)]".to_string();
+        state.apply_response_query();
+        assert_eq!(
+            state.response_body(),
+            Some(&serde_json::json!([{"id": 2}, {"id": 3}]))
+        );
+
+        // Values picked from the filtered view are extracted from the full body
+        state.move_json_selection(1);
+        state.toggle_selected_json_node();
+        state.move_json_selection(1);
+        state.add_extract_for_selected("secondId");
+        assert_eq!(
+            state.interpolator.get_variable("secondId"),
+            Some("2".to_string())
+        );
+        assert_eq!(
+            state.collection[0].extract["secondId"],
+            "[$.items[?(@.id > 1)]] | 0.id"
+        );
+
+        // The query is remembered for the next response to the same request
+        state.set_response(response);
+        assert_eq!(state.response_query, "$.items[?(@.id > 1)]");
+
+        state.response_query = "$.items[".to_string();
+        state.apply_response_query();
+        assert!(matches!(state.response_query_result, Some(Err(_))));
+        assert_eq!(state.response_body().unwrap()["items"][0]["id"], 1);
+    }
+
+    #[test]
+    fn test_response_format_from_content_type() {
+        let mut state = test_state(create_test_requests());
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Content-Type".to_string(),
+            "application/xml; charset=utf-8".to_string(),
+        );
+        state.set_response(Response {
+            id: uuid::Uuid::new_v4(),
+            request_id: None,
+            status: 200,
+            headers,
+            body: serde_json::Value::String("<a><b>1</b></a>".to_string()),
+            timing: crate::core::ResponseTiming::default(),
+            timestamp: chrono::Utc::now(),
+            sent: None,
+        });
+
+        assert_eq!(state.response_format, BodyFormat::Xml);
+        assert!(!state.shows_json_tree());
+        assert_eq!(
+            state.response_text_lines(),
+            vec!["<a>", "  <b>1</b>", "</a>"]
+        );
+
+        state.response_tab = ResponseTab::Raw;
+        assert_eq!(state.response_text_lines(), vec!["<a><b>1</b></a>"]);
+    }
+
+    #[test]
+    fn test_yank_text() {
+        let mut state = test_state(create_test_requests());
+        assert!(state.yank_text(&YankTarget::Body).is_err());
+        let (code, what) = state
+            .yank_text(&YankTarget::Code(CodeLanguage::Curl))
+            .unwrap();
+        assert!(code.starts_with("curl"));
+        assert_eq!(what, "'Get Users' as curl");
+
+        let mut headers = HashMap::new();
+        headers.insert("ETag".to_string(), "\"v1\"".to_string());
+        state.set_response(Response {
+            id: uuid::Uuid::new_v4(),
+            request_id: None,
+            status: 200,
+            headers,
+            body: serde_json::json!({"data": {"name": "Ann"}}),
+            timing: crate::core::ResponseTiming::default(),
+            timestamp: chrono::Utc::now(),
+            sent: None,
+        });
+
+        let yank = |state: &AppState, target| state.yank_text(&target).unwrap();
+        assert_eq!(
+            yank(&state, YankTarget::Body).0,
+            "{\n  \"data\": {\n    \"name\": \"Ann\"\n  }\n}"
+        );
+        assert_eq!(
+            yank(&state, YankTarget::Header("etag".to_string())),
+            ("\"v1\"".to_string(), "the ETag header".to_string())
+        );
+        assert!(
+            state
+                .yank_text(&YankTarget::Header("Age".to_string()))
+                .is_err()
+        );
+
+        state.expand_all_json();
+        state.move_json_selection(2);
+        assert_eq!(state.response_yank_target(), YankTarget::Value);
+        assert_eq!(
+            yank(&state, YankTarget::Value),
+            ("Ann".to_string(), "$.data.name".to_string())
+        );
+
+        state.response_tab = ResponseTab::Headers;
+        assert_eq!(state.response_yank_target(), YankTarget::Headers);
+
+        // Responses from before requests were recorded have no Request tab text
+        state.response_tab = ResponseTab::Request;
+        assert_eq!(state.response_yank_target(), YankTarget::Request);
+        assert!(state.yank_text(&YankTarget::Request).is_err());
+        let mut response = state.current_response.clone().unwrap();
+        response.sent = Some(
+            crate::core::SentRequest {
+                method: HttpMethod::Get,
+                url: "https://api.example.com/users".to_string(),
+                headers: HashMap::from([("Authorization".to_string(), "Bearer abc".to_string())]),
+                body: None,
+            }
+            .redacted(),
+        );
+        state.set_response(response);
+        assert_eq!(
+            state.response_text().unwrap().0,
+            "GET https://api.example.com/users\nAuthorization: Bearer ***\n"
+        );
+        assert_eq!(
+            yank(&state, YankTarget::Request).1,
+            "the sent request".to_string()
+        );
+    }
+
+    #[test]
+    fn test_save_response_parts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = test_state(create_test_requests());
+        state.open_save_response();
+        assert_eq!(state.mode, AppMode::Normal);
+
+        let mut headers = HashMap::new();
+        headers.insert("ETag".to_string(), "v1".to_string());
+        state.set_response(Response {
+            id: uuid::Uuid::new_v4(),
+            request_id: None,
+            status: 200,
+            headers,
+            body: serde_json::json!({"ok": true}),
+            timing: crate::core::ResponseTiming::default(),
+            timestamp: chrono::Utc::now(),
+            sent: None,
+        });
+
+        state.open_save_response();
+        assert_eq!(state.mode, AppMode::SaveResponse);
+        assert_eq!(state.name_input, "response.json");
+        state.cycle_save_part();
+        assert_eq!(
+            (state.save_part, state.name_input.as_str()),
+            (ResponsePart::Headers, "headers.txt")
+        );
+        // A typed path is kept
+        state.name_input = "out.json".to_string();
+        state.cycle_save_part();
+        assert_eq!(
+            (state.save_part, state.name_input.as_str()),
+            (ResponsePart::Full, "out.json")
+        );
+
+        let read = |part| {
+            let path = dir.path().join("out");
+            state.save_response(part, &path).unwrap();
+            std::fs::read_to_string(path).unwrap()
+        };
+        assert_eq!(read(ResponsePart::Body), "{\n  \"ok\": true\n}");
+        assert_eq!(read(ResponsePart::Headers), "ETag: v1");
+        let full: Response = serde_json::from_str(&read(ResponsePart::Full)).unwrap();
+        assert_eq!(full.status, 200);
+    }
+
+    #[test]
+    fn test_history_diff() {
+        let mut state = test_state(create_test_requests());
+        let response = |status, body| Response {
+            id: uuid::Uuid::new_v4(),
+            request_id: None,
+            status,
+            headers: HashMap::new(),
+            body,
+            timing: crate::core::ResponseTiming::default(),
+            timestamp: chrono::Utc::now(),
+            sent: None,
+        };
+        state.history.add_entry(
+            "Old".to_string(),
+            response(200, serde_json::json!({"n": 1})),
+        );
+        state.history.add_entry(
+            "New".to_string(),
+            response(500, serde_json::json!({"n": 2})),
+        );
+
+        // The modal lists the newest entry first
+        assert_eq!(state.selected_history_entry().unwrap().request_name, "New");
+        state.open_diff();
+        assert!(state.diff_view.is_none());
+
+        state.history_selected_index = 1;
+        state.toggle_history_mark();
+        state.history_selected_index = 0;
+        state.open_diff();
+        assert_eq!(state.mode, AppMode::Diff);
+        let view = state.diff_view.as_ref().unwrap();
+        assert!(view.old_label.contains("Old"));
+        assert_eq!(view.diff.status, Some((200, 500)));
+        assert_eq!(view.diff.body[0].path, "$.n");
+
+        // Without a mark the selected entry is compared with the response
+        state.history_mark = None;
+        state.set_response(response(500, serde_json::json!({"n": 2})));
+        state.open_diff();
+        let view = state.diff_view.as_ref().unwrap();
+        assert_eq!(view.new_label, "current response");
+        assert!(view.diff.is_empty());
+    }
+
+    #[test]
+    fn test_history_filter_and_detail() {
+        let mut state = test_state(create_test_requests());
+        let response = |status| Response {
+            id: uuid::Uuid::new_v4(),
+            request_id: None,
+            status,
+            headers: HashMap::from([("ETag".to_string(), "v1".to_string())]),
+            body: serde_json::json!({"n": 1}),
+            timing: crate::core::ResponseTiming::default(),
+            timestamp: chrono::Utc::now(),
+            sent: None,
+        };
+        let mut sent = state.collection[0].clone();
+        sent.url = "https://api.example.com/users".to_string();
+        let mut ran = response(200);
+        ran.sent = Some(crate::core::SentRequest::of(&sent));
+        state.history.push(HistoryEntry::sent(&sent, ran));
+        state
+            .history
+            .add_entry("Create Post".to_string(), response(404));
+        state
+            .history
+            .add_entry("Get Posts".to_string(), response(500));
+
+        state.update_history_filter("4xx 5xx".to_string());
+        assert_eq!(state.visible_history().len(), 2);
+        state.update_history_filter("get 5xx".to_string());
+        assert_eq!(state.visible_history().len(), 1);
+        assert_eq!(
+            state.selected_history_entry().unwrap().request_name,
+            "Get Posts"
+        );
+
+        // Text that doesn't parse keeps the last filter
+        state.update_history_filter("get 5xx since:yesterday".to_string());
+        assert!(state.history_filter_error.is_some());
+        assert_eq!(state.visible_history().len(), 1);
+
+        state.update_history_filter(sent.name.to_lowercase());
+        assert!(state.history_filter_error.is_none());
+        state.open_history_detail();
+        assert_eq!(state.mode, AppMode::HistoryDetail);
+        let lines = &state.history_detail.as_ref().unwrap().lines;
+        assert!(lines.contains(&DetailLine::Text(format!("GET {}", sent.full_url()))));
+        assert!(lines.contains(&DetailLine::Text("ETag: v1".to_string())));
+
+        // Older entries have no request to show
+        state.update_history_filter("create".to_string());
+        state.open_history_detail();
+        let lines = &state.history_detail.as_ref().unwrap().lines;
+        assert!(lines.contains(&DetailLine::Text(
+            "Not recorded for entries this old".to_string()
+        )));
+    }
 }
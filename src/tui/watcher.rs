@@ -0,0 +1,92 @@
+use crate::core::VariableInterpolator;
+use crate::tui::AppEvent;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Modification time and size of every watched file; `None` when missing
+pub type Fingerprint = Vec<(PathBuf, Option<(SystemTime, u64)>)>;
+
+/// The collection plus every env file the interpolator reads for it
+pub fn watched_paths(collection_path: &Path, environment: Option<&str>) -> Vec<PathBuf> {
+    let mut paths = vec![collection_path.to_path_buf()];
+    paths.extend(VariableInterpolator::env_file_paths(collection_path));
+    if let Some(environment) = environment {
+        paths.push(VariableInterpolator::environment_file_path(
+            collection_path,
+            environment,
+        ));
+    }
+    paths
+}
+
+pub fn fingerprint(paths: &[PathBuf]) -> Fingerprint {
+    let mut entries = Vec::new();
+    for path in paths {
+        collect(path, &mut entries);
+    }
+    entries
+}
+
+fn collect(path: &Path, entries: &mut Fingerprint) {
+    let metadata = std::fs::metadata(path).ok();
+
+    // Directory collections: watch every file below them
+    if metadata.as_ref().is_some_and(|m| m.is_dir()) {
+        let mut children: Vec<PathBuf> = std::fs::read_dir(path)
+            .map(|dir| dir.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default();
+        children.sort();
+        for child in children {
+            collect(&child, entries);
+        }
+        return;
+    }
+
+    let stamp = metadata.and_then(|m| Some((m.modified().ok()?, m.len())));
+    entries.push((path.to_path_buf(), stamp));
+}
+
+/// Polls the given paths and sends `AppEvent::FilesChanged` whenever one of
+/// them is created, modified or removed
+pub fn spawn_watcher(paths: Vec<PathBuf>, tx: mpsc::UnboundedSender<AppEvent>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last = fingerprint(&paths);
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let current = fingerprint(&paths);
+            if current != last {
+                last = current;
+                if tx.send(AppEvent::FilesChanged).is_err() {
+                    break;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_detects_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let collection = dir.path().join("requests");
+        std::fs::create_dir_all(&collection).unwrap();
+        std::fs::write(collection.join("a.yaml"), "name: A\n").unwrap();
+
+        let paths = watched_paths(&collection, None);
+        let before = fingerprint(&paths);
+
+        std::fs::write(collection.join("b.yaml"), "name: B\n").unwrap();
+        let added = fingerprint(&paths);
+        assert_ne!(before, added);
+
+        std::fs::write(dir.path().join(".env"), "token=abc\n").unwrap();
+        assert_ne!(added, fingerprint(&paths));
+    }
+}
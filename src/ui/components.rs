@@ -1,138 +1,283 @@
+use crate::ui::Theme;
 use ratatui::{
-    style::{Color, Style},
+    style::Style,
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
+use std::collections::HashSet;
 
-pub struct JsonTreeComponent {
-    pub expanded_paths: std::collections::HashSet<String>,
+/// One row of the JSON tree: a value and where it sits in the document
+pub struct JsonNode<'a> {
+    /// Dotted path from the root, with array indices as segments
+    /// (`data.items.0.id`); the root is the empty path
+    pub path: String,
+    pub depth: usize,
+    /// Object key or array index this value is stored under
+    pub key: Option<String>,
+    /// Whether `key` is an array index
+    pub in_array: bool,
+    pub value: &'a serde_json::Value,
 }
 
-impl JsonTreeComponent {
-    pub fn new() -> Self {
-        Self {
-            expanded_paths: std::collections::HashSet::new(),
+impl JsonNode<'_> {
+    pub fn is_container(&self) -> bool {
+        matches!(
+            self.value,
+            serde_json::Value::Object(_) | serde_json::Value::Array(_)
+        )
+    }
+
+    /// Whether the key or scalar value contains `needle`, ignoring case
+    pub fn matches(&self, needle: &str) -> bool {
+        let needle = needle.to_lowercase();
+        let value = match self.value {
+            serde_json::Value::String(s) => s.clone(),
+            _ if self.is_container() => String::new(),
+            value => value.to_string(),
+        };
+        self.key
+            .iter()
+            .chain(std::iter::once(&value))
+            .any(|text| text.to_lowercase().contains(&needle))
+    }
+}
+
+/// Path of the child stored under `key` (an object key or array index)
+pub fn json_child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+/// Nodes shown when only the objects and arrays in `expanded` are open, in
+/// display order
+pub fn visible_json_nodes<'a>(
+    json: &'a serde_json::Value,
+    expanded: &HashSet<String>,
+) -> Vec<JsonNode<'a>> {
+    fn visit<'a>(node: JsonNode<'a>, expanded: &HashSet<String>, nodes: &mut Vec<JsonNode<'a>>) {
+        let (path, depth, value) = (node.path.clone(), node.depth, node.value);
+        nodes.push(node);
+        if !expanded.contains(&path) {
+            return;
+        }
+        let in_array = value.is_array();
+        let children: Vec<(String, &serde_json::Value)> = match value {
+            serde_json::Value::Object(obj) => obj.iter().map(|(k, v)| (k.clone(), v)).collect(),
+            serde_json::Value::Array(arr) => arr
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i.to_string(), v))
+                .collect(),
+            _ => return,
+        };
+        for (key, value) in children {
+            let child = JsonNode {
+                path: json_child_path(&path, &key),
+                depth: depth + 1,
+                key: Some(key),
+                in_array,
+                value,
+            };
+            visit(child, expanded, nodes);
         }
     }
 
-    pub fn render_json_tree(
-        &self,
-        json: &serde_json::Value,
-        path: &str,
-        depth: usize,
-    ) -> Vec<Line> {
-        let mut lines = Vec::new();
-        let indent = "  ".repeat(depth);
+    let mut nodes = Vec::new();
+    let root = JsonNode {
+        path: String::new(),
+        depth: 0,
+        key: None,
+        in_array: false,
+        value: json,
+    };
+    visit(root, expanded, &mut nodes);
+    nodes
+}
 
+/// Paths of every object and array in `json`, i.e. the fully expanded tree
+pub fn json_container_paths(json: &serde_json::Value) -> HashSet<String> {
+    fn visit(json: &serde_json::Value, path: String, paths: &mut HashSet<String>) {
         match json {
             serde_json::Value::Object(obj) => {
-                let is_expanded = self.expanded_paths.contains(path);
-                let icon = if is_expanded { "▼" } else { "▶" };
-
-                lines.push(Line::from(vec![
-                    Span::raw(indent.clone()),
-                    Span::styled(icon, Style::default().fg(Color::Blue)),
-                    Span::raw(" {"),
-                ]));
-
-                if is_expanded {
-                    for (key, value) in obj {
-                        let child_path = if path.is_empty() {
-                            key.clone()
-                        } else {
-                            format!("{}.{}", path, key)
-                        };
-
-                        lines.push(Line::from(vec![
-                            Span::raw(format!("{}  ", &indent)),
-                            Span::styled(format!("\"{}\"", key), Style::default().fg(Color::Cyan)),
-                            Span::raw(": "),
-                        ]));
-
-                        let mut child_lines = self.render_json_tree(value, &child_path, depth + 1);
-                        lines.append(&mut child_lines);
-                    }
-                    lines.push(Line::from(format!("{}}}", &indent)));
+                for (key, value) in obj {
+                    visit(value, json_child_path(&path, key), paths);
                 }
             }
             serde_json::Value::Array(arr) => {
-                let is_expanded = self.expanded_paths.contains(path);
-                let icon = if is_expanded { "▼" } else { "▶" };
-
-                lines.push(Line::from(vec![
-                    Span::raw(indent.clone()),
-                    Span::styled(icon, Style::default().fg(Color::Blue)),
-                    Span::raw(format!(" [{} items]", arr.len())),
-                ]));
-
-                if is_expanded {
-                    for (index, value) in arr.iter().enumerate() {
-                        let child_path = if path.is_empty() {
-                            index.to_string()
-                        } else {
-                            format!("{}[{}]", path, index)
-                        };
-
-                        lines.push(Line::from(vec![
-                            Span::raw(format!("{}  ", &indent)),
-                            Span::styled(
-                                format!("[{}]", index),
-                                Style::default().fg(Color::Yellow),
-                            ),
-                            Span::raw(": "),
-                        ]));
-
-                        let mut child_lines = self.render_json_tree(value, &child_path, depth + 1);
-                        lines.append(&mut child_lines);
-                    }
-                    lines.push(Line::from(format!("{}]", &indent)));
+                for (index, value) in arr.iter().enumerate() {
+                    visit(value, json_child_path(&path, &index.to_string()), paths);
                 }
             }
+            _ => return,
+        }
+        paths.insert(path);
+    }
+
+    let mut paths = HashSet::new();
+    visit(json, String::new(), &mut paths);
+    paths
+}
+
+/// Collapsible view of a JSON document, one node per line
+pub struct JsonTreeComponent<'a> {
+    pub expanded_paths: &'a HashSet<String>,
+    pub selected_path: Option<&'a str>,
+    /// Text to highlight in keys and values
+    pub search: Option<&'a str>,
+    pub theme: &'a Theme,
+}
+
+impl<'a> JsonTreeComponent<'a> {
+    pub fn new(
+        expanded_paths: &'a HashSet<String>,
+        selected_path: Option<&'a str>,
+        theme: &'a Theme,
+    ) -> Self {
+        Self {
+            expanded_paths,
+            selected_path,
+            search: None,
+            theme,
+        }
+    }
+
+    pub fn with_search(mut self, search: &'a str) -> Self {
+        self.search = Some(search).filter(|s| !s.is_empty());
+        self
+    }
+
+    pub fn render_json_tree(&self, json: &serde_json::Value) -> Vec<Line<'static>> {
+        self.render_nodes(&visible_json_nodes(json, self.expanded_paths))
+    }
+
+    /// Renders a slice of `visible_json_nodes`, e.g. the rows that fit on
+    /// screen
+    pub fn render_nodes(&self, nodes: &[JsonNode]) -> Vec<Line<'static>> {
+        nodes.iter().map(|node| self.render_node(node)).collect()
+    }
+
+    fn render_node(&self, node: &JsonNode) -> Line<'static> {
+        let theme = self.theme;
+        let mut spans = vec![Span::raw("  ".repeat(node.depth))];
+
+        if node.is_container() {
+            let icon = if self.expanded_paths.contains(&node.path) {
+                "▼ "
+            } else {
+                "▶ "
+            };
+            spans.push(Span::styled(icon, theme.fg(theme.syntax_tag)));
+        } else {
+            spans.push(Span::raw("  "));
+        }
+
+        if let Some(key) = &node.key {
+            let label = if node.in_array {
+                format!("[{}]", key)
+            } else {
+                format!("\"{}\"", key)
+            };
+            spans.extend(highlight_matches(
+                label,
+                theme.fg(theme.syntax_key),
+                self.search,
+                theme,
+            ));
+            spans.push(Span::raw(": "));
+        }
+
+        let value = match node.value {
+            serde_json::Value::Object(obj) => {
+                Span::styled(format!("{{}} {} keys", obj.len()), theme.fg(theme.muted))
+            }
+            serde_json::Value::Array(arr) => {
+                Span::styled(format!("[] {} items", arr.len()), theme.fg(theme.muted))
+            }
             serde_json::Value::String(s) => {
-                lines.push(Line::from(vec![
-                    Span::raw(indent.clone()),
-                    Span::styled(format!("\"{}\"", s), Style::default().fg(Color::Green)),
-                ]));
+                Span::styled(format!("\"{}\"", s), theme.fg(theme.syntax_string))
             }
             serde_json::Value::Number(n) => {
-                lines.push(Line::from(vec![
-                    Span::raw(indent.clone()),
-                    Span::styled(n.to_string(), Style::default().fg(Color::Magenta)),
-                ]));
+                Span::styled(n.to_string(), theme.fg(theme.syntax_number))
             }
             serde_json::Value::Bool(b) => {
-                lines.push(Line::from(vec![
-                    Span::raw(indent.clone()),
-                    Span::styled(b.to_string(), Style::default().fg(Color::Red)),
-                ]));
-            }
-            serde_json::Value::Null => {
-                lines.push(Line::from(vec![
-                    Span::raw(indent),
-                    Span::styled("null", Style::default().fg(Color::DarkGray)),
-                ]));
+                Span::styled(b.to_string(), theme.fg(theme.syntax_keyword))
             }
+            serde_json::Value::Null => Span::styled("null", theme.fg(theme.muted)),
+        };
+        if node.is_container() {
+            spans.push(value);
+        } else {
+            spans.extend(highlight_matches(
+                value.content.into_owned(),
+                value.style,
+                self.search,
+                theme,
+            ));
+        }
+
+        let line = Line::from(spans);
+        if self.selected_path == Some(node.path.as_str()) {
+            line.style(theme.selection())
+        } else {
+            line
         }
+    }
+}
 
-        lines
+/// Splits `text` into spans, marking case-insensitive occurrences of
+/// `needle` on top of `style`
+pub fn highlight_matches(
+    text: String,
+    style: Style,
+    needle: Option<&str>,
+    theme: &Theme,
+) -> Vec<Span<'static>> {
+    let Some(needle) = needle.filter(|n| !n.is_empty()) else {
+        return vec![Span::styled(text, style)];
+    };
+    let lower = text.to_lowercase();
+    let needle = needle.to_lowercase();
+    // Lowercasing can change byte lengths; fall back to no highlighting
+    if lower.len() != text.len() {
+        return vec![Span::styled(text, style)];
     }
 
-    pub fn toggle_path(&mut self, path: &str) {
-        if self.expanded_paths.contains(path) {
-            self.expanded_paths.remove(path);
-        } else {
-            self.expanded_paths.insert(path.to_string());
+    let match_style = style.patch(theme.search_match());
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (index, _) in lower.match_indices(&needle) {
+        if index > start {
+            spans.push(Span::styled(text[start..index].to_string(), style));
         }
+        let end = index + needle.len();
+        spans.push(Span::styled(text[index..end].to_string(), match_style));
+        start = end;
+    }
+    if start < text.len() || spans.is_empty() {
+        spans.push(Span::styled(text[start..].to_string(), style));
     }
+    spans
 }
 
-impl Default for JsonTreeComponent {
-    fn default() -> Self {
-        Self::new()
+/// Hard-wraps each line of `text` at `width` characters, so the result can
+/// be scrolled and windowed line by line
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(chars.chunks(width).map(|chunk| chunk.iter().collect()));
     }
+    lines
 }
 
-pub fn create_loading_widget(message: &str) -> Paragraph {
+pub fn create_loading_widget<'a>(message: &'a str, theme: &Theme) -> Paragraph<'a> {
     let spinner_chars = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
     let spinner_index = (std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -144,7 +289,7 @@ pub fn create_loading_widget(message: &str) -> Paragraph {
     let content = vec![Line::from(vec![
         Span::styled(
             spinner_chars[spinner_index as usize].to_string(),
-            Style::default().fg(Color::Yellow),
+            theme.fg(theme.focus),
         ),
         Span::raw(" "),
         Span::raw(message),
@@ -152,7 +297,7 @@ pub fn create_loading_widget(message: &str) -> Paragraph {
 
     Paragraph::new(content)
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Yellow))
+        .style(theme.fg(theme.focus))
 }
 
 pub fn format_duration(millis: u64) -> String {
@@ -217,16 +362,47 @@ mod tests {
         assert_eq!(truncate_text("hi", 5), "hi");
     }
 
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(wrap_text("abcdef\n\nxy", 4), ["abcd", "ef", "", "xy"]);
+        assert!(wrap_text("", 4).is_empty());
+    }
+
+    #[test]
+    fn test_highlight_matches() {
+        let theme = Theme::default();
+        let spans = highlight_matches(
+            "Token: tok".to_string(),
+            Style::default(),
+            Some("TOK"),
+            &theme,
+        );
+        let texts: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(texts, ["Tok", "en: ", "tok"]);
+        assert_eq!(spans[0].style.bg, Some(theme.search_bg));
+    }
+
     #[test]
     fn test_json_tree_component() {
-        let mut tree = JsonTreeComponent::new();
-        let json = serde_json::json!({"name": "test", "value": 42});
+        let json = serde_json::json!({"name": "test", "items": [1, {"id": 2}]});
+        let mut expanded = HashSet::new();
+
+        let theme = Theme::default();
+        let collapsed = JsonTreeComponent::new(&expanded, None, &theme).render_json_tree(&json);
+        assert_eq!(collapsed.len(), 1);
 
-        let lines_len = tree.render_json_tree(&json, "", 0).len();
-        assert!(lines_len > 0);
+        expanded.insert(String::new());
+        let tree = JsonTreeComponent::new(&expanded, None, &theme);
+        assert_eq!(tree.render_json_tree(&json).len(), 3);
 
-        tree.toggle_path("");
-        let expanded_lines_len = tree.render_json_tree(&json, "", 0).len();
-        assert!(expanded_lines_len > lines_len);
+        let all = json_container_paths(&json);
+        let paths: Vec<String> = visible_json_nodes(&json, &all)
+            .into_iter()
+            .map(|node| node.path)
+            .collect();
+        assert_eq!(
+            paths,
+            ["", "items", "items.0", "items.1", "items.1.id", "name"]
+        );
     }
 }
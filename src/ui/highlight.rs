@@ -0,0 +1,492 @@
+use crate::ui::Theme;
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+use std::collections::HashMap;
+
+/// How a body is formatted and colored, chosen from its Content-Type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFormat {
+    Json,
+    Xml,
+    Html,
+    Yaml,
+    Text,
+}
+
+impl BodyFormat {
+    /// File extension for a body in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            BodyFormat::Json => "json",
+            BodyFormat::Xml => "xml",
+            BodyFormat::Html => "html",
+            BodyFormat::Yaml => "yaml",
+            BodyFormat::Text => "txt",
+        }
+    }
+
+    /// Format for a body with the given headers, falling back to sniffing
+    /// the text when there is no useful Content-Type
+    pub fn detect(headers: &HashMap<String, String>, text: &str) -> Self {
+        let content_type = headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.to_lowercase())
+            .unwrap_or_default();
+        let mime = content_type.split(';').next().unwrap_or("").trim();
+
+        match mime {
+            m if m.ends_with("json") => BodyFormat::Json,
+            "text/html" | "application/xhtml+xml" => BodyFormat::Html,
+            m if m.ends_with("xml") => BodyFormat::Xml,
+            m if m.ends_with("yaml") || m.ends_with("yml") => BodyFormat::Yaml,
+            _ => Self::sniff(text),
+        }
+    }
+
+    fn sniff(text: &str) -> Self {
+        let start = text.trim_start();
+        let lower = start.get(..15).unwrap_or(start).to_lowercase();
+        if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+            BodyFormat::Html
+        } else if start.starts_with('<') {
+            BodyFormat::Xml
+        } else if (start.starts_with('{') || start.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(start).is_ok()
+        {
+            BodyFormat::Json
+        } else {
+            BodyFormat::Text
+        }
+    }
+
+    /// `text` re-indented for reading; returned unchanged when it can't be
+    /// parsed
+    pub fn pretty_print(self, text: &str) -> String {
+        match self {
+            BodyFormat::Json => serde_json::from_str::<serde_json::Value>(text)
+                .ok()
+                .and_then(|value| serde_json::to_string_pretty(&value).ok())
+                .unwrap_or_else(|| text.to_string()),
+            BodyFormat::Xml => indent_markup(text, false),
+            BodyFormat::Html => indent_markup(text, true),
+            BodyFormat::Yaml | BodyFormat::Text => text.to_string(),
+        }
+    }
+
+    /// Colors one line of a body in this format
+    pub fn highlight_line(self, line: &str, theme: &Theme) -> Line<'static> {
+        let spans = match self {
+            BodyFormat::Json => highlight_json(line, theme),
+            BodyFormat::Xml | BodyFormat::Html => highlight_markup(line, theme),
+            BodyFormat::Yaml => highlight_yaml(line, theme),
+            BodyFormat::Text => vec![Span::raw(line.to_string())],
+        };
+        Line::from(spans)
+    }
+}
+
+/// Highlights the rows `offset..offset + height` of `text` hard-wrapped at
+/// `width` (as `wrap_text` does). Only the source lines in that window are
+/// highlighted, and each is highlighted whole before being cut so tokens
+/// keep their color across the wrap.
+pub fn highlight_window(
+    text: &str,
+    format: BodyFormat,
+    theme: &Theme,
+    width: usize,
+    offset: usize,
+    height: usize,
+) -> Vec<Line<'static>> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut row = 0;
+    for line in text.lines() {
+        if rows.len() >= height {
+            break;
+        }
+        let count = line.chars().count().div_ceil(width).max(1);
+        if row + count > offset {
+            let wrapped = split_line(format.highlight_line(line, theme), width);
+            let skip = offset.saturating_sub(row);
+            rows.extend(wrapped.into_iter().skip(skip).take(height - rows.len()));
+        }
+        row += count;
+    }
+    rows
+}
+
+/// Cuts a styled line into rows of `width` characters
+fn split_line(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let mut rows = vec![Vec::new()];
+    let mut used = 0;
+    for span in line.spans {
+        let mut text = span.content.as_ref();
+        while !text.is_empty() {
+            if used == width {
+                rows.push(Vec::new());
+                used = 0;
+            }
+            let take = text
+                .char_indices()
+                .nth(width - used)
+                .map_or(text.len(), |(i, _)| i);
+            let (head, tail) = text.split_at(take);
+            used += head.chars().count();
+            rows.last_mut()
+                .unwrap()
+                .push(Span::styled(head.to_string(), span.style));
+            text = tail;
+        }
+    }
+    rows.into_iter().map(Line::from).collect()
+}
+
+fn key_style(theme: &Theme) -> Style {
+    theme.fg(theme.syntax_key)
+}
+
+fn string_style(theme: &Theme) -> Style {
+    theme.fg(theme.syntax_string)
+}
+
+fn number_style(theme: &Theme) -> Style {
+    theme.fg(theme.syntax_number)
+}
+
+fn keyword_style(theme: &Theme) -> Style {
+    theme.fg(theme.syntax_keyword)
+}
+
+fn muted_style(theme: &Theme) -> Style {
+    theme.fg(theme.muted)
+}
+
+fn tag_style(theme: &Theme) -> Style {
+    theme.fg(theme.syntax_tag)
+}
+
+fn highlight_json(line: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let (length, style) = match c {
+            '"' => {
+                let length = string_end(rest);
+                let is_key = rest[length..].trim_start().starts_with(':');
+                (
+                    length,
+                    if is_key {
+                        key_style(theme)
+                    } else {
+                        string_style(theme)
+                    },
+                )
+            }
+            '-' | '0'..='9' => (
+                rest.find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+                    .unwrap_or(rest.len()),
+                number_style(theme),
+            ),
+            c if c.is_alphabetic() => {
+                let length = rest
+                    .find(|c: char| !c.is_alphanumeric())
+                    .unwrap_or(rest.len());
+                let style = match &rest[..length] {
+                    "true" | "false" => keyword_style(theme),
+                    "null" => muted_style(theme),
+                    _ => Style::default(),
+                };
+                (length, style)
+            }
+            c if c.is_whitespace() => (whitespace_end(rest), Style::default()),
+            c => (c.len_utf8(), Style::default()),
+        };
+        spans.push(Span::styled(rest[..length].to_string(), style));
+        rest = &rest[length..];
+    }
+    spans
+}
+
+fn whitespace_end(text: &str) -> usize {
+    text.find(|c: char| !c.is_whitespace())
+        .unwrap_or(text.len())
+}
+
+/// Length of the quoted string at the start of `text`, including quotes
+fn string_end(text: &str) -> usize {
+    let quote = text.chars().next().unwrap_or('"');
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return index + c.len_utf8(),
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+fn highlight_markup(line: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(rest.len(), |i| i + 3);
+            spans.push(Span::styled(rest[..end].to_string(), muted_style(theme)));
+            rest = &rest[end..];
+        } else if rest.starts_with('<') {
+            let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            spans.extend(highlight_tag(&rest[..end], theme));
+            rest = &rest[end..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            spans.push(Span::raw(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+    }
+    spans
+}
+
+/// `<name attr="value">`: the name in tag color, attributes as keys and
+/// their values as strings
+fn highlight_tag(tag: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let start = if tag.starts_with("</") { 2 } else { 1 };
+    let name_end = tag[start..]
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .map_or(tag.len(), |i| i + start);
+    let mut spans = vec![Span::styled(tag[..name_end].to_string(), tag_style(theme))];
+
+    let mut rest = &tag[name_end..];
+    while let Some(c) = rest.chars().next() {
+        let (length, style) = match c {
+            '"' | '\'' => (string_end(rest), string_style(theme)),
+            '/' | '>' | '?' => (c.len_utf8(), tag_style(theme)),
+            c if c.is_whitespace() => (whitespace_end(rest), Style::default()),
+            '=' => (1, Style::default()),
+            _ => (
+                rest.find(|c: char| c.is_whitespace() || "=/>".contains(c))
+                    .unwrap_or(rest.len()),
+                key_style(theme),
+            ),
+        };
+        spans.push(Span::styled(rest[..length].to_string(), style));
+        rest = &rest[length..];
+    }
+    spans
+}
+
+fn highlight_yaml(line: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let mut spans = vec![Span::raw(indent.to_string())];
+
+    if trimmed.starts_with('#') {
+        spans.push(Span::styled(trimmed.to_string(), muted_style(theme)));
+        return spans;
+    }
+
+    let mut rest = trimmed;
+    if let Some(item) = rest.strip_prefix("- ") {
+        spans.push(Span::styled("- ", tag_style(theme)));
+        rest = item;
+    }
+    if let Some((key, value)) = rest.split_once(':')
+        && (value.is_empty() || value.starts_with(' '))
+        && !key.starts_with(['"', '\''])
+    {
+        spans.push(Span::styled(key.to_string(), key_style(theme)));
+        spans.push(Span::raw(":"));
+        rest = value;
+    }
+
+    let value = rest.trim();
+    let style = match value {
+        "" => Style::default(),
+        "true" | "false" | "yes" | "no" => keyword_style(theme),
+        "null" | "~" => muted_style(theme),
+        v if v.parse::<f64>().is_ok() => number_style(theme),
+        v if v.starts_with('#') => muted_style(theme),
+        _ => string_style(theme),
+    };
+    spans.push(Span::styled(rest.to_string(), style));
+    spans
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Puts each tag on its own line, indented by nesting depth. An element
+/// holding only text stays on one line, and the contents of `<script>` and
+/// `<style>` are kept as they are.
+fn indent_markup(text: &str, html: bool) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut depth = 0usize;
+    let mut rest = text.trim();
+    let indent = |depth: usize| "  ".repeat(depth);
+
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let content = rest[..end].trim();
+            if !content.is_empty() {
+                lines.push(format!("{}{}", indent(depth), content));
+            }
+            rest = &rest[end..];
+            continue;
+        }
+
+        let end = if rest.starts_with("<!--") {
+            rest.find("-->").map(|i| i + 3)
+        } else if rest.starts_with("<![CDATA[") {
+            rest.find("]]>").map(|i| i + 3)
+        } else {
+            rest.find('>').map(|i| i + 1)
+        };
+        let Some(end) = end else {
+            // Unterminated tag: keep the remainder as it is
+            lines.push(format!("{}{}", indent(depth), rest));
+            break;
+        };
+        let tag = &rest[..end];
+        rest = &rest[end..];
+
+        let name = tag
+            .trim_start_matches(['<', '/'])
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        let is_special = tag.starts_with("<!") || tag.starts_with("<?");
+        let is_void = tag.ends_with("/>") || (html && VOID_ELEMENTS.contains(&name.as_str()));
+
+        if tag.starts_with("</") {
+            depth = depth.saturating_sub(1);
+            lines.push(format!("{}{}", indent(depth), tag));
+        } else if is_special || is_void {
+            lines.push(format!("{}{}", indent(depth), tag));
+        } else if html && (name == "script" || name == "style") {
+            let close = format!("</{}", name);
+            let body_end = rest.to_lowercase().find(&close).unwrap_or(rest.len());
+            lines.push(format!("{}{}", indent(depth), tag));
+            for line in rest[..body_end].lines().filter(|l| !l.trim().is_empty()) {
+                lines.push(format!("{}{}", indent(depth + 1), line.trim()));
+            }
+            rest = &rest[body_end..];
+            depth += 1;
+        } else {
+            // `<a>text</a>` stays on one line
+            let text_end = rest.find('<').unwrap_or(rest.len());
+            let closing = format!("</{}>", name);
+            if rest[text_end..].to_lowercase().starts_with(&closing) {
+                let close_end = text_end + closing.len();
+                lines.push(format!(
+                    "{}{}{}{}",
+                    indent(depth),
+                    tag,
+                    rest[..text_end].trim(),
+                    &rest[text_end..close_end]
+                ));
+                rest = &rest[close_end..];
+            } else {
+                lines.push(format!("{}{}", indent(depth), tag));
+                depth += 1;
+            }
+        }
+        rest = rest.trim_start();
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(line: &Line) -> Vec<String> {
+        line.spans.iter().map(|s| s.content.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let headers =
+            |value: &str| HashMap::from([("content-type".to_string(), value.to_string())]);
+        assert_eq!(
+            BodyFormat::detect(&headers("application/problem+json"), ""),
+            BodyFormat::Json
+        );
+        assert_eq!(
+            BodyFormat::detect(&headers("text/html; charset=utf-8"), ""),
+            BodyFormat::Html
+        );
+        assert_eq!(
+            BodyFormat::detect(&headers("application/atom+xml"), ""),
+            BodyFormat::Xml
+        );
+        assert_eq!(
+            BodyFormat::detect(&headers("application/x-yaml"), ""),
+            BodyFormat::Yaml
+        );
+        assert_eq!(
+            BodyFormat::detect(&HashMap::new(), "<?xml version=\"1.0\"?><a/>"),
+            BodyFormat::Xml
+        );
+        assert_eq!(
+            BodyFormat::detect(&HashMap::new(), "plain"),
+            BodyFormat::Text
+        );
+    }
+
+    #[test]
+    fn test_pretty_print_markup() {
+        let xml = "<?xml version=\"1.0\"?><feed><title>News</title><entry id=\"1\"><link href=\"/a\"/></entry></feed>";
+        assert_eq!(
+            BodyFormat::Xml.pretty_print(xml),
+            "<?xml version=\"1.0\"?>\n<feed>\n  <title>News</title>\n  <entry id=\"1\">\n    <link href=\"/a\"/>\n  </entry>\n</feed>"
+        );
+
+        let html = "<html><head><meta charset=\"utf-8\"><script>if (a < b) { go(); }</script></head><body><p>Hi<br>there</p></body></html>";
+        assert_eq!(
+            BodyFormat::Html.pretty_print(html),
+            "<html>\n  <head>\n    <meta charset=\"utf-8\">\n    <script>\n      if (a < b) { go(); }\n    </script>\n  </head>\n  <body>\n    <p>\n      Hi\n      <br>\n      there\n    </p>\n  </body>\n</html>"
+        );
+    }
+
+    #[test]
+    fn test_highlight_lines() {
+        let theme = Theme::default();
+        let line = BodyFormat::Json.highlight_line(r#"  "id": 12, "ok": true"#, &theme);
+        assert_eq!(
+            texts(&line),
+            [
+                "  ", "\"id\"", ":", " ", "12", ",", " ", "\"ok\"", ":", " ", "true"
+            ]
+        );
+        assert_eq!(line.spans[1].style, key_style(&theme));
+        assert_eq!(line.spans[4].style, number_style(&theme));
+
+        let line = BodyFormat::Xml.highlight_line(r#"<a href="/x">link</a>"#, &theme);
+        assert_eq!(
+            texts(&line),
+            ["<a", " ", "href", "=", "\"/x\"", ">", "link", "</a", ">"]
+        );
+        assert_eq!(line.spans[4].style, string_style(&theme));
+
+        let line = BodyFormat::Yaml.highlight_line("  - name: Jane # admin", &theme);
+        assert_eq!(texts(&line), ["  ", "- ", "name", ":", " Jane # admin"]);
+    }
+
+    #[test]
+    fn test_highlight_window_wraps_like_wrap_text() {
+        let text = "{\n  \"message\": \"abcdefgh\"\n}";
+        let rows = highlight_window(text, BodyFormat::Json, &Theme::default(), 10, 1, 2);
+        let rows: Vec<String> = rows.iter().map(|l| texts(l).concat()).collect();
+        assert_eq!(rows, ["  \"message", "\": \"abcdef"]);
+        assert_eq!(rows.len(), crate::ui::wrap_text(text, 10)[1..3].len());
+    }
+}
@@ -1,9 +1,16 @@
-use crate::tui::{AppMode, AppState, ResponseTab};
+use crate::core::RequestBody;
+use crate::tui::{
+    Action, AppMode, AppState, COMMANDS, DetailLine, FormField, RequestForm, ResponseTab,
+};
+use crate::ui::components::{JsonTreeComponent, highlight_matches};
+use crate::ui::highlight::{BodyFormat, highlight_window};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    layout::{Constraint, Direction, Layout, Position, Rect},
+    style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row, Table, Tabs, Wrap,
+    },
 };
 
 pub struct MainLayout {
@@ -13,6 +20,63 @@ pub struct MainLayout {
     pub status: Rect,
 }
 
+/// Titles of the response tabs, in `ResponseTab` order
+pub const RESPONSE_TABS: [&str; 5] = ["Pretty", "Raw", "Headers", "Timeline", "Request"];
+
+/// Where the clickable widgets were drawn in the last frame, for mouse
+/// hit-testing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScreenAreas {
+    pub requests: Rect,
+    /// First request row shown in the list
+    pub requests_offset: usize,
+    pub response_tabs: Rect,
+    pub response: Rect,
+    pub history: Rect,
+    pub history_offset: usize,
+}
+
+impl ScreenAreas {
+    /// Index in the filtered list of the request row at `position`
+    pub fn request_at(&self, position: Position) -> Option<usize> {
+        list_row_at(self.requests, self.requests_offset, position)
+    }
+
+    pub fn history_at(&self, position: Position) -> Option<usize> {
+        list_row_at(self.history, self.history_offset, position)
+    }
+
+    pub fn response_tab_at(&self, position: Position) -> Option<usize> {
+        let inner = inner_area(self.response_tabs);
+        if position.y != inner.y || !inner.contains(position) {
+            return None;
+        }
+        // Each title is padded by a space on both sides, with a one
+        // column divider between tabs
+        let mut start = inner.x;
+        for (i, title) in RESPONSE_TABS.iter().enumerate() {
+            let end = start + title.chars().count() as u16 + 2;
+            if (start..end).contains(&position.x) {
+                return Some(i);
+            }
+            start = end + 1;
+        }
+        None
+    }
+}
+
+/// Row of a bordered one-line-per-item list at `position`
+fn list_row_at(area: Rect, offset: usize, position: Position) -> Option<usize> {
+    let inner = inner_area(area);
+    inner
+        .contains(position)
+        .then(|| offset + (position.y - inner.y) as usize)
+}
+
+fn inner_area(area: Rect) -> Rect {
+    Block::default().borders(Borders::ALL).inner(area)
+}
+
 pub fn create_main_layout(area: Rect) -> MainLayout {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -37,6 +101,7 @@ pub fn create_main_layout(area: Rect) -> MainLayout {
 }
 
 pub fn render_request_list(frame: &mut ratatui::Frame, area: Rect, state: &AppState) {
+    let theme = &state.theme;
     let filtered_requests = state.get_filtered_requests();
 
     let items: Vec<ListItem> = filtered_requests
@@ -44,24 +109,15 @@ pub fn render_request_list(frame: &mut ratatui::Frame, area: Rect, state: &AppSt
         .enumerate()
         .map(|(i, request)| {
             let style = if i == state.selected_request_index {
-                Style::default().bg(Color::Blue).fg(Color::White)
+                theme.selection()
             } else {
                 Style::default()
             };
 
-            let method_color = match request.method {
-                crate::core::HttpMethod::Get => Color::Green,
-                crate::core::HttpMethod::Post => Color::Yellow,
-                crate::core::HttpMethod::Put => Color::Blue,
-                crate::core::HttpMethod::Patch => Color::Magenta,
-                crate::core::HttpMethod::Delete => Color::Red,
-                _ => Color::White,
-            };
-
             ListItem::new(vec![Line::from(vec![
                 Span::styled(
                     format!("{:<7}", request.method),
-                    Style::default().fg(method_color),
+                    theme.fg(theme.method(&request.method)),
                 ),
                 Span::raw(" "),
                 Span::raw(&request.name),
@@ -94,76 +150,107 @@ pub fn render_request_list(frame: &mut ratatui::Frame, area: Rect, state: &AppSt
                 .title(block_title)
                 .borders(Borders::ALL)
                 .border_style(if matches!(state.mode, AppMode::Filter) {
-                    Style::default().fg(Color::Yellow)
+                    theme.fg(theme.focus)
                 } else {
                     Style::default()
                 }),
         )
-        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White));
+        .highlight_style(theme.selection());
 
     frame.render_stateful_widget(list, area, &mut list_state);
+
+    let mut areas = state.screen_areas.get();
+    areas.requests = area;
+    areas.requests_offset = list_state.offset();
+    state.screen_areas.set(areas);
 }
 
 pub fn render_request_details(frame: &mut ratatui::Frame, area: Rect, state: &AppState) {
+    let theme = &state.theme;
     if let Some(request) = state.get_current_request() {
         let interpolated = state.interpolator.interpolate_request(request);
+        let label = theme.fg(theme.accent);
 
         let mut content = vec![
             Line::from(vec![
-                Span::styled("Method: ", Style::default().fg(Color::Cyan)),
-                Span::raw(interpolated.method.to_string()),
+                Span::styled("Method: ", label),
+                Span::styled(
+                    interpolated.method.to_string(),
+                    theme.fg(theme.method(&interpolated.method)),
+                ),
             ]),
             Line::from(vec![
-                Span::styled("URL: ", Style::default().fg(Color::Cyan)),
+                Span::styled("URL: ", label),
                 Span::raw(&interpolated.url),
             ]),
             Line::from(""),
         ];
 
+        if let Some(folder) = &interpolated.folder {
+            content.insert(
+                0,
+                Line::from(vec![
+                    Span::styled("Folder: ", label),
+                    Span::raw(folder.as_str()),
+                ]),
+            );
+        }
+
         // Headers
-        if !interpolated.headers.is_empty() {
-            content.push(Line::from(Span::styled(
-                "Headers:",
-                Style::default().fg(Color::Cyan),
-            )));
+        if !interpolated.headers.is_empty() || !interpolated.disabled_headers.is_empty() {
+            content.push(Line::from(Span::styled("Headers:", label)));
             for (key, value) in &interpolated.headers {
                 content.push(Line::from(format!("  {}: {}", key, value)));
             }
+            for (key, value) in &interpolated.disabled_headers {
+                content.push(Line::from(Span::styled(
+                    format!("  {}: {} (disabled)", key, value),
+                    theme.fg(theme.muted),
+                )));
+            }
             content.push(Line::from(""));
         }
 
         // Query parameters
-        if !interpolated.query.is_empty() {
-            content.push(Line::from(Span::styled(
-                "Query:",
-                Style::default().fg(Color::Cyan),
-            )));
+        if !interpolated.query.is_empty() || !interpolated.disabled_query.is_empty() {
+            content.push(Line::from(Span::styled("Query:", label)));
             for (key, value) in &interpolated.query {
                 content.push(Line::from(format!("  {}: {}", key, value)));
             }
+            for (key, value) in &interpolated.disabled_query {
+                content.push(Line::from(Span::styled(
+                    format!("  {}: {} (disabled)", key, value),
+                    theme.fg(theme.muted),
+                )));
+            }
             content.push(Line::from(""));
         }
 
         // Body
         if let Some(body) = &interpolated.body {
-            content.push(Line::from(Span::styled(
-                "Body:",
-                Style::default().fg(Color::Cyan),
-            )));
-            let body_str = body.to_string();
+            content.push(Line::from(Span::styled("Body:", label)));
+            let (body_str, format) = match body {
+                RequestBody::Json(value) => (
+                    serde_json::to_string_pretty(value).unwrap_or_default(),
+                    BodyFormat::Json,
+                ),
+                RequestBody::Text(text) => {
+                    let format = BodyFormat::detect(&interpolated.headers, text);
+                    (format.pretty_print(text), format)
+                }
+            };
             for line in body_str.lines().take(10) {
                 // Limit to first 10 lines
-                content.push(Line::from(format!("  {}", line)));
+                let mut line = format.highlight_line(line, theme);
+                line.spans.insert(0, Span::raw("  "));
+                content.push(line);
             }
             content.push(Line::from(""));
         }
 
         // Notes
         if let Some(notes) = &interpolated.notes {
-            content.push(Line::from(Span::styled(
-                "Notes:",
-                Style::default().fg(Color::Cyan),
-            )));
+            content.push(Line::from(Span::styled("Notes:", label)));
             content.push(Line::from(notes.as_str()));
         }
 
@@ -173,7 +260,7 @@ pub fn render_request_details(frame: &mut ratatui::Frame, area: Rect, state: &Ap
                     .title("Request Details")
                     .borders(Borders::ALL),
             )
-            .wrap(Wrap { trim: true });
+            .wrap(Wrap { trim: false });
 
         frame.render_widget(paragraph, area);
     } else {
@@ -186,41 +273,153 @@ pub fn render_request_details(frame: &mut ratatui::Frame, area: Rect, state: &Ap
     }
 }
 
+pub fn render_request_form(
+    frame: &mut ratatui::Frame,
+    area: Rect,
+    state: &AppState,
+    form: &RequestForm,
+) {
+    let theme = &state.theme;
+    let editing = matches!(state.mode, AppMode::FormEdit);
+    let label = |field: FormField, text: &'static str| {
+        let style = if form.focus == field {
+            theme.fg(theme.focus)
+        } else {
+            theme.fg(theme.accent)
+        };
+        Span::styled(text, style)
+    };
+    // Appends a cursor to the text being typed into
+    let text = |value: &str, active: bool| {
+        if active && editing {
+            Span::styled(format!("{}▏", value), theme.fg(theme.text))
+        } else if active {
+            Span::styled(value.to_string(), theme.selection())
+        } else {
+            Span::raw(value.to_string())
+        }
+    };
+
+    let mut content = vec![
+        Line::from(vec![
+            label(FormField::Method, "Method: "),
+            text(
+                &format!("◀ {} ▶", form.method),
+                form.focus == FormField::Method,
+            ),
+        ]),
+        Line::from(vec![
+            label(FormField::Url, "URL: "),
+            text(&form.url, form.focus == FormField::Url),
+        ]),
+        Line::from(""),
+    ];
+
+    for (field, title, rows) in [
+        (FormField::Headers, "Headers:", &form.headers),
+        (FormField::Query, "Query:", &form.query),
+    ] {
+        content.push(Line::from(label(field, title)));
+        if rows.is_empty() {
+            content.push(Line::from(Span::styled(
+                "  (none, press a to add)",
+                theme.fg(theme.muted),
+            )));
+        }
+        for (i, row) in rows.iter().enumerate() {
+            let selected = form.focus == field && form.row == i;
+            let dim = if row.enabled {
+                Style::default()
+            } else {
+                theme.fg(theme.muted)
+            };
+            content.push(Line::from(vec![
+                Span::styled(if row.enabled { "  [x] " } else { "  [ ] " }, dim),
+                text(&row.key, selected && !form.value_column).patch_style(dim),
+                Span::styled(": ", dim),
+                text(&row.value, selected && form.value_column).patch_style(dim),
+            ]));
+        }
+        content.push(Line::from(""));
+    }
+
+    content.push(Line::from(label(FormField::Body, "Body:")));
+    let body_focused = form.focus == FormField::Body;
+    let body_lines: Vec<&str> = form.body.split('\n').collect();
+    for (i, line) in body_lines.iter().enumerate() {
+        // The cursor sits at the end of the body
+        let active = body_focused && (i + 1 == body_lines.len() || !editing);
+        content.push(Line::from(vec![Span::raw("  "), text(line, active)]));
+    }
+
+    let title = if form.dirty {
+        format!("Edit '{}' (modified)", form.request.name)
+    } else {
+        format!("Edit '{}'", form.request.name)
+    };
+    let paragraph = Paragraph::new(content).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(theme.fg(theme.focus)),
+    );
+
+    frame.render_widget(paragraph, area);
+}
+
 pub fn render_response_pane(frame: &mut ratatui::Frame, area: Rect, state: &AppState) {
+    let theme = &state.theme;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3), Constraint::Min(0)])
         .split(area);
 
     // Response tabs
-    let tab_titles = vec!["Pretty", "Raw", "Headers", "Timeline"];
-    let selected_tab = match state.response_tab {
-        ResponseTab::Pretty => 0,
-        ResponseTab::Raw => 1,
-        ResponseTab::Headers => 2,
-        ResponseTab::Timeline => 3,
-    };
+    let selected_tab = state.response_tab.index();
 
-    let tabs = Tabs::new(tab_titles)
-        .block(Block::default().borders(Borders::ALL).title("Response"))
+    let mut title = vec![Span::raw("Response")];
+    if let Some(response) = &state.current_response {
+        title.extend([
+            Span::raw(" "),
+            Span::styled(
+                response.status.to_string(),
+                theme.fg(theme.status(response.status)),
+            ),
+            Span::raw(format!(" {}ms", response.timing.total_ms)),
+        ]);
+    }
+    let tabs = Tabs::new(RESPONSE_TABS)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Line::from(title)),
+        )
         .select(selected_tab)
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().fg(Color::Yellow));
+        .style(theme.fg(theme.text))
+        .highlight_style(theme.fg(theme.focus));
 
     frame.render_widget(tabs, chunks[0]);
 
     // Response content
-    if let Some(response) = &state.current_response {
+    let body = chunks[1];
+    let mut areas = state.screen_areas.get();
+    areas.response_tabs = chunks[0];
+    areas.response = body;
+    state.screen_areas.set(areas);
+    state
+        .response_viewport
+        .set((body.width.saturating_sub(2), body.height.saturating_sub(2)));
+    if state.current_response.is_some() {
         match state.response_tab {
-            ResponseTab::Pretty => render_pretty_response(frame, chunks[1], response, state),
-            ResponseTab::Raw => render_raw_response(frame, chunks[1], response),
-            ResponseTab::Headers => render_response_headers(frame, chunks[1], response),
-            ResponseTab::Timeline => render_response_timeline(frame, chunks[1], response),
+            ResponseTab::Pretty if state.shows_json_tree() => {
+                render_pretty_response(frame, body, state)
+            }
+            _ => render_response_text(frame, body, state),
         }
     } else if state.is_executing {
         let paragraph = Paragraph::new("Executing request...")
             .block(Block::default().borders(Borders::ALL))
-            .style(Style::default().fg(Color::Yellow));
+            .style(theme.fg(theme.focus));
         frame.render_widget(paragraph, chunks[1]);
     } else {
         let paragraph =
@@ -229,117 +428,312 @@ pub fn render_response_pane(frame: &mut ratatui::Frame, area: Rect, state: &AppS
     }
 }
 
-fn render_pretty_response(
-    frame: &mut ratatui::Frame,
-    area: Rect,
-    response: &crate::core::Response,
-    _state: &AppState,
-) {
-    let formatted = match serde_json::to_string_pretty(&response.body) {
-        Ok(pretty) => pretty,
-        Err(_) => response.body.to_string(),
+fn render_pretty_response(frame: &mut ratatui::Frame, area: Rect, state: &AppState) {
+    let focused = response_focused(state);
+    let nodes = state.json_tree_nodes();
+    let selected = state.selected_json_index();
+
+    // Only the rows that fit are rendered; the offset normally tracks the
+    // cursor already, but the pane may have shrunk since
+    let height = area.height.saturating_sub(2).max(1) as usize;
+    let offset = state.response_scroll[ResponseTab::Pretty.index()]
+        .min(selected)
+        .max((selected + 1).saturating_sub(height));
+    let end = nodes.len().min(offset + height);
+
+    let selected_path = state.json_tree_state.selected_path.as_deref().unwrap_or("");
+    let tree = JsonTreeComponent::new(
+        &state.json_tree_state.expanded_paths,
+        focused.then_some(selected_path),
+        &state.theme,
+    )
+    .with_search(&state.response_search);
+    let lines = tree.render_nodes(&nodes[offset.min(end)..end]);
+
+    let path = focused.then(|| crate::tui::display_json_path(selected_path));
+    let position = format!(" {}/{} ", selected + 1, nodes.len());
+    let block = response_block(state, path, position);
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Raw, Headers, Timeline and Request tabs: pre-wrapped lines, of which only the
+/// visible window is rendered
+fn render_response_text(frame: &mut ratatui::Frame, area: Rect, state: &AppState) {
+    let Some((text, format)) = state.response_text() else {
+        return;
     };
+    let lines = state.response_text_lines();
+    let width = area.width.saturating_sub(2) as usize;
+    let height = area.height.saturating_sub(2).max(1) as usize;
+    let offset =
+        state.response_scroll[state.response_tab.index()].min(lines.len().saturating_sub(height));
+    let end = lines.len().min(offset + height);
 
-    let lines: Vec<Line> = formatted.lines().map(Line::from).collect();
+    let search = Some(state.response_search.as_str());
+    let theme = &state.theme;
+    let visible: Vec<Line> = highlight_window(&text, format, theme, width, offset, end - offset)
+        .into_iter()
+        .map(|line| {
+            Line::from(
+                line.spans
+                    .into_iter()
+                    .flat_map(|span| {
+                        highlight_matches(span.content.into_owned(), span.style, search, theme)
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
 
-    let paragraph = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL))
-        .wrap(Wrap { trim: false });
+    let position = if lines.len() > height {
+        format!(" {}-{}/{} ", offset + 1, end, lines.len())
+    } else {
+        String::new()
+    };
+    let block = response_block(state, None, position);
 
-    frame.render_widget(paragraph, area);
+    frame.render_widget(Paragraph::new(visible).block(block), area);
 }
 
-fn render_raw_response(frame: &mut ratatui::Frame, area: Rect, response: &crate::core::Response) {
-    let raw_content = response.body.to_string();
+fn response_focused(state: &AppState) -> bool {
+    matches!(
+        state.mode,
+        AppMode::Response
+            | AppMode::ResponseSearch
+            | AppMode::ResponseQuery
+            | AppMode::SaveJsonValue
+            | AppMode::AddExtract
+    )
+}
 
-    let paragraph = Paragraph::new(raw_content)
-        .block(Block::default().borders(Borders::ALL))
-        .wrap(Wrap { trim: false });
+/// Border of the response body, with the JSON path and search on the left
+/// and the scroll position on the right
+fn response_block(state: &AppState, path: Option<String>, position: String) -> Block<'static> {
+    let theme = &state.theme;
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title_bottom(Line::from(position).right_aligned());
+    if response_focused(state) {
+        block = block.border_style(theme.fg(theme.focus));
+    }
 
-    frame.render_widget(paragraph, area);
+    let mut footer = path.unwrap_or_default();
+    if matches!(state.mode, AppMode::ResponseSearch) || !state.response_search.is_empty() {
+        if !footer.is_empty() {
+            footer.push_str("  ");
+        }
+        footer.push('/');
+        footer.push_str(&state.response_search);
+    }
+    if !footer.is_empty() {
+        block = block.title_bottom(Line::from(footer));
+    }
+
+    if matches!(state.mode, AppMode::ResponseQuery) || !state.response_query.is_empty() {
+        let mut query = vec![
+            Span::styled("query: ", theme.fg(theme.muted)),
+            Span::raw(state.response_query.clone()),
+        ];
+        if let Some(Err(error)) = &state.response_query_result {
+            query.push(Span::styled(
+                format!("  ✗ {}", error),
+                theme.fg(theme.error),
+            ));
+        }
+        block = block.title(Line::from(query));
+    }
+    block
 }
 
-fn render_response_headers(
-    frame: &mut ratatui::Frame,
-    area: Rect,
-    response: &crate::core::Response,
-) {
-    let mut content = vec![
-        Line::from(format!("Status: {}", response.status)),
-        Line::from(""),
-    ];
+pub fn render_status_bar(frame: &mut ratatui::Frame, area: Rect, state: &AppState) {
+    if state.mode == AppMode::Command {
+        render_command_line(frame, area, state);
+        return;
+    }
+
+    let keys = |action| state.keymap.keys(&state.mode, action);
+    let keybindings = match state.mode {
+        AppMode::Normal if state.is_executing => {
+            format!("Executing... Press {} to quit", keys(Action::Quit))
+        }
+        AppMode::Filter => "Type to filter, Enter: apply, Esc: cancel".to_string(),
+        AppMode::NewRequest | AppMode::RenameRequest => {
+            "Type a name, Enter: save, Esc: cancel".to_string()
+        }
+        AppMode::FormEdit => "Type to edit, Enter/Esc: done".to_string(),
+        AppMode::VariableEdit => {
+            "Tab: next field, Space: toggle saving, Enter: apply, Esc: cancel".to_string()
+        }
+        AppMode::SaveJsonValue | AppMode::AddExtract => {
+            "Type a variable name, Enter: save, Esc: cancel".to_string()
+        }
+        AppMode::ResponseSearch => "Type to search, Enter: find, Esc: cancel".to_string(),
+        AppMode::HistoryFilter => {
+            "Request name, 2xx/4xx/5xx, since:/until: 2026-10-01 or 7d, Enter: apply, Esc: clear"
+                .to_string()
+        }
+        AppMode::SaveResponse => {
+            "Type a path, Tab: body/headers/full response, Enter: save, Esc: cancel".to_string()
+        }
+        AppMode::ResponseQuery => {
+            "JSONPath ($.items[0].id) or jq (.items[] | .id), Enter: keep, Esc: clear".to_string()
+        }
+        ref mode => state.keymap.hints(mode).unwrap_or_default(),
+    };
 
-    for (key, value) in &response.headers {
-        content.push(Line::from(format!("{}: {}", key, value)));
+    let theme = &state.theme;
+    let mut spans = Vec::new();
+    if let Some(environment) = &state.environment {
+        spans.push(Span::styled(
+            format!("[{}] ", environment),
+            theme.fg(theme.environment),
+        ));
     }
+    spans.extend([
+        Span::styled(&state.status_message, theme.fg(theme.message)),
+        Span::raw(" | "),
+        Span::raw(keybindings),
+    ]);
+    let status_content = vec![Line::from(spans)];
+
+    let paragraph = Paragraph::new(status_content);
+    frame.render_widget(paragraph, area);
+}
+
+/// The `:` prompt, followed by completions or the usage of the typed command
+fn render_command_line(frame: &mut ratatui::Frame, area: Rect, state: &AppState) {
+    let theme = &state.theme;
+    let input = &state.command_input;
+    let name = input.split_whitespace().next().unwrap_or_default();
+
+    let hint = if !state.command_completions.is_empty() {
+        // Only the part being completed, e.g. the request names after `run `
+        let start = input.rfind(' ').map_or(0, |i| i + 1);
+        state
+            .command_completions
+            .iter()
+            .map(|c| c.get(start..).unwrap_or(c))
+            .collect::<Vec<_>>()
+            .join("  ")
+    } else if let Some((name, args, description)) = COMMANDS.iter().find(|(n, ..)| *n == name) {
+        format!(":{} {} - {}", name, args, description)
+    } else {
+        "Tab: complete, Enter: run, Esc: cancel".to_string()
+    };
+
+    let line = Line::from(vec![
+        Span::styled(":", theme.fg(theme.accent)),
+        Span::raw(input.as_str()),
+        Span::styled("▏", theme.fg(theme.focus)),
+        Span::raw("   "),
+        Span::styled(hint, theme.fg(theme.muted)),
+    ]);
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// Keys of the current mode, generated from the keymap
+pub fn render_help_modal(frame: &mut ratatui::Frame, state: &AppState) {
+    let area = centered_rect(60, 70, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let theme = &state.theme;
+    let help = state.keymap.help(&state.mode);
+    let width = help.iter().map(|(keys, _)| keys.chars().count()).max();
+    let mut content: Vec<Line> = help
+        .into_iter()
+        .map(|(keys, description)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}  ", keys, width = width.unwrap_or(0)),
+                    theme.fg(theme.accent),
+                ),
+                Span::raw(description),
+            ])
+        })
+        .collect();
+    content.push(Line::from(""));
+    content.push(Line::from(Span::styled(
+        "Press any key to close",
+        theme.fg(theme.muted),
+    )));
 
     let paragraph = Paragraph::new(content)
-        .block(Block::default().borders(Borders::ALL))
-        .wrap(Wrap { trim: true });
+        .block(Block::default().title("Keys").borders(Borders::ALL))
+        .style(theme.popup());
 
     frame.render_widget(paragraph, area);
 }
 
-fn render_response_timeline(
-    frame: &mut ratatui::Frame,
-    area: Rect,
-    response: &crate::core::Response,
-) {
+pub fn render_filter_modal(frame: &mut ratatui::Frame, state: &AppState) {
+    let area = centered_rect(60, 20, frame.area());
+
+    frame.render_widget(Clear, area);
+
     let content = vec![
-        Line::from(format!("Total Time: {}ms", response.timing.total_ms)),
-        Line::from(format!(
-            "Timestamp: {}",
-            response.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
-        )),
+        Line::from("Filter requests:"),
         Line::from(""),
-        Line::from("Detailed timing information not available"),
-        Line::from("(reqwest doesn't expose detailed timing)"),
+        Line::from(format!("> {}", state.filter_text)),
     ];
 
-    let paragraph = Paragraph::new(content).block(Block::default().borders(Borders::ALL));
+    let paragraph = Paragraph::new(content)
+        .block(Block::default().title("Filter").borders(Borders::ALL))
+        .style(state.theme.popup());
 
     frame.render_widget(paragraph, area);
 }
 
-pub fn render_status_bar(frame: &mut ratatui::Frame, area: Rect, state: &AppState) {
-    let keybindings = match state.mode {
-        AppMode::Normal => {
-            if state.is_executing {
-                "Executing... Press q to quit"
-            } else {
-                "Enter: run | e: edit | v: variables | h: history | /: filter | q: quit"
-            }
+pub fn render_name_modal(frame: &mut ratatui::Frame, state: &AppState) {
+    let area = centered_rect(60, 20, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let save_label;
+    let (title, label) = match state.mode {
+        AppMode::NewRequest => ("New Request", "Request name:"),
+        AppMode::SaveJsonValue => ("Save Value", "Variable name:"),
+        AppMode::AddExtract => ("Extract Variable", "Variable set on every run:"),
+        AppMode::SaveResponse => {
+            save_label = format!("File to write the {} to:", state.save_part.label());
+            ("Save Response", save_label.as_str())
         }
-        AppMode::Filter => "Type to filter, Enter: apply, Esc: cancel",
-        AppMode::Variables => "Esc: back to main",
-        AppMode::History => "↑↓: navigate, Enter: select, Esc: back",
-        AppMode::Command => "e: edit request, Esc: cancel",
+        _ => ("Rename Request", "Request name:"),
     };
+    let content = vec![
+        Line::from(label),
+        Line::from(""),
+        Line::from(format!("> {}", state.name_input)),
+    ];
 
-    let status_content = vec![Line::from(vec![
-        Span::styled(&state.status_message, Style::default().fg(Color::Green)),
-        Span::raw(" | "),
-        Span::raw(keybindings),
-    ])];
+    let paragraph = Paragraph::new(content)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(state.theme.popup());
 
-    let paragraph = Paragraph::new(status_content);
     frame.render_widget(paragraph, area);
 }
 
-pub fn render_filter_modal(frame: &mut ratatui::Frame, state: &AppState) {
+pub fn render_confirm_delete_modal(frame: &mut ratatui::Frame, state: &AppState) {
     let area = centered_rect(60, 20, frame.area());
 
     frame.render_widget(Clear, area);
 
+    let name = state
+        .get_current_request()
+        .map(|r| r.name.as_str())
+        .unwrap_or_default();
     let content = vec![
-        Line::from("Filter requests:"),
+        Line::from(format!("Delete request '{}'?", name)),
         Line::from(""),
-        Line::from(format!("> {}", state.filter_text)),
+        Line::from(vec![
+            Span::styled("y", state.theme.fg(state.theme.error)),
+            Span::raw(": delete   n/Esc: cancel"),
+        ]),
     ];
 
     let paragraph = Paragraph::new(content)
-        .block(Block::default().title("Filter").borders(Borders::ALL))
-        .style(Style::default().bg(Color::Black));
+        .block(Block::default().title("Delete").borders(Borders::ALL))
+        .style(state.theme.popup());
 
     frame.render_widget(paragraph, area);
 }
@@ -349,22 +743,96 @@ pub fn render_variables_modal(frame: &mut ratatui::Frame, state: &AppState) {
 
     frame.render_widget(Clear, area);
 
+    let theme = &state.theme;
     let variables = state.get_all_variables();
-    let mut content = vec![Line::from("Current Variables:")];
-    content.push(Line::from(""));
+    let items: Vec<ListItem> = variables
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let style = if i == state.variables_selected_index {
+                theme.selection()
+            } else {
+                Style::default()
+            };
 
-    if variables.is_empty() {
-        content.push(Line::from("No variables defined"));
+            ListItem::new(Line::from(vec![
+                Span::styled(&entry.key, theme.fg(theme.accent)),
+                Span::raw(" = "),
+                Span::raw(&entry.value),
+                Span::styled(format!("  [{}]", entry.source), theme.fg(theme.muted)),
+            ]))
+            .style(style)
+        })
+        .collect();
+
+    if items.is_empty() {
+        let paragraph = Paragraph::new("No variables defined. Press a to add one.")
+            .block(Block::default().title("Variables").borders(Borders::ALL))
+            .style(state.theme.popup());
+        frame.render_widget(paragraph, area);
     } else {
-        for (key, value) in variables {
-            content.push(Line::from(format!("{}: {}", key, value)));
-        }
+        let mut list_state = ListState::default();
+        list_state.select(Some(state.variables_selected_index));
+        let list = List::new(items)
+            .block(Block::default().title("Variables").borders(Borders::ALL))
+            .style(state.theme.popup());
+        frame.render_stateful_widget(list, area, &mut list_state);
+    }
+
+    if let Some(edit) = &state.variable_edit {
+        render_variable_edit_modal(frame, edit, theme);
     }
+}
+
+fn render_variable_edit_modal(
+    frame: &mut ratatui::Frame,
+    edit: &crate::tui::VariableEdit,
+    theme: &crate::ui::Theme,
+) {
+    use crate::tui::VariableEditField;
+
+    let area = centered_rect(60, 25, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let field = |focus: VariableEditField, label: &'static str, value: String| {
+        let focused = edit.focus == focus;
+        Line::from(vec![
+            Span::styled(
+                label,
+                theme.fg(if focused { theme.focus } else { theme.accent }),
+            ),
+            Span::raw(if focused && focus != VariableEditField::Persist {
+                format!("{}▏", value)
+            } else {
+                value
+            }),
+        ])
+    };
 
+    let content = vec![
+        field(VariableEditField::Key, "Name:  ", edit.key.clone()),
+        field(VariableEditField::Value, "Value: ", edit.value.clone()),
+        Line::from(""),
+        field(
+            VariableEditField::Persist,
+            "Keep:  ",
+            if edit.persist {
+                "[x] save for future sessions".to_string()
+            } else {
+                "[ ] this session only".to_string()
+            },
+        ),
+    ];
+
+    let title = if edit.is_new {
+        "New Variable"
+    } else {
+        "Edit Variable"
+    };
     let paragraph = Paragraph::new(content)
-        .block(Block::default().title("Variables").borders(Borders::ALL))
-        .style(Style::default().bg(Color::Black))
-        .wrap(Wrap { trim: true });
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(theme.popup());
 
     frame.render_widget(paragraph, area);
 }
@@ -374,31 +842,50 @@ pub fn render_history_modal(frame: &mut ratatui::Frame, state: &AppState) {
 
     frame.render_widget(Clear, area);
 
-    let items: Vec<ListItem> = state
-        .history
-        .entries
-        .iter()
-        .rev()
+    let theme = &state.theme;
+    let entries = state.visible_history();
+    let title = if state.history_filter.is_empty() {
+        "Response History".to_string()
+    } else {
+        format!(
+            "Response History - {} of {}",
+            entries.len(),
+            state.history.entries().len()
+        )
+    };
+    let items: Vec<ListItem> = entries
+        .into_iter()
         .enumerate()
         .map(|(i, entry)| {
             let style = if i == state.history_selected_index {
-                Style::default().bg(Color::Blue).fg(Color::White)
+                theme.selection()
             } else {
                 Style::default()
             };
 
+            let mark = if state.history_mark == Some(entry.id) {
+                "* "
+            } else {
+                "  "
+            };
             ListItem::new(vec![Line::from(vec![
+                Span::styled(mark, theme.fg(theme.focus)),
+                Span::styled(entry.short_id(), theme.fg(theme.muted)),
+                Span::raw(" "),
                 Span::raw(entry.created_at.format("%Y-%m-%d %H:%M:%S").to_string()),
                 Span::raw(" - "),
-                Span::raw(&entry.request_name),
+                Span::raw(
+                    // Show the request's current name in case it was renamed
+                    state
+                        .collection
+                        .iter()
+                        .find(|r| entry.is_for(r))
+                        .map_or(entry.request_name.as_str(), |r| r.name.as_str()),
+                ),
                 Span::raw(" ("),
                 Span::styled(
                     entry.response.status.to_string(),
-                    if entry.response.status >= 200 && entry.response.status < 300 {
-                        Style::default().fg(Color::Green)
-                    } else {
-                        Style::default().fg(Color::Red)
-                    },
+                    theme.fg(theme.status(entry.response.status)),
                 ),
                 Span::raw(")"),
             ])])
@@ -409,16 +896,192 @@ pub fn render_history_modal(frame: &mut ratatui::Frame, state: &AppState) {
     let mut list_state = ListState::default();
     list_state.select(Some(state.history_selected_index));
 
+    let mut block = Block::default().title(title).borders(Borders::ALL);
+    if state.mode == AppMode::HistoryFilter || !state.history_filter_text.is_empty() {
+        let mut filter = vec![
+            Span::styled("/", theme.fg(theme.muted)),
+            Span::raw(state.history_filter_text.clone()),
+        ];
+        if let Some(error) = &state.history_filter_error {
+            filter.push(Span::styled(
+                format!("  ✗ {}", error),
+                theme.fg(theme.error),
+            ));
+        }
+        block = block.title_bottom(Line::from(filter));
+    }
     let list = List::new(items)
+        .block(block)
+        .style(state.theme.popup())
+        .highlight_style(theme.selection());
+
+    frame.render_stateful_widget(list, area, &mut list_state);
+
+    let mut areas = state.screen_areas.get();
+    areas.history = area;
+    areas.history_offset = list_state.offset();
+    state.screen_areas.set(areas);
+}
+
+/// The request of a history entry as it was sent, and its response
+pub fn render_history_detail_modal(frame: &mut ratatui::Frame, state: &AppState) {
+    let Some(view) = &state.history_detail else {
+        return;
+    };
+    let area = centered_rect(90, 80, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let theme = &state.theme;
+    let height = area.height.saturating_sub(2).max(1) as usize;
+    view.page_height.set(height);
+    let offset = view.scroll.min(view.lines.len().saturating_sub(height));
+    let lines: Vec<Line> = view
+        .lines
+        .iter()
+        .skip(offset)
+        .take(height)
+        .map(|line| match line {
+            DetailLine::Heading(heading) => Line::styled(heading.clone(), theme.fg(theme.accent)),
+            DetailLine::Text(text) => Line::raw(text.clone()),
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
         .block(
             Block::default()
-                .title("Response History")
+                .title("History Entry")
+                .title_bottom(
+                    Line::from(format!("{}/{}", offset + 1, view.lines.len())).right_aligned(),
+                )
                 .borders(Borders::ALL),
         )
-        .style(Style::default().bg(Color::Black))
-        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White));
+        .style(theme.popup());
+    frame.render_widget(paragraph, area);
+}
 
-    frame.render_stateful_widget(list, area, &mut list_state);
+/// Status, header and body differences in three columns: what changed,
+/// the old side and the new side
+pub fn render_diff_modal(frame: &mut ratatui::Frame, state: &AppState) {
+    let Some(view) = &state.diff_view else {
+        return;
+    };
+    let area = centered_rect(90, 80, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let theme = &state.theme;
+    let diff = &view.diff;
+    let mut rows: Vec<Row> = Vec::new();
+    if let Some((old, new)) = diff.status {
+        rows.push(
+            Row::new(["status".to_string(), old.to_string(), new.to_string()])
+                .style(theme.fg(theme.diff_changed)),
+        );
+    }
+    for (name, change) in &diff.headers {
+        rows.push(diff_row(
+            format!("header {}", name),
+            change,
+            |value| value.clone(),
+            theme,
+        ));
+    }
+    for change in &diff.body {
+        rows.push(diff_row(
+            change.path.clone(),
+            &change.change,
+            serde_json::Value::to_string,
+            theme,
+        ));
+    }
+
+    let height = area.height.saturating_sub(3).max(1) as usize;
+    view.page_height.set(height);
+    let offset = view.scroll.min(rows.len().saturating_sub(height));
+    let title = if diff.is_empty() {
+        "Diff - no differences".to_string()
+    } else {
+        format!("Diff - {} differences", diff.len())
+    };
+
+    let table = Table::new(
+        rows.into_iter().skip(offset),
+        [
+            Constraint::Percentage(30),
+            Constraint::Percentage(35),
+            Constraint::Percentage(35),
+        ],
+    )
+    .header(
+        Row::new([
+            "".to_string(),
+            format!("- {}", view.old_label),
+            format!("+ {}", view.new_label),
+        ])
+        .style(theme.fg(theme.accent)),
+    )
+    .block(Block::default().title(title).borders(Borders::ALL))
+    .style(theme.popup());
+
+    frame.render_widget(table, area);
+}
+
+fn diff_row<'a, T>(
+    name: String,
+    change: &crate::core::Change<T>,
+    show: impl Fn(&T) -> String,
+    theme: &crate::ui::Theme,
+) -> Row<'a> {
+    Row::new([
+        format!("{} {}", change.marker(), name),
+        change.old_side().map(&show).unwrap_or_default(),
+        change.new_side().map(&show).unwrap_or_default(),
+    ])
+    .style(theme.fg(theme.diff(change)))
+}
+
+pub fn render_codegen_modal(frame: &mut ratatui::Frame, state: &AppState) {
+    let area = centered_rect(80, 70, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let Some(request) = state.get_current_request() else {
+        return;
+    };
+    let interpolated = state.interpolator.interpolate_request(request);
+    let code = crate::core::generate_code(&interpolated, state.codegen_language);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let titles: Vec<&str> = crate::core::CodeLanguage::ALL
+        .iter()
+        .map(|language| language.label())
+        .collect();
+    let selected = crate::core::CodeLanguage::ALL
+        .iter()
+        .position(|language| *language == state.codegen_language)
+        .unwrap_or(0);
+
+    let tabs = Tabs::new(titles)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Copy as code - {}", request.name)),
+        )
+        .select(selected)
+        .style(state.theme.popup())
+        .highlight_style(state.theme.fg(state.theme.focus));
+    frame.render_widget(tabs, chunks[0]);
+
+    let lines: Vec<Line> = code.lines().map(Line::from).collect();
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL))
+        .style(state.theme.popup());
+    frame.render_widget(paragraph, chunks[1]);
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
@@ -440,3 +1103,50 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{buffer::Buffer, widgets::Widget};
+
+    #[test]
+    fn test_response_tab_at() {
+        let area = Rect::new(10, 5, 50, 3);
+        let mut buffer = Buffer::empty(area);
+        Tabs::new(RESPONSE_TABS)
+            .block(Block::default().borders(Borders::ALL))
+            .render(area, &mut buffer);
+        let row: String = (area.x..area.right())
+            .map(|x| buffer[(x, area.y + 1)].symbol().to_string())
+            .collect();
+
+        let areas = ScreenAreas {
+            response_tabs: area,
+            ..Default::default()
+        };
+        for (i, title) in RESPONSE_TABS.iter().enumerate() {
+            let before = &row[..row.find(title).unwrap()];
+            let column = area.x + before.chars().count() as u16;
+            for x in column..column + title.len() as u16 {
+                assert_eq!(areas.response_tab_at(Position::new(x, 6)), Some(i));
+            }
+        }
+        // Borders and the title row of the block
+        assert_eq!(areas.response_tab_at(Position::new(10, 6)), None);
+        assert_eq!(areas.response_tab_at(Position::new(12, 5)), None);
+    }
+
+    #[test]
+    fn test_list_row_at() {
+        let areas = ScreenAreas {
+            requests: Rect::new(0, 0, 20, 10),
+            requests_offset: 4,
+            ..Default::default()
+        };
+        assert_eq!(areas.request_at(Position::new(3, 1)), Some(4));
+        assert_eq!(areas.request_at(Position::new(3, 8)), Some(11));
+        assert_eq!(areas.request_at(Position::new(3, 0)), None);
+        assert_eq!(areas.request_at(Position::new(3, 9)), None);
+        assert_eq!(areas.request_at(Position::new(25, 3)), None);
+    }
+}
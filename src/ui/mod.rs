@@ -1,7 +1,11 @@
 pub mod components;
+pub mod highlight;
 pub mod layouts;
 pub mod renderer;
+pub mod theme;
 
 pub use components::*;
+pub use highlight::*;
 pub use layouts::*;
 pub use renderer::*;
+pub use theme::*;
@@ -1,6 +1,7 @@
 use crate::tui::AppState;
 use color_eyre::Result;
 use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
@@ -10,7 +11,7 @@ use std::io;
 pub fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let terminal = Terminal::new(backend)?;
     Ok(terminal)
@@ -18,7 +19,11 @@ pub fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>> {
 
 pub fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
     terminal.show_cursor()?;
     Ok(())
 }
@@ -31,8 +36,11 @@ pub fn render_app(frame: &mut ratatui::Frame, state: &AppState) {
     // Render left pane (request list)
     render_request_list(frame, main_layout.left, state);
 
-    // Render main pane (request details)
-    render_request_details(frame, main_layout.main, state);
+    // Render main pane (request details, or the editor while it is open)
+    match &state.form {
+        Some(form) => render_request_form(frame, main_layout.main, state, form),
+        None => render_request_details(frame, main_layout.main, state),
+    }
 
     // Render right pane (response)
     render_response_pane(frame, main_layout.right, state);
@@ -43,8 +51,25 @@ pub fn render_app(frame: &mut ratatui::Frame, state: &AppState) {
     // Render modal dialogs if needed
     match state.mode {
         crate::tui::AppMode::Filter => render_filter_modal(frame, state),
-        crate::tui::AppMode::Variables => render_variables_modal(frame, state),
-        crate::tui::AppMode::History => render_history_modal(frame, state),
+        crate::tui::AppMode::Variables | crate::tui::AppMode::VariableEdit => {
+            render_variables_modal(frame, state)
+        }
+        crate::tui::AppMode::History | crate::tui::AppMode::HistoryFilter => {
+            render_history_modal(frame, state)
+        }
+        crate::tui::AppMode::HistoryDetail => render_history_detail_modal(frame, state),
+        crate::tui::AppMode::Diff => render_diff_modal(frame, state),
+        crate::tui::AppMode::Codegen => render_codegen_modal(frame, state),
+        crate::tui::AppMode::NewRequest
+        | crate::tui::AppMode::RenameRequest
+        | crate::tui::AppMode::SaveJsonValue
+        | crate::tui::AppMode::AddExtract
+        | crate::tui::AppMode::SaveResponse => render_name_modal(frame, state),
+        crate::tui::AppMode::ConfirmDelete => render_confirm_delete_modal(frame, state),
         _ => {}
     }
+
+    if state.show_help {
+        render_help_modal(frame, state);
+    }
 }
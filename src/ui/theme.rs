@@ -0,0 +1,339 @@
+use crate::core::HttpMethod;
+use crate::io::Config;
+use color_eyre::{Result, eyre::eyre};
+use ratatui::style::{Color, Style};
+use std::str::FromStr;
+
+/// Colors used by every widget. Built-in themes are `dark` (the default),
+/// `light` and `high-contrast`; user palettes in the config file start from
+/// one of them and override single colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Plain text in lists, tabs and modals
+    pub text: Color,
+    /// Disabled rows, hints and JSON `null`
+    pub muted: Color,
+    /// Field labels and variable names
+    pub accent: Color,
+    /// Focused borders and fields, the active tab and the spinner
+    pub focus: Color,
+    pub selection_fg: Color,
+    pub selection_bg: Color,
+    /// Background of modal dialogs
+    pub popup_bg: Color,
+    /// Status bar messages
+    pub message: Color,
+    /// Errors and destructive actions
+    pub error: Color,
+    /// Active environment in the status bar
+    pub environment: Color,
+    pub search_fg: Color,
+    pub search_bg: Color,
+    pub syntax_key: Color,
+    pub syntax_string: Color,
+    pub syntax_number: Color,
+    /// Booleans and other keywords
+    pub syntax_keyword: Color,
+    /// Markup tags, YAML list markers and tree icons
+    pub syntax_tag: Color,
+    pub method_get: Color,
+    pub method_post: Color,
+    pub method_put: Color,
+    pub method_patch: Color,
+    pub method_delete: Color,
+    /// HEAD and OPTIONS
+    pub method_other: Color,
+    /// 1xx and 2xx responses
+    pub status_success: Color,
+    pub status_redirect: Color,
+    pub status_client_error: Color,
+    pub status_server_error: Color,
+    pub diff_added: Color,
+    pub diff_removed: Color,
+    pub diff_changed: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub const BUILT_IN: &'static [&'static str] = &["dark", "light", "high-contrast"];
+
+    pub fn dark() -> Self {
+        Self {
+            text: Color::White,
+            muted: Color::DarkGray,
+            accent: Color::Cyan,
+            focus: Color::Yellow,
+            selection_fg: Color::White,
+            selection_bg: Color::Blue,
+            popup_bg: Color::Black,
+            message: Color::Green,
+            error: Color::Red,
+            environment: Color::Magenta,
+            search_fg: Color::Black,
+            search_bg: Color::Yellow,
+            syntax_key: Color::Cyan,
+            syntax_string: Color::Green,
+            syntax_number: Color::Magenta,
+            syntax_keyword: Color::Red,
+            syntax_tag: Color::Blue,
+            method_get: Color::Green,
+            method_post: Color::Yellow,
+            method_put: Color::Blue,
+            method_patch: Color::Magenta,
+            method_delete: Color::Red,
+            method_other: Color::White,
+            status_success: Color::Green,
+            status_redirect: Color::Cyan,
+            status_client_error: Color::Yellow,
+            status_server_error: Color::Red,
+            diff_added: Color::Green,
+            diff_removed: Color::Red,
+            diff_changed: Color::Yellow,
+        }
+    }
+
+    /// For terminals with a light background: no yellow or white text
+    pub fn light() -> Self {
+        Self {
+            text: Color::Black,
+            muted: Color::Gray,
+            accent: Color::Blue,
+            focus: Color::Magenta,
+            selection_fg: Color::White,
+            selection_bg: Color::Blue,
+            popup_bg: Color::White,
+            message: Color::Green,
+            error: Color::Red,
+            environment: Color::Magenta,
+            search_fg: Color::Black,
+            search_bg: Color::LightYellow,
+            syntax_key: Color::Blue,
+            syntax_string: Color::Green,
+            syntax_number: Color::Magenta,
+            syntax_keyword: Color::Red,
+            syntax_tag: Color::Cyan,
+            method_get: Color::Green,
+            method_post: Color::Rgb(0xaf, 0x5f, 0x00),
+            method_put: Color::Blue,
+            method_patch: Color::Magenta,
+            method_delete: Color::Red,
+            method_other: Color::Black,
+            status_success: Color::Green,
+            status_redirect: Color::Blue,
+            status_client_error: Color::Rgb(0xaf, 0x5f, 0x00),
+            status_server_error: Color::Red,
+            diff_added: Color::Green,
+            diff_removed: Color::Red,
+            diff_changed: Color::Rgb(0xaf, 0x5f, 0x00),
+        }
+    }
+
+    /// Bright colors on black, with the selection and search shown inverted
+    pub fn high_contrast() -> Self {
+        Self {
+            text: Color::White,
+            muted: Color::Gray,
+            accent: Color::LightCyan,
+            focus: Color::LightYellow,
+            selection_fg: Color::Black,
+            selection_bg: Color::White,
+            popup_bg: Color::Black,
+            message: Color::LightGreen,
+            error: Color::LightRed,
+            environment: Color::LightMagenta,
+            search_fg: Color::Black,
+            search_bg: Color::LightYellow,
+            syntax_key: Color::LightCyan,
+            syntax_string: Color::LightGreen,
+            syntax_number: Color::LightMagenta,
+            syntax_keyword: Color::LightRed,
+            syntax_tag: Color::LightBlue,
+            method_get: Color::LightGreen,
+            method_post: Color::LightYellow,
+            method_put: Color::LightBlue,
+            method_patch: Color::LightMagenta,
+            method_delete: Color::LightRed,
+            method_other: Color::White,
+            status_success: Color::LightGreen,
+            status_redirect: Color::LightCyan,
+            status_client_error: Color::LightYellow,
+            status_server_error: Color::LightRed,
+            diff_added: Color::LightGreen,
+            diff_removed: Color::LightRed,
+            diff_changed: Color::LightYellow,
+        }
+    }
+
+    /// A built-in theme by name
+    pub fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// The theme selected in the config: a user palette from `themes`, laid
+    /// over its `base`, or a built-in one
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let Some(name) = config.theme.as_deref() else {
+            return Ok(Self::default());
+        };
+        let Some(palette) = config.themes.get(name) else {
+            return Self::built_in(name).ok_or_else(|| {
+                eyre!(
+                    "unknown theme `{}` (built-in themes: {})",
+                    name,
+                    Self::BUILT_IN.join(", ")
+                )
+            });
+        };
+
+        let base = palette.base.as_deref().unwrap_or("dark");
+        let mut theme = Self::built_in(base)
+            .ok_or_else(|| eyre!("theme `{}`: unknown base theme `{}`", name, base))?;
+        let mut colors: Vec<_> = palette.colors.iter().collect();
+        colors.sort();
+        for (slot, value) in colors {
+            let color = Color::from_str(value)
+                .map_err(|_| eyre!("theme `{}`: invalid color `{}` for {}", name, value, slot))?;
+            *theme
+                .slot_mut(slot)
+                .ok_or_else(|| eyre!("theme `{}`: unknown color name `{}`", name, slot))? = color;
+        }
+        Ok(theme)
+    }
+
+    fn slot_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "accent" => &mut self.accent,
+            "focus" => &mut self.focus,
+            "selection_fg" => &mut self.selection_fg,
+            "selection_bg" => &mut self.selection_bg,
+            "popup_bg" => &mut self.popup_bg,
+            "message" => &mut self.message,
+            "error" => &mut self.error,
+            "environment" => &mut self.environment,
+            "search_fg" => &mut self.search_fg,
+            "search_bg" => &mut self.search_bg,
+            "syntax_key" => &mut self.syntax_key,
+            "syntax_string" => &mut self.syntax_string,
+            "syntax_number" => &mut self.syntax_number,
+            "syntax_keyword" => &mut self.syntax_keyword,
+            "syntax_tag" => &mut self.syntax_tag,
+            "method_get" => &mut self.method_get,
+            "method_post" => &mut self.method_post,
+            "method_put" => &mut self.method_put,
+            "method_patch" => &mut self.method_patch,
+            "method_delete" => &mut self.method_delete,
+            "method_other" => &mut self.method_other,
+            "status_success" => &mut self.status_success,
+            "status_redirect" => &mut self.status_redirect,
+            "status_client_error" => &mut self.status_client_error,
+            "status_server_error" => &mut self.status_server_error,
+            "diff_added" => &mut self.diff_added,
+            "diff_removed" => &mut self.diff_removed,
+            "diff_changed" => &mut self.diff_changed,
+            _ => return None,
+        })
+    }
+
+    pub fn fg(&self, color: Color) -> Style {
+        Style::default().fg(color)
+    }
+
+    pub fn selection(&self) -> Style {
+        Style::default().bg(self.selection_bg).fg(self.selection_fg)
+    }
+
+    pub fn popup(&self) -> Style {
+        Style::default().bg(self.popup_bg).fg(self.text)
+    }
+
+    pub fn search_match(&self) -> Style {
+        Style::default().bg(self.search_bg).fg(self.search_fg)
+    }
+
+    pub fn method(&self, method: &HttpMethod) -> Color {
+        match method {
+            HttpMethod::Get => self.method_get,
+            HttpMethod::Post => self.method_post,
+            HttpMethod::Put => self.method_put,
+            HttpMethod::Patch => self.method_patch,
+            HttpMethod::Delete => self.method_delete,
+            HttpMethod::Head | HttpMethod::Options => self.method_other,
+        }
+    }
+
+    pub fn diff<T>(&self, change: &crate::core::Change<T>) -> Color {
+        match change {
+            crate::core::Change::Added(_) => self.diff_added,
+            crate::core::Change::Removed(_) => self.diff_removed,
+            crate::core::Change::Changed(..) => self.diff_changed,
+        }
+    }
+
+    pub fn status(&self, status: u16) -> Color {
+        match status {
+            300..=399 => self.status_redirect,
+            400..=499 => self.status_client_error,
+            500.. => self.status_server_error,
+            _ => self.status_success,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> Config {
+        crate::io::parse_config(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_theme_from_config() {
+        assert_eq!(
+            Theme::from_config(&Config::default()).unwrap(),
+            Theme::dark()
+        );
+        assert_eq!(
+            Theme::from_config(&config("theme: light")).unwrap(),
+            Theme::light()
+        );
+
+        let theme = Theme::from_config(&config(
+            "theme: solarized\nthemes:\n  solarized:\n    base: light\n    accent: \"#268bd2\"\n    method_get: dark-gray\n",
+        ))
+        .unwrap();
+        assert_eq!(theme.accent, Color::Rgb(0x26, 0x8b, 0xd2));
+        assert_eq!(theme.method(&HttpMethod::Get), Color::DarkGray);
+        assert_eq!(theme.popup_bg, Theme::light().popup_bg);
+    }
+
+    #[test]
+    fn test_theme_errors() {
+        let error = |yaml: &str| Theme::from_config(&config(yaml)).unwrap_err().to_string();
+        assert!(error("theme: solar").contains("unknown theme `solar`"));
+        assert!(error("theme: t\nthemes:\n  t:\n    accent: nope\n").contains("invalid color"));
+        assert!(error("theme: t\nthemes:\n  t:\n    acent: red\n").contains("`acent`"));
+        assert!(error("theme: t\nthemes:\n  t:\n    base: sepia\n").contains("`sepia`"));
+    }
+
+    #[test]
+    fn test_status_colors() {
+        let theme = Theme::dark();
+        assert_eq!(theme.status(204), theme.status_success);
+        assert_eq!(theme.status(304), theme.status_redirect);
+        assert_eq!(theme.status(404), theme.status_client_error);
+        assert_eq!(theme.status(503), theme.status_server_error);
+    }
+}